@@ -1,64 +1,1117 @@
 use lib_es5e_core::{
-    action::attack::Attack,
-    combatant::{
-        config::CombatantConfig,
-        defences::save::SaveModifiers,
-        state::{Recharge, ResourceCfg, ResourceCosts},
+    action::multi::MultiAction,
+    attack::{
+        area::{Area, Shape, Targets},
+        save_based::SaveBasedAttack,
     },
 };
-use lib_es5e_core::{action::multi::MultiAction, attack::save_based::SaveBasedAttack};
 use lib_es5e_core::{
     action::negative_effect::negative_effect::NegativeEffect, attack::damage::DamageRoll,
 };
 use lib_es5e_core::{action::single::Execution, combatant::config::ActionType};
 use lib_es5e_core::{action::single::SingleAction, combatant::stats::CombatantStats};
+use lib_es5e_core::{
+    action::{
+        action::ActionSlot,
+        attack::Attack,
+        reaction::{Reaction, ReactionEffect, ReactionTrigger},
+        spell::Spell,
+    },
+    combat::{
+        action_selection::{LookaheadConfig, SelectionMode},
+        adventuring_day::{DayEncounter, Rest},
+        encounter::{Encounter, EncounterSettings, Side, SideShelters},
+        grid::{Grid, Position, SQUARE},
+        initiative::InitiativeRules,
+        lair::LairCfg,
+        lines::Line,
+        morale::Morale,
+        outcome::Objective,
+        reinforcements::{ArrivalTrigger, Reinforcement},
+    },
+    combatant::{
+        config::CombatantConfig,
+        defences::{
+            ability::AbilityModifiers,
+            cover::{Cover, Obscurement, Shelter},
+            save::SaveModifiers,
+        },
+        hit_dice::{HitDice, HitDicePolicy},
+        legendary::{LegendaryActionCfg, LegendaryCfg},
+        skill::Skill,
+        spell_slots::{PactSlots, SlotPreference, SpellSlots},
+        state::{Recharge, ResourceCfg, ResourceCosts, ResourceKey},
+    },
+};
 use lib_es5e_core::{
     combatant::state::ResourceCfgs,
+    targeting::Targeting,
     utils::save::{Save, SaveType},
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-pub fn load_combatants_from_file(file_path: &Path) -> Vec<CombatantConfig> {
-    let contents =
-        fs::read_to_string(file_path).expect(format!("{file_path:?} not found").as_str());
-    let values: Vec<CombatantDto> = serde_yaml::from_str(contents.as_str())
-        .expect(format!("Unable to parse {file_path:?}").as_str());
+mod derivation;
+mod error;
+mod import;
+mod library;
+mod validation;
+
+pub use error::{LoadError, Location, Problem};
+pub use validation::Warning;
+
+/// Conversions after loading rely on the validation of the loaded values
+const VALIDATED: &str = "values are validated when loading";
+/// Conversions after loading rely on the derivation of the values that were left out
+const DERIVED: &str = "values are derived when loading";
+
+/// Loads a combatant file, or imports the combatants of a JSON file, see [import_combatants]
+pub fn load_combatants_from_file(file_path: &Path) -> Result<Vec<CombatantConfig>, LoadError> {
+    if is_json(file_path) {
+        let (combatants, warnings) = import_combatants(file_path)?;
+        for warning in &warnings {
+            println!("Warning: {warning}");
+        }
+        println!(
+            "Combatants imported from {file_path:?}: {}",
+            combatants.len()
+        );
+        return Ok(combatants);
+    }
+    let values = read_combatants(file_path)?;
     let nr_combatants = values.len();
     println!("Combatants loaded from {file_path:?}: {nr_combatants}");
 
-    values.into_iter().map(|e| e.into()).collect()
+    Ok(values.into_iter().map(|e| e.into()).collect())
+}
+
+fn is_json(file_path: &Path) -> bool {
+    file_path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+}
+
+/// Combatants of a combatant file or imported ones, whose warnings are added to the given ones
+fn read_side(
+    file_path: &Path,
+    warnings: &mut Vec<Warning>,
+) -> Result<Vec<CombatantDto>, LoadError> {
+    if is_json(file_path) {
+        let (combatants, import_warnings) = import::read_imported(file_path)?;
+        warnings.extend(import_warnings);
+        return Ok(combatants);
+    }
+    read_combatants(file_path)
+}
+
+fn read_file(file_path: &Path) -> Result<String, LoadError> {
+    fs::read_to_string(file_path).map_err(|source| LoadError::Io {
+        file: file_path.to_path_buf(),
+        source,
+    })
+}
+
+fn parse_file<T: DeserializeOwned>(file_path: &Path) -> Result<T, LoadError> {
+    let contents = read_file(file_path)?;
+    serde_yaml::from_str(contents.as_str())
+        .map_err(|e| LoadError::yaml(&Location::file(file_path), e))
+}
+
+/// Validated combatants of a file, with templates resolved and counts expanded
+fn read_combatants(file_path: &Path) -> Result<Vec<CombatantDto>, LoadError> {
+    let mut combatants = vec![];
+    for entry in resolve_templates(file_path)? {
+        combatants.extend(read_entry(entry, file_path)?);
+    }
+    Ok(combatants)
+}
+
+/// Validated combatants of an entry whose templates are resolved, numbered by their count.
+/// Entries that are only templates have none.
+fn read_entry(mut entry: Value, file_path: &Path) -> Result<Vec<CombatantDto>, LoadError> {
+    let name = entry
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let location = Location::combatant(file_path, name);
+    library::expand_spells(&mut entry, &location)?;
+    let mut dto: CombatantDto =
+        serde_yaml::from_value(entry).map_err(|e| LoadError::yaml(&location, e))?;
+    if dto.template {
+        return Ok(vec![]);
+    }
+    dto.location = location;
+    // identical monsters share their name before they are numbered
+    dto.initiative_group.get_or_insert_with(|| dto.name.clone());
+    derivation::derive_stats(&mut dto)?;
+    validation::validate_combatant(&dto)?;
+    let count = dto.count;
+    Ok(numbered(dto, count))
+}
+
+/// Entries of a combatant file, in which every entry that extends another one
+/// has been merged with it
+fn resolve_templates(file_path: &Path) -> Result<Vec<Value>, LoadError> {
+    let entries: Vec<Value> = parse_file(file_path)?;
+    (0..entries.len())
+        .map(|idx| resolve_template(&entries, idx, file_path, &mut vec![]))
+        .collect()
+}
+
+/// Entry merged with the one it extends. `extending` holds the canonical file path and name of
+/// every entry whose base is being resolved, across files, to detect cycles.
+fn resolve_template(
+    entries: &[Value],
+    idx: usize,
+    file_path: &Path,
+    extending: &mut Vec<(PathBuf, String)>,
+) -> Result<Value, LoadError> {
+    let mut entry = library::with_monster(entries[idx].clone(), &Location::file(file_path))?;
+    let Some(extends) = entry.as_mapping_mut().and_then(|e| e.remove("extends")) else {
+        return Ok(entry);
+    };
+    let name = entry
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let location = Location::combatant(file_path, &name);
+    let key = (
+        fs::canonicalize(file_path).unwrap_or(file_path.to_path_buf()),
+        name.clone(),
+    );
+    if extending.contains(&key) {
+        return Err(LoadError::invalid(&location, Problem::CyclicExtends(name)));
+    }
+    let extends: ExtendsDto =
+        serde_yaml::from_value(extends).map_err(|e| LoadError::yaml(&location, e))?;
+    extending.push(key);
+    let mut base = match extends {
+        ExtendsDto::Name(name) => {
+            let base_idx = find_entry(entries, &name, &location)?;
+            resolve_template(entries, base_idx, file_path, extending)?
+        }
+        ExtendsDto::Library { file, name } => {
+            let library_path = file_path.parent().unwrap_or(Path::new("")).join(file);
+            let library: Vec<Value> = parse_file(&library_path)?;
+            let base_idx = find_entry(&library, &name, &Location::file(&library_path))?;
+            resolve_template(&library, base_idx, &library_path, extending)?
+        }
+    };
+    extending.pop();
+    // Whether an entry is a template and how often it appears are not inherited
+    if let Some(base) = base.as_mapping_mut() {
+        base.remove("template");
+        base.remove("count");
+    }
+    Ok(merge(base, entry))
+}
+
+fn find_entry(entries: &[Value], name: &str, location: &Location) -> Result<usize, LoadError> {
+    entries
+        .iter()
+        .position(|entry| entry.get("name").and_then(Value::as_str) == Some(name))
+        .ok_or_else(|| LoadError::invalid(location, Problem::UnknownCombatant(name.to_string())))
+}
+
+/// Overrides the fields of the base entry. Nested mappings such as the saves are merged field by field,
+/// everything else, including lists of actions, is replaced.
+fn merge(base: Value, overrides: Value) -> Value {
+    match (base, overrides) {
+        (Value::Mapping(mut base), Value::Mapping(overrides)) => {
+            for (key, value) in overrides {
+                let merged = match base.remove(&key) {
+                    Some(base_value) => merge(base_value, value),
+                    None => value,
+                };
+                base.insert(key, merged);
+            }
+            Value::Mapping(base)
+        }
+        (_, overrides) => overrides,
+    }
+}
+
+/// The given number of copies of a combatant, which are numbered if there is more than one of them
+fn numbered(dto: CombatantDto, count: usize) -> Vec<CombatantDto> {
+    if count == 1 {
+        return vec![dto];
+    }
+    (1..=count)
+        .map(|nr| CombatantDto {
+            name: format!("{} {nr}", dto.name),
+            ..dto.clone()
+        })
+        .collect()
+}
+
+/// Loads an encounter document, which defines both sides and the rules of the encounter.
+/// The files it refers to are relative to the document.
+pub fn load_encounter_from_file(file_path: &Path) -> Result<(String, Encounter), LoadError> {
+    let encounter = read_encounter(file_path)?;
+    println!(
+        "Encounter loaded from {file_path:?}: {} players, {} enemies",
+        encounter.players.len(),
+        encounter.enemies.len()
+    );
+
+    let into_configs = |side: Vec<CombatantDto>| -> Vec<CombatantConfig> {
+        side.into_iter().map(|dto| dto.into()).collect()
+    };
+    Ok((
+        encounter.name,
+        Encounter::new_with_settings(
+            into_configs(encounter.players),
+            into_configs(encounter.enemies),
+            encounter.lair,
+            encounter.settings,
+        ),
+    ))
+}
+
+/// Imports the combatants of a JSON file, either monsters in the structure of the 5e SRD API or of Open5e
+/// or actors exported from Foundry VTT, together with warnings about everything that could not be translated
+pub fn import_combatants(
+    file_path: &Path,
+) -> Result<(Vec<CombatantConfig>, Vec<Warning>), LoadError> {
+    let (combatants, warnings) = import::read_imported(file_path)?;
+    Ok((
+        combatants.into_iter().map(|dto| dto.into()).collect(),
+        warnings,
+    ))
+}
+
+/// Translates imported combatants into the contents of a combatant file, which can then be refined by hand
+pub fn imported_combatants_to_yaml(file_path: &Path) -> Result<(String, Vec<Warning>), LoadError> {
+    let (combatants, warnings) = import::read_imported(file_path)?;
+    let yaml = serde_yaml::to_string(&combatants).expect("combatants can be written as YAML");
+    Ok((yaml, warnings))
+}
+
+/// Checks the files of a fight between the players and the enemies without simulating it
+pub fn validate_fight(
+    players: &Path,
+    enemies: &Path,
+    lair: Option<&Path>,
+) -> Result<Vec<Warning>, LoadError> {
+    let mut warnings = vec![];
+    let players = read_side(players, &mut warnings)?;
+    let enemies = read_side(enemies, &mut warnings)?;
+    if let Some(lair) = lair {
+        read_lair(lair)?;
+    }
+    warnings.extend(validation::fight_warnings(&players, &enemies));
+    Ok(warnings)
+}
+
+/// Checks an encounter document and the files it refers to without simulating it
+pub fn validate_encounter(file_path: &Path) -> Result<Vec<Warning>, LoadError> {
+    let encounter = read_encounter(file_path)?;
+    Ok(validation::fight_warnings(
+        &encounter.players,
+        &encounter.enemies,
+    ))
+}
+
+/// Checks the players and every encounter of an adventuring day without simulating them
+pub fn validate_day(file_path: &Path, players: &Path) -> Result<Vec<Warning>, LoadError> {
+    let mut warnings = vec![];
+    let players = read_side(players, &mut warnings)?;
+    let party: Vec<_> = players.iter().map(|dto| dto.name.clone()).collect();
+    let (day, _) = read_day(file_path)?;
+    for (encounter, _) in read_day_encounters(day, file_path, &party)? {
+        warnings.extend(validation::fight_warnings(&players, &encounter.enemies));
+    }
+    Ok(warnings)
+}
+
+struct LoadedEncounter {
+    name: String,
+    players: Vec<CombatantDto>,
+    enemies: Vec<CombatantDto>,
+    lair: Option<LairCfg>,
+    settings: EncounterSettings,
+}
+
+fn read_encounter(file_path: &Path) -> Result<LoadedEncounter, LoadError> {
+    let encounter: EncounterDto = parse_file(file_path)?;
+    into_encounter(encounter, file_path, None)
+}
+
+/// Encounter whose files are relative to the given one. The players of an adventuring day are its
+/// party, which is given instead of players of the encounter.
+fn into_encounter(
+    encounter: EncounterDto,
+    file_path: &Path,
+    party: Option<&[String]>,
+) -> Result<LoadedEncounter, LoadError> {
+    let location = Location::file(file_path);
+    match party {
+        Some(_) if !encounter.players.is_empty() => {
+            return Err(LoadError::invalid(&location, Problem::PlayersInDay));
+        }
+        None if encounter.players.is_empty() => {
+            return Err(LoadError::invalid(
+                &location,
+                Problem::MissingField("players"),
+            ));
+        }
+        _ => {}
+    }
+    let lair = match encounter.lair {
+        Some(lair) => Some(read_lair_entry(lair, file_path)?),
+        None => None,
+    };
+    let mut reinforcements = vec![];
+    for reinforcement in encounter.reinforcements {
+        let combatants = into_side(reinforcement.combatants, file_path, encounter.targeting)?;
+        reinforcements.push(Reinforcement {
+            side: reinforcement.side,
+            trigger: reinforcement.trigger,
+            combatants: combatants.into_iter().map(CombatantConfig::from).collect(),
+        });
+    }
+    let players = into_side(encounter.players, file_path, encounter.targeting)?;
+    let enemies = into_side(encounter.enemies, file_path, encounter.targeting)?;
+    if let Some(objective) = &encounter.objective {
+        let names = |side: &[CombatantDto]| side.iter().map(|dto| dto.name.clone()).collect();
+        let players = party.map_or_else(|| names(&players), <[String]>::to_vec);
+        validate_objective(
+            objective,
+            &players,
+            &names(&enemies),
+            &reinforcements,
+            file_path,
+        )?;
+    }
+    Ok(LoadedEncounter {
+        name: encounter.name,
+        players,
+        enemies,
+        lair,
+        settings: EncounterSettings {
+            round_limit: encounter.round_limit,
+            objective: encounter.objective,
+            surprised: encounter.surprised,
+            ambush: encounter.ambush,
+            initiative: encounter.initiative,
+            grid: encounter.grid,
+            shelter: encounter.shelter,
+            reinforcements,
+        },
+    })
+}
+
+/// Checks that the combatant the objective refers to fights on the side it is expected on,
+/// possibly as a reinforcement
+fn validate_objective(
+    objective: &Objective,
+    players: &[String],
+    enemies: &[String],
+    reinforcements: &[Reinforcement],
+    file_path: &Path,
+) -> Result<(), LoadError> {
+    let (name, side, side_names) = match objective {
+        Objective::Survive(_) => return Ok(()),
+        Objective::Kill(name) => (name, Side::Enemies, enemies),
+        Objective::Protect(name) => (name, Side::Players, players),
+    };
+    let arrives = reinforcements
+        .iter()
+        .filter(|r| r.side == side)
+        .flat_map(|r| &r.combatants)
+        .any(|c| &c.name == name);
+    if arrives || side_names.contains(name) {
+        return Ok(());
+    }
+    Err(LoadError::invalid(
+        &Location::file(file_path),
+        Problem::UnknownCombatant(name.clone()),
+    ))
+}
+
+fn into_side(
+    entries: Vec<SideEntryDto>,
+    file_path: &Path,
+    targeting: Targeting,
+) -> Result<Vec<CombatantDto>, LoadError> {
+    let dir = file_path.parent().unwrap_or(Path::new(""));
+    let mut combatants = vec![];
+    for entry in entries {
+        match entry {
+            SideEntryDto::Reference(ReferenceDto { file, name, count }) => {
+                let path = dir.join(file);
+                let mut selected = vec![];
+                for mut entry in resolve_templates(&path)? {
+                    let entry_name = entry.get("name").and_then(Value::as_str);
+                    if name.as_ref().is_some_and(|name| entry_name != Some(name)) {
+                        continue;
+                    }
+                    if let (Some(count), Some(entry)) = (count, entry.as_mapping_mut()) {
+                        entry.insert(Value::from("count"), Value::from(count));
+                    }
+                    selected.extend(read_entry(entry, &path)?);
+                }
+                if let Some(name) = name.filter(|_| selected.is_empty()) {
+                    let location = Location::file(&path);
+                    return Err(LoadError::invalid(
+                        &location,
+                        Problem::UnknownCombatant(name),
+                    ));
+                }
+                combatants.extend(selected);
+            }
+            SideEntryDto::Inline(entry) => {
+                let entry = library::with_monster(entry, &Location::file(file_path))?;
+                combatants.extend(read_entry(entry, file_path)?);
+            }
+        }
+    }
+    for dto in combatants.iter_mut() {
+        dto.targeting.get_or_insert(targeting);
+    }
+    Ok(combatants)
+}
+
+pub fn load_lair_from_file(file_path: &Path) -> Result<LairCfg, LoadError> {
+    let lair = read_lair(file_path)?;
+    let nr_actions = lair.actions.len();
+    println!("Lair actions loaded from {file_path:?}: {nr_actions}");
+
+    Ok(lair)
+}
+
+fn read_lair(file_path: &Path) -> Result<LairCfg, LoadError> {
+    let lair: LairDto = parse_file(file_path)?;
+    into_lair(lair, &Location::file(file_path))
+}
+
+/// Lair actions given in place or in a file relative to the file that refers to them
+fn read_lair_entry(entry: LairEntryDto, file_path: &Path) -> Result<LairCfg, LoadError> {
+    match entry {
+        LairEntryDto::File(file) => {
+            read_lair(&file_path.parent().unwrap_or(Path::new("")).join(file))
+        }
+        LairEntryDto::Inline(lair) => into_lair(lair, &Location::file(file_path)),
+    }
+}
+
+fn into_lair(lair: LairDto, location: &Location) -> Result<LairCfg, LoadError> {
+    for action in &lair.actions {
+        validation::validate_actions(&action.actions, &location.action(&action.name))?;
+    }
+    Ok(lair.into())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncounterDto {
+    #[serde(default)]
+    pub name: String,
+    /// Left out in the encounters of an adventuring day, which are fought by its party
+    #[serde(default)]
+    pub players: Vec<SideEntryDto>,
+    pub enemies: Vec<SideEntryDto>,
+    /// Lair actions, either the path of a lair file or `{actions: [...]}` in place
+    #[serde(default)]
+    pub lair: Option<LairEntryDto>,
+    #[serde(default)]
+    pub surprised: Option<Side>,
+    /// Side lying in ambush, whose Stealth checks surprise the enemies that fail to notice them
+    #[serde(default)]
+    pub ambush: Option<Side>,
+    /// `individual`, `group` for identical monsters sharing their roll, or `side` for a single roll per side
+    #[serde(default)]
+    pub initiative: InitiativeRules,
+    /// Number of rounds after which the fight ends in a draw
+    #[serde(default)]
+    pub round_limit: Option<usize>,
+    /// `!survive 5` for holding out 5 rounds, `!kill <enemy>` or `!protect <player>`
+    #[serde(default)]
+    pub objective: Option<Objective>,
+    /// Targeting of the combatants that do not choose their own
+    #[serde(default)]
+    pub targeting: Targeting,
+    /// Grid on which the combatants move, e.g. `grid: {distance: 60}` for sides starting 60 feet apart
+    #[serde(default)]
+    pub grid: Option<Grid>,
+    /// Cover and obscurement of each side, e.g. `shelter: {enemies: {cover: half, obscured: lightly}}`
+    /// Cover hinders melee attacks as well, unless the side sets `melee_steps_around: true`
+    #[serde(default)]
+    pub shelter: SideShelters,
+    #[serde(default)]
+    pub reinforcements: Vec<ReinforcementDto>,
 }
 
+/// Combatants that join a side of the encounter in progress
 #[derive(Debug, Serialize, Deserialize)]
+pub struct ReinforcementDto {
+    pub side: Side,
+    /// `!round 3`, `boss_bloodied` or `first_down`, checked at the start of every round
+    pub trigger: ArrivalTrigger,
+    pub combatants: Vec<SideEntryDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(try_from = "serde_yaml::Value")]
+pub enum SideEntryDto {
+    /// Combatants from another file, either all of them or only the one with the given name
+    Reference(ReferenceDto),
+    /// Combatant defined in place, which may refer to a monster of the library
+    Inline(Value),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReferenceDto {
+    file: String,
+    /// Name of the entry in the file, before its combatants are numbered
+    #[serde(default)]
+    name: Option<String>,
+    /// Number of combatants of each selected entry, instead of the count given in the file
+    #[serde(default)]
+    count: Option<usize>,
+}
+
+// Untagged enums lose the YAML tags of the actions, so entries are told apart by hand
+impl TryFrom<serde_yaml::Value> for SideEntryDto {
+    type Error = serde_yaml::Error;
+
+    fn try_from(value: serde_yaml::Value) -> Result<Self, Self::Error> {
+        if value.get("file").is_some() {
+            serde_yaml::from_value(value).map(SideEntryDto::Reference)
+        } else {
+            Ok(SideEntryDto::Inline(value))
+        }
+    }
+}
+
+/// Lair actions of an encounter
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(try_from = "serde_yaml::Value")]
+pub enum LairEntryDto {
+    /// Path of a lair file
+    File(String),
+    /// Lair actions defined in place
+    Inline(LairDto),
+}
+
+impl TryFrom<serde_yaml::Value> for LairEntryDto {
+    type Error = serde_yaml::Error;
+
+    fn try_from(value: serde_yaml::Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::String(file) => Ok(LairEntryDto::File(file)),
+            value => serde_yaml::from_value(value).map(LairEntryDto::Inline),
+        }
+    }
+}
+
+fn default_count() -> usize {
+    1
+}
+
+/// Loads the encounters of an adventuring day fought by the given party. The files they refer to
+/// are relative to the day's file.
+pub fn load_day_from_file(
+    file_path: &Path,
+    party: &[CombatantConfig],
+) -> Result<(Vec<DayEncounter>, HitDicePolicy), LoadError> {
+    let (day, _) = read_day(file_path)?;
+    let hit_dice_policy = day.hit_dice_policy;
+    let party: Vec<_> = party.iter().map(|player| player.name.clone()).collect();
+    let encounters = read_day_encounters(day, file_path, &party)?;
+    println!("Encounters loaded from {file_path:?}: {}", encounters.len());
+
+    Ok((
+        encounters
+            .into_iter()
+            .map(|(encounter, rest_after)| DayEncounter {
+                enemies: encounter
+                    .enemies
+                    .into_iter()
+                    .map(|dto| dto.into())
+                    .collect(),
+                lair: encounter.lair,
+                settings: encounter.settings,
+                rest_after,
+            })
+            .collect(),
+        hit_dice_policy,
+    ))
+}
+
+/// Encounters of an adventuring day, each with the rest taken after it
+fn read_day_encounters(
+    day: DayDto,
+    file_path: &Path,
+    party: &[String],
+) -> Result<Vec<(LoadedEncounter, Rest)>, LoadError> {
+    let dir = file_path.parent().unwrap_or(Path::new(""));
+    let mut encounters = vec![];
+    for entry in day.encounters {
+        let encounter = match entry.encounter {
+            EncounterEntryDto::File(file) => {
+                let path = dir.join(file);
+                into_encounter(parse_file(&path)?, &path, Some(party))?
+            }
+            EncounterEntryDto::Inline(encounter) => {
+                into_encounter(*encounter, file_path, Some(party))?
+            }
+        };
+        encounters.push((encounter, entry.rest));
+    }
+    Ok(encounters)
+}
+
+fn read_day(file_path: &Path) -> Result<(DayDto, PathBuf), LoadError> {
+    let day: DayDto = parse_file(file_path)?;
+    if let HitDicePolicy::HealTo(fraction) = day.hit_dice_policy {
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(LoadError::invalid(
+                &Location::file(file_path),
+                Problem::InvalidChance(fraction),
+            ));
+        }
+    }
+    let dir = file_path.parent().unwrap_or(Path::new("")).to_path_buf();
+    Ok((day, dir))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DayDto {
+    pub encounters: Vec<DayEncounterDto>,
+    /// How many hit dice the players spend on short rests
+    #[serde(default)]
+    pub hit_dice_policy: HitDicePolicy,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DayEncounterDto {
+    /// Either the path of an encounter file or the encounter in place, both without players
+    pub encounter: EncounterEntryDto,
+    /// Rest taken after the encounter
+    #[serde(default)]
+    pub rest: Rest,
+}
+
+/// Encounter of an adventuring day
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(try_from = "serde_yaml::Value")]
+pub enum EncounterEntryDto {
+    /// Path of an encounter file
+    File(String),
+    /// Encounter defined in place
+    Inline(Box<EncounterDto>),
+}
+
+impl TryFrom<serde_yaml::Value> for EncounterEntryDto {
+    type Error = serde_yaml::Error;
+
+    fn try_from(value: serde_yaml::Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::String(file) => Ok(EncounterEntryDto::File(file)),
+            value => serde_yaml::from_value(value).map(EncounterEntryDto::Inline),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LairDto {
+    pub actions: Vec<LairActionDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LairActionDto {
+    pub name: String,
+    pub actions: Vec<ActionDto>,
+}
+
+impl From<LairDto> for LairCfg {
+    fn from(dto: LairDto) -> Self {
+        Self {
+            actions: dto
+                .actions
+                .into_iter()
+                .map(|x| into_multi_action(x.actions))
+                .collect(),
+        }
+    }
+}
+
+/// Combatant entry that is based on another one, either from the same file or from a library file
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum ExtendsDto {
+    Name(String),
+    Library { file: String, name: String },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct CombatantDto {
     pub name: String,
+    /// Number of identical combatants, which get numbered names
+    #[serde(default = "default_count")]
+    pub count: usize,
+    /// Entries that are only extended by others do not take part in fights
+    #[serde(default)]
+    pub template: bool,
     pub hp: u32,
     pub ac: i16,
-    pub init: i16,
-    pub saves: SaveModifiersDto,
+    /// Derived from the dexterity modifier if not given
+    #[serde(default)]
+    pub init: Option<i16>,
+    /// Flat bonus added to the initiative, e.g. 5 for the Alert feat
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub init_bonus: i16,
+    /// Rolls initiative with advantage, e.g. due to Feral Instinct
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub init_advantage: bool,
+    /// Combatants of the same group share their roll in group initiative. Defaults to the name,
+    /// so that the numbered copies of a combatant form a group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initiative_group: Option<String>,
+    /// Derived from the ability modifiers and save proficiencies if not given
+    #[serde(default)]
+    pub saves: Option<SaveModifiersDto>,
     pub actions: ActionSelectionDto,
+    #[serde(default)]
+    pub decision: DecisionDto,
+    #[serde(default)]
+    pub reactions: Vec<ReactionDto>,
+    #[serde(default)]
+    pub legendary: LegendaryDto,
+    /// Number of spell slots per level, starting with 1st level
+    #[serde(default)]
+    pub spell_slots: Vec<u32>,
+    #[serde(default)]
+    pub pact_slots: Option<PactSlots>,
+    #[serde(default)]
+    pub slot_preference: SlotPreference,
+    /// Resources shared by several actions, e.g. ki points or channel divinity
+    #[serde(default)]
+    pub resources: BTreeMap<String, ResourceDto>,
+    #[serde(default)]
+    pub abilities: AbilityModifiersDto,
+    /// Ability scores, from which the ability modifiers are derived instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ability_scores: Option<AbilityScoresDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proficiency_bonus: Option<i16>,
+    /// Character level, from which the proficiency bonus is derived if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<u32>,
+    /// Challenge rating, from which the proficiency bonus is derived if not given, e.g. 0.25
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cr: Option<f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub save_proficiencies: Vec<SaveType>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skill_proficiencies: Vec<Skill>,
+    /// Skills that add twice the proficiency bonus
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expertise: Vec<Skill>,
+    /// Skill modifiers that are not derived, e.g. those of a stat block
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub skills: BTreeMap<Skill, i16>,
+    /// Ability from which the spell save DC and spell attack bonus are derived
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spellcasting: Option<SaveType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spell_save_dc: Option<i16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spell_attack: Option<i16>,
+    /// Hit dice spent on short rests, e.g. "8d10"
+    #[serde(default)]
+    pub hit_dice: Option<String>,
+    #[serde(default)]
+    pub targeting: Option<Targeting>,
+    /// Walking speed in feet, 30 if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<u32>,
+    /// Square to start on in encounters on a grid, e.g. `{x: 0, y: 6}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+    /// Line to fight in, `front` or `back`. Melee attacks only reach the back line once the front line is down.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<Line>,
+    /// Spends its action on slipping past the enemy front line, provoking opportunity attacks from
+    /// it, to reach the enemy back line in melee
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub slip_past: bool,
+    /// Cover against attacks and Dexterity saves, `half`, `three_quarters` or `total`, overriding
+    /// that of its side
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover: Option<Cover>,
+    /// `lightly` or `heavily`, overriding the obscurement of its side. Attacks against heavily
+    /// obscured combatants have disadvantage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obscured: Option<Obscurement>,
+    /// Caught by surprise at the start of the fight, losing its first turn and reactions until then
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub surprised: bool,
+    /// When the combatant flees or surrenders, e.g. `morale: {hp_below: 0.5, save_dc: 10, rout: surrender}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub morale: Option<Morale>,
+    /// Leads its side, whose combatants with `leader_down` morale may lose heart once it is down
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub leader: bool,
+    /// Where the combatant is defined, for reporting problems
+    #[serde(skip)]
+    pub location: Location,
 }
 
 impl From<CombatantDto> for CombatantConfig {
     fn from(dto: CombatantDto) -> Self {
-        let (actions, resources) = get_action_selection_and_resources(dto.actions);
+        let slots = SpellSlots {
+            per_level: dto.spell_slots,
+            pact: dto.pact_slots,
+            preference: dto.slot_preference,
+        };
+        let (actions, resources) = get_action_selection_and_resources(
+            dto.actions,
+            &slots,
+            into_named_resources(dto.resources),
+        );
+        let reactions = dto
+            .reactions
+            .into_iter()
+            .flat_map(|r| into_reactions(r, &slots, &resources))
+            .collect();
         Self {
+            name: dto.name,
             resources,
             actions,
             stats: CombatantStats {
                 max_hp: dto.hp,
                 ac: dto.ac,
-                initiative: dto.init,
-                saves: dto.saves.into(),
+                initiative: dto.init.expect(DERIVED),
+                initiative_advantage: dto.init_advantage,
+                saves: dto.saves.expect(DERIVED).into(),
+                abilities: dto.abilities.into(),
+                hit_dice: dto
+                    .hit_dice
+                    .map(|hit_dice| HitDice::from_str(hit_dice.as_str()).expect(VALIDATED)),
+                skills: dto.skills,
+                speed: dto.speed,
             },
+            selection_mode: dto.decision.into(),
+            reactions,
+            legendary: dto.legendary.into(),
+            targeting: dto.targeting.unwrap_or_default(),
+            position: dto.position,
+            line: dto.line.unwrap_or_default(),
+            slip_past: dto.slip_past,
+            shelter: Shelter {
+                cover: dto.cover,
+                obscured: dto.obscured,
+                ..Default::default()
+            },
+            surprised: dto.surprised,
+            initiative_group: dto.initiative_group,
+            morale: dto.morale,
+            leader: dto.leader,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub enum DecisionDto {
+    #[default]
+    FirstAvailable,
+    /// Simulates each option several times and picks the best one. Slow, intended for boss monsters.
+    Lookahead { rollouts: usize, rounds: usize },
+}
+
+impl From<DecisionDto> for SelectionMode {
+    fn from(decision: DecisionDto) -> Self {
+        match decision {
+            DecisionDto::FirstAvailable => SelectionMode::FirstAvailable,
+            DecisionDto::Lookahead { rollouts, rounds } => {
+                SelectionMode::Lookahead(LookaheadConfig { rollouts, rounds })
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LegendaryDto {
+    pub points: u32,
+    pub resistances: u32,
+    pub actions: Vec<LegendaryActionDto>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LegendaryActionDto {
+    pub cost: u32,
+    pub actions: Vec<ActionDto>,
+}
+
+impl From<LegendaryDto> for LegendaryCfg {
+    fn from(dto: LegendaryDto) -> Self {
+        Self {
+            points: dto.points,
+            resistances: dto.resistances,
+            actions: dto
+                .actions
+                .into_iter()
+                .map(|x| LegendaryActionCfg {
+                    cost: x.cost,
+                    action: into_multi_action(x.actions),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReactionDto {
+    pub name: String,
+    pub trigger: ReactionTrigger,
+    pub effect: ReactionEffectDto,
+    /// Level of the spell cast as reaction, if any, which then costs a spell slot
+    #[serde(default)]
+    pub spell_level: Option<u8>,
+    /// Charges of named resources spent on the reaction
+    #[serde(default)]
+    pub cost: BTreeMap<String, u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ReactionEffectDto {
+    AcBonus {
+        bonus: i16,
+        #[serde(default)]
+        until_turn_start: bool,
+    },
+    HalveDamage,
+    ReduceDamage {
+        amount: String,
+    },
+    Counter {
+        level: u8,
+        ability_modifier: i16,
+    },
+    Strike {
+        action: ActionDto,
+    },
+}
+
+/// A reaction spell turns into one reaction per slot it can be cast with, in order of preference
+fn into_reactions(dto: ReactionDto, slots: &SpellSlots, resources: &ResourceCfgs) -> Vec<Reaction> {
+    let effect = ReactionEffect::from(dto.effect);
+    let resource_cost = into_named_costs(dto.cost, resources);
+    match dto.spell_level {
+        Some(level) if level > 0 => slots
+            .usable_for(level)
+            .into_iter()
+            .map(|(key, cast_level)| Reaction {
+                trigger: dto.trigger,
+                effect: match &effect {
+                    // a higher slot automatically counters higher level spells
+                    ReactionEffect::Counter {
+                        ability_modifier, ..
+                    } => ReactionEffect::Counter {
+                        level: cast_level,
+                        ability_modifier: *ability_modifier,
+                    },
+                    effect => effect.clone(),
+                },
+                resource_cost: {
+                    let mut resource_cost = resource_cost.clone();
+                    resource_cost.insert(key, 1);
+                    resource_cost
+                },
+            })
+            .collect(),
+        _ => vec![Reaction {
+            trigger: dto.trigger,
+            effect,
+            resource_cost,
+        }],
+    }
+}
+
+impl From<ReactionEffectDto> for ReactionEffect {
+    fn from(effect: ReactionEffectDto) -> Self {
+        match effect {
+            ReactionEffectDto::AcBonus {
+                bonus,
+                until_turn_start,
+            } => ReactionEffect::AcBonus {
+                bonus,
+                until_turn_start,
+            },
+            ReactionEffectDto::HalveDamage => ReactionEffect::HalveDamage,
+            ReactionEffectDto::ReduceDamage { amount } => ReactionEffect::ReduceDamage(
+                DamageRoll::from_str(amount.as_str()).expect(VALIDATED),
+            ),
+            ReactionEffectDto::Counter {
+                level,
+                ability_modifier,
+            } => ReactionEffect::Counter {
+                level,
+                ability_modifier,
+            },
+            ReactionEffectDto::Strike { action } => {
+                ReactionEffect::Strike(SingleAction::from(action).execution)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AbilityModifiersDto {
+    pub str: i16,
+    pub dex: i16,
+    pub con: i16,
+    pub int: i16,
+    pub wis: i16,
+    pub cha: i16,
+}
+
+impl From<AbilityModifiersDto> for AbilityModifiers {
+    fn from(abilities: AbilityModifiersDto) -> Self {
+        AbilityModifiers::new(
+            abilities.str,
+            abilities.dex,
+            abilities.con,
+            abilities.int,
+            abilities.wis,
+            abilities.cha,
+        )
+    }
+}
+
+/// Ability scores, e.g. 14 for a modifier of +2
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AbilityScoresDto {
+    pub str: u8,
+    pub dex: u8,
+    pub con: u8,
+    pub int: u8,
+    pub wis: u8,
+    pub cha: u8,
+}
+
+impl Default for AbilityScoresDto {
+    fn default() -> Self {
+        Self {
+            str: 10,
+            dex: 10,
+            con: 10,
+            int: 10,
+            wis: 10,
+            cha: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SaveModifiersDto {
     pub str: i16,
     pub dex: i16,
@@ -76,7 +1129,7 @@ impl From<SaveModifiersDto> for SaveModifiers {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ActionSelectionDto {
     pub default: Vec<ActionDto>,
     pub special: Vec<RechargeActionDto>,
@@ -88,9 +1141,12 @@ fn into_multi_action(actions: Vec<ActionDto>) -> ActionType {
     ))
 }
 
-fn multiple_actions_with_cost(actions: Vec<ActionDto>, name: usize) -> ActionType {
-    let mut resource_cost = ResourceCosts::new();
-    resource_cost.insert(name, 1);
+fn multiple_actions_with_cost(
+    actions: Vec<ActionDto>,
+    resource_cost: ResourceCosts,
+    slot: ActionSlot,
+    spell_level: Option<u8>,
+) -> ActionType {
     let mut actions: Vec<SingleAction> = actions.into_iter().map(|x| x.into()).collect();
     if !actions.is_empty() {
         let action = actions.remove(0);
@@ -102,65 +1158,283 @@ fn multiple_actions_with_cost(actions: Vec<ActionDto>, name: usize) -> ActionTyp
             },
         );
     };
-    ActionType::MultiAction(MultiAction::new(actions))
+    ActionType::MultiAction(MultiAction::new_with_slot(actions, slot, spell_level))
+}
+
+fn into_named_resources(resources: BTreeMap<String, ResourceDto>) -> ResourceCfgs {
+    resources
+        .into_iter()
+        .map(|(name, res)| {
+            (
+                ResourceKey::Named(name),
+                ResourceCfg::new(res.charges, res.recharge.into()),
+            )
+        })
+        .collect()
+}
+
+/// Costs in named resources, which the combatant has to declare
+fn into_named_costs(cost: BTreeMap<String, u32>, resources: &ResourceCfgs) -> ResourceCosts {
+    cost.into_iter()
+        .map(|(name, amount)| {
+            let key = ResourceKey::Named(name);
+            assert!(resources.contains_key(&key), "{VALIDATED}");
+            (key, amount)
+        })
+        .collect()
 }
 
 fn get_action_selection_and_resources(
     actions: ActionSelectionDto,
+    slots: &SpellSlots,
+    mut resources: ResourceCfgs,
 ) -> (Vec<ActionType>, ResourceCfgs) {
     let default_multi = into_multi_action(actions.default);
-    let resources: ResourceCfgs = actions
-        .special
-        .iter()
-        .enumerate()
-        .map(|(i, a)| {
-            (
-                i,
-                ResourceCfg::new(
-                    1,
-                    match a.recharge {
-                        5 => Some(Recharge::Recharge5),
-                        6 => Some(Recharge::Recharge6),
-                        0 => Some(Recharge::TurnStart),
-                        _ => None,
-                    },
-                ),
-            )
-        })
-        .collect();
-    let mut actions: Vec<_> = actions
-        .special
-        .into_iter()
-        .enumerate()
-        .map(|(i, conf)| multiple_actions_with_cost(conf.actions, i))
-        .collect();
-    actions.push(default_multi);
-    (actions, resources)
+    resources.extend(slots.resources());
+    let mut special_actions = vec![];
+    for (i, conf) in actions.special.into_iter().enumerate() {
+        let mut resource_cost = into_named_costs(conf.cost, &resources);
+        match conf.spell_level {
+            Some(level) if level > 0 && conf.per_day.is_none() => {
+                let spell = Spell {
+                    level,
+                    upcast: conf
+                        .upcast
+                        .map(|upcast| DamageRoll::from_str(upcast.as_str()).expect(VALIDATED)),
+                    actions: conf.actions.into_iter().map(|x| x.into()).collect(),
+                    slot: conf.slot,
+                    resource_cost,
+                };
+                special_actions.extend(
+                    spell
+                        .castings(slots)
+                        .into_iter()
+                        .map(ActionType::MultiAction),
+                );
+            }
+            _ => {
+                // actions paid for with named resources only have charges of their own if configured explicitly
+                if resource_cost.is_empty() || conf.recharge.is_some() || conf.per_day.is_some() {
+                    let key = ResourceKey::Special(i);
+                    let recharge = conf.recharge.unwrap_or_default().into();
+                    let charges = ResourceCfg::new(conf.per_day.unwrap_or(1), recharge);
+                    resources.insert(key.clone(), charges);
+                    resource_cost.insert(key, 1);
+                }
+                special_actions.push(multiple_actions_with_cost(
+                    conf.actions,
+                    resource_cost,
+                    conf.slot,
+                    conf.spell_level,
+                ));
+            }
+        }
+    }
+    special_actions.push(default_multi);
+    (special_actions, resources)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResourceDto {
+    pub charges: u32,
+    #[serde(default)]
+    pub recharge: RechargeDto,
+}
+
+/// `never`, `turn_start`, `short_rest`, `long_rest`, or the lowest roll on a d6 that recharges at
+/// the start of the turn, e.g. `5`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "serde_yaml::Value", into = "serde_yaml::Value")]
+pub enum RechargeDto {
+    #[default]
+    Never,
+    TurnStart,
+    ShortRest,
+    LongRest,
+    Roll(u8),
+}
+
+impl TryFrom<serde_yaml::Value> for RechargeDto {
+    type Error = String;
+
+    fn try_from(value: serde_yaml::Value) -> Result<Self, Self::Error> {
+        match &value {
+            Value::String(rule) => match rule.as_str() {
+                "never" => Ok(RechargeDto::Never),
+                "turn_start" => Ok(RechargeDto::TurnStart),
+                "short_rest" => Ok(RechargeDto::ShortRest),
+                "long_rest" => Ok(RechargeDto::LongRest),
+                _ => Err(format!(
+                    "unknown recharge {rule}, expected e.g. short_rest or 5"
+                )),
+            },
+            _ => value
+                .as_u64()
+                .and_then(|roll| u8::try_from(roll).ok())
+                .map(RechargeDto::Roll)
+                .ok_or_else(|| format!("invalid recharge {value:?}, expected e.g. 5")),
+        }
+    }
+}
+
+impl From<RechargeDto> for serde_yaml::Value {
+    fn from(recharge: RechargeDto) -> Self {
+        match recharge {
+            RechargeDto::Never => Value::from("never"),
+            RechargeDto::TurnStart => Value::from("turn_start"),
+            RechargeDto::ShortRest => Value::from("short_rest"),
+            RechargeDto::LongRest => Value::from("long_rest"),
+            RechargeDto::Roll(roll) => Value::from(roll),
+        }
+    }
+}
+
+impl From<RechargeDto> for Option<Recharge> {
+    fn from(recharge: RechargeDto) -> Self {
+        match recharge {
+            RechargeDto::Never => None,
+            RechargeDto::TurnStart => Some(Recharge::TurnStart),
+            RechargeDto::ShortRest => Some(Recharge::ShortRest),
+            RechargeDto::LongRest => Some(Recharge::LongRest),
+            RechargeDto::Roll(5) => Some(Recharge::Recharge5),
+            RechargeDto::Roll(6) => Some(Recharge::Recharge6),
+            // lower values are rejected when loading, higher ones never succeed
+            RechargeDto::Roll(_) => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RechargeActionDto {
     actions: Vec<ActionDto>,
-    recharge: u8,
+    /// Recharge rule of the action's own charges
+    #[serde(default)]
+    recharge: Option<RechargeDto>,
+    /// Charges of named resources spent on the action, e.g. `ki: 1`
+    #[serde(default)]
+    cost: BTreeMap<String, u32>,
+    #[serde(default)]
+    slot: ActionSlot,
+    /// Level of the spell cast with this action (0 for cantrips), if any. Leveled spells cost a spell slot.
+    #[serde(default)]
+    spell_level: Option<u8>,
+    /// Extra damage per slot level above the spell's level, e.g. "1d6"
+    #[serde(default)]
+    upcast: Option<String>,
+    /// Number of uses per day instead of spending spell slots, e.g. for innate spellcasting
+    #[serde(default)]
+    per_day: Option<u32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ActionDto {
+    /// Attack whose bonus and damage are either given or derived from an ability modifier
     Attack {
         name: String,
-        atk: i16,
-        dmg: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        atk: Option<i16>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        dmg: Option<String>,
+        /// Ability whose modifier is added to the attack and damage rolls
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ability: Option<AttackAbilityDto>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        proficient: bool,
+        /// Damage dice of the weapon, e.g. "1d8"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        weapon: Option<String>,
+        /// Reach or range in feet in encounters on a grid, 5 if not given
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<u32>,
     },
     SaveBasedAttack {
         name: String,
-        save_dc: i16,
+        /// The spell save DC of the combatant if not given
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        save_dc: Option<i16>,
         save_type: SaveType,
-        targets: usize,
+        /// Fixed number of targets, for effects without an area
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        targets: Option<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        area: Option<AreaDto>,
         damage: String,
         half_on_success: bool,
+        /// Range in feet in encounters on a grid. Cones and lines reach as far as they are long,
+        /// other effects 5 feet if not given.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<u32>,
     },
 }
 
+/// Area of effect, e.g. `{ shape: sphere, size: 20 }`, whose targets are estimated from the
+/// number of conscious enemies
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AreaDto {
+    pub shape: Shape,
+    /// Size in feet: the length of cones and lines, the edge of cubes and the radius of cylinders and spheres
+    pub size: u32,
+    /// Chance of every further enemy to be caught as well, if they are not crowded together
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub density: Option<f32>,
+    /// Chance of every ally of the attacker to be caught as well
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub friendly_fire: Option<f32>,
+    /// Chance of every enemy in the other line than the one the area is aimed at to be caught as well
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spill: Option<f32>,
+}
+
+impl From<AreaDto> for Area {
+    fn from(dto: AreaDto) -> Self {
+        let area = Area::new(dto.shape, dto.size);
+        let area = match dto.density {
+            Some(density) => area.with_density(density),
+            None => area,
+        };
+        area.with_friendly_fire(dto.friendly_fire.unwrap_or_default())
+            .with_spill(dto.spill.unwrap_or_default())
+    }
+}
+
+impl ActionDto {
+    /// Attack with a given attack bonus, damage and optionally reach or range
+    fn attack(name: &str, atk: i16, dmg: String, range: Option<u32>) -> Self {
+        ActionDto::Attack {
+            name: name.to_string(),
+            atk: Some(atk),
+            dmg: Some(dmg),
+            ability: None,
+            proficient: false,
+            weapon: None,
+            range,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            ActionDto::Attack { name, .. } | ActionDto::SaveBasedAttack { name, .. } => name,
+        }
+    }
+}
+
+/// Ability of an attack, which can be the spellcasting ability of the combatant for spell attacks
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum AttackAbilityDto {
+    #[serde(rename = "spellcasting")]
+    Spellcasting,
+    #[serde(untagged)]
+    Ability(SaveType),
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn is_zero(value: &i16) -> bool {
+    *value == 0
+}
+
 impl From<ActionDto> for SingleAction {
     fn from(val: ActionDto) -> Self {
         Self {
@@ -170,20 +1444,36 @@ impl From<ActionDto> for SingleAction {
                     save_dc,
                     save_type,
                     targets,
+                    area,
                     damage,
                     half_on_success,
+                    range,
                 } => {
-                    Execution::ApplyNegativeEffect(NegativeEffect::Saveable(SaveBasedAttack::new(
-                        Save::new(save_type, save_dc),
+                    let targets = match area {
+                        Some(area) => Targets::Area(area.into()),
+                        None => Targets::Number(targets.expect(VALIDATED)),
+                    };
+                    let attack = SaveBasedAttack::new(
+                        Save::new(save_type, save_dc.expect(VALIDATED)),
                         targets,
                         half_on_success,
-                        DamageRoll::from_str(damage.as_str()).unwrap(),
-                    )))
+                        DamageRoll::from_str(damage.as_str()).expect(VALIDATED),
+                    );
+                    let attack = match range {
+                        Some(range) => attack.with_range(range),
+                        None => attack,
+                    };
+                    Execution::ApplyNegativeEffect(NegativeEffect::Saveable(attack))
+                }
+                ActionDto::Attack {
+                    atk, dmg, range, ..
+                } => {
+                    let attack = Attack::new(
+                        atk.expect(VALIDATED),
+                        DamageRoll::from_str(dmg.expect(VALIDATED).as_str()).expect(VALIDATED),
+                    );
+                    Execution::Attack(attack.with_range(range.unwrap_or(SQUARE)))
                 }
-                ActionDto::Attack { name: _, atk, dmg } => Execution::Attack(Attack::new(
-                    atk,
-                    DamageRoll::from_str(dmg.as_str()).unwrap(),
-                )),
             },
             resource_cost: ResourceCosts::new(), // TODO
         }
@@ -192,9 +1482,39 @@ impl From<ActionDto> for SingleAction {
 
 #[cfg(test)]
 mod test {
-    use lib_es5e_core::combatant::config::CombatantConfig;
+    use lib_es5e_core::combatant::{
+        config::CombatantConfig, hit_dice::HitDicePolicy, skill::Skill,
+    };
+
+    use std::path::Path;
+
+    use lib_es5e_core::{
+        action::reaction::ReactionTrigger,
+        combat::{
+            adventuring_day::Rest,
+            encounter::Side,
+            grid::{Grid, Position},
+            initiative::InitiativeRules,
+            lines::Line,
+            morale::Rout,
+            outcome::Objective,
+            reinforcements::ArrivalTrigger,
+        },
+        combatant::defences::cover::{Cover, Obscurement, Shelter},
+        targeting::Targeting,
+        utils::save::SaveType,
+    };
 
-    use crate::loader::CombatantDto;
+    use std::fs;
+
+    use lib_es5e_core::attack::area::Shape;
+    use serde_yaml::Value;
+
+    use crate::loader::{
+        import, into_side, library, read_combatants, read_day, read_day_encounters, read_encounter,
+        read_entry, validation, validation::WarningKind, ActionDto, CombatantDto, EncounterDto,
+        LoadError, Location, Problem, RechargeDto,
+    };
 
     // Note: the API is currently very volatile, so more detailed tests are omitted for the time being
     #[test]
@@ -211,6 +1531,35 @@ mod test {
       int: 3
       wis: 9
       cha: 11
+    decision: !Lookahead
+      rollouts: 20
+      rounds: 1
+    reactions:
+      - name: tail attack
+        trigger: leaves_reach
+        effect: !Strike
+          action: !Attack
+            name: tail
+            atk: 15
+            dmg: 2d8+8
+      - name: shield
+        trigger: hit
+        effect: !AcBonus
+          bonus: 5
+          until_turn_start: true
+    legendary:
+      points: 3
+      resistances: 3
+      actions:
+        - cost: 2
+          actions:
+            - !SaveBasedAttack
+              name: wing attack
+              save_dc: 22
+              save_type: !DEX
+              targets: 3
+              damage: 2d6+8
+              half_on_success: false
     actions:
       default:
         - &claws !Attack
@@ -232,6 +1581,13 @@ mod test {
               targets: 3
               damage: 15d8
               half_on_success: true
+        - recharge: turn_start
+          slot: bonus
+          actions:
+            - !Attack
+              name: tail
+              atk: 15
+              dmg: 2d8+8
     ";
 
         let combatants: Vec<CombatantDto> =
@@ -239,4 +1595,937 @@ mod test {
         let part: Vec<CombatantConfig> = combatants.into_iter().map(|e| e.into()).collect();
         assert_eq!(part.len(), 1);
     }
+
+    #[test]
+    fn test_parse_spellcaster() {
+        let yaml = "
+  - name: wizard
+    hp: 80
+    ac: 15
+    init: 2
+    saves:
+      str: 0
+      dex: 2
+      con: 2
+      int: 9
+      wis: 5
+      cha: 0
+    spell_slots: [4, 3, 2]
+    pact_slots:
+      count: 1
+      level: 3
+    reactions:
+      - name: counterspell
+        trigger: spell_cast
+        spell_level: 3
+        effect: !Counter
+          level: 3
+          ability_modifier: 5
+    actions:
+      default:
+        - !Attack
+          name: fire bolt
+          atk: 9
+          dmg: 3d10
+      special:
+        - spell_level: 2
+          upcast: 1d8
+          actions:
+            - !Attack
+              name: scorching ray
+              atk: 9
+              dmg: 6d6
+        - spell_level: 4
+          per_day: 1
+          actions:
+            - !SaveBasedAttack
+              name: innate blight
+              save_dc: 17
+              save_type: !CON
+              targets: 1
+              damage: 8d8
+              half_on_success: true
+    ";
+
+        let combatants: Vec<CombatantDto> =
+            serde_yaml::from_str(yaml).expect("unable to parse test data");
+        let wizard: CombatantConfig = combatants.into_iter().next().unwrap().into();
+        // scorching ray with a 2nd level slot, a pact slot or a 3rd level slot, innate blight, cantrip
+        assert_eq!(wizard.actions.len(), 5);
+        assert_eq!(wizard.reactions.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_shared_resources() {
+        let yaml = "
+  - name: monk
+    hp: 90
+    ac: 18
+    init: 4
+    saves:
+      str: 5
+      dex: 9
+      con: 2
+      int: 0
+      wis: 7
+      cha: 0
+    resources:
+      ki:
+        charges: 6
+        recharge: short_rest
+    reactions:
+      - name: deflect missiles
+        trigger: hit
+        effect: !ReduceDamage
+          amount: 1d10+15
+    actions:
+      default:
+        - &fist !Attack
+          name: unarmed strike
+          atk: 9
+          dmg: 1d8+4
+        - *fist
+      special:
+        - cost:
+            ki: 1
+          slot: bonus
+          actions:
+            - *fist
+            - *fist
+        - cost:
+            ki: 2
+          recharge: turn_start
+          actions:
+            - !Attack
+              name: stunning strike
+              atk: 9
+              dmg: 3d8+4
+    ";
+
+        let combatants: Vec<CombatantDto> =
+            serde_yaml::from_str(yaml).expect("unable to parse test data");
+        let monk: CombatantConfig = combatants.into_iter().next().unwrap().into();
+        // ki and the own charges of the action that recharges every turn
+        assert_eq!(monk.resources.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_encounter() {
+        let yaml = "
+name: ambush
+surprised: players
+ambush: enemies
+initiative: group
+targeting: lowest_ac
+players:
+  - name: fighter
+    hp: 40
+    ac: 18
+    init: 1
+    init_bonus: 5
+    init_advantage: true
+    saves:
+      str: 5
+      dex: 1
+      con: 5
+      int: 0
+      wis: 1
+      cha: 0
+    targeting: random
+    surprised: true
+    actions:
+      default:
+        - !Attack
+          name: longsword
+          atk: 7
+          dmg: 1d8+4
+      special: []
+enemies:
+  - file: ogres.yaml
+    name: ogre
+    count: 2
+    ";
+
+        let encounter: EncounterDto =
+            serde_yaml::from_str(yaml).expect("unable to parse test data");
+        assert_eq!(encounter.surprised, Some(Side::Players));
+        assert_eq!(encounter.ambush, Some(Side::Enemies));
+        let test_data = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test_data");
+        let file_path = test_data.join("ambush.yaml");
+        let players = into_side(encounter.players, &file_path, encounter.targeting).unwrap();
+        let enemies = into_side(encounter.enemies, &file_path, encounter.targeting).unwrap();
+        assert_eq!(players[0].targeting, Some(Targeting::Random));
+        assert!(players[0].surprised);
+        assert_eq!(encounter.initiative, InitiativeRules::Group);
+        assert_eq!(
+            (players[0].init, players[0].init_advantage),
+            (Some(6), true)
+        );
+        let names: Vec<_> = enemies.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["ogre 1", "ogre 2"]);
+        assert!(enemies
+            .iter()
+            .all(|e| e.initiative_group == Some(String::from("ogre"))));
+        assert!(enemies
+            .iter()
+            .all(|e| e.targeting == Some(Targeting::LowestAc)));
+    }
+
+    #[test]
+    fn test_parse_encounter_on_grid() {
+        let yaml = "
+name: archers on the wall
+grid: { distance: 60 }
+players:
+  - monster: veteran
+    speed: 25
+enemies:
+  - name: archer
+    hp: 20
+    ac: 13
+    init: 2
+    position: { x: 0, y: 18 }
+    saves: { str: 0, dex: 2, con: 0, int: 0, wis: 0, cha: 0 }
+    actions:
+      default:
+        - !Attack
+          name: longbow
+          atk: 5
+          dmg: 1d8+2
+          range: 150
+      special: []
+    ";
+
+        let encounter: EncounterDto =
+            serde_yaml::from_str(yaml).expect("unable to parse test data");
+        assert_eq!(encounter.grid, Some(Grid { distance: 60 }));
+        let file_path = Path::new("archers.yaml");
+        let players = into_side(encounter.players, file_path, encounter.targeting).unwrap();
+        let enemies = into_side(encounter.enemies, file_path, encounter.targeting).unwrap();
+        assert_eq!(players[0].speed, Some(25));
+        assert_eq!(enemies[0].position, Some(Position::new(0, 18)));
+        match &enemies[0].actions.default[0] {
+            ActionDto::Attack { range, .. } => assert_eq!(*range, Some(150)),
+            action => panic!("unexpected action {action:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let yaml = "
+- name: wizard
+  hp: 30
+  ac: 12
+  init: 2
+  line: back
+  saves: { str: 0, dex: 2, con: 1, int: 6, wis: 2, cha: 0 }
+  actions:
+    default: []
+    special:
+      - name: fireball
+        per_day: 1
+        actions:
+          - !SaveBasedAttack
+            name: fireball
+            save_dc: 15
+            save_type: !DEX
+            area: { shape: sphere, size: 20, spill: 0.25 }
+            damage: 8d6
+            half_on_success: true
+- monster: wolf
+  slip_past: true
+";
+        let entries: Vec<Value> = serde_yaml::from_str(yaml).unwrap();
+        let mut configs = vec![];
+        for entry in entries {
+            let entry = library::with_monster(entry, &Location::default()).unwrap();
+            let dtos = read_entry(entry, Path::new("party.yaml")).unwrap();
+            configs.extend(dtos.into_iter().map(CombatantConfig::from));
+        }
+        assert_eq!((configs[0].line, configs[0].slip_past), (Line::Back, false));
+        assert_eq!((configs[1].line, configs[1].slip_past), (Line::Front, true));
+    }
+
+    #[test]
+    fn test_parse_reinforcements() {
+        let yaml = "
+name: hold the gate
+players:
+  - monster: veteran
+enemies:
+  - monster: orc
+reinforcements:
+  - side: enemies
+    trigger: !round 3
+    combatants:
+      - monster: orc
+        count: 2
+  - side: players
+    trigger: first_down
+    combatants:
+      - monster: veteran
+        name: guard
+";
+        let file_path = std::env::temp_dir().join("es5e_gate.yaml");
+        fs::write(&file_path, yaml).unwrap();
+        let encounter = read_encounter(&file_path).unwrap();
+        fs::remove_file(&file_path).unwrap();
+        let reinforcements = &encounter.settings.reinforcements;
+        assert_eq!(reinforcements[0].trigger, ArrivalTrigger::Round(3));
+        assert_eq!(reinforcements[0].combatants.len(), 2);
+        assert_eq!(reinforcements[1].side, Side::Players);
+        assert_eq!(reinforcements[1].trigger, ArrivalTrigger::FirstDown);
+        assert_eq!(reinforcements[1].combatants[0].name, "guard");
+    }
+
+    #[test]
+    fn test_parse_inline_lair() {
+        let yaml = "
+name: collapsing cave
+players:
+  - monster: veteran
+enemies:
+  - monster: orc
+lair:
+  actions:
+    - name: falling rocks
+      actions:
+        - !SaveBasedAttack
+          name: falling rocks
+          save_dc: 15
+          save_type: !DEX
+          targets: 3
+          damage: 2d10
+          half_on_success: true
+";
+        let file_path = std::env::temp_dir().join("es5e_cave.yaml");
+        fs::write(&file_path, yaml).unwrap();
+        let encounter = read_encounter(&file_path);
+        fs::remove_file(&file_path).unwrap();
+        assert_eq!(encounter.unwrap().lair.unwrap().actions.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_hit_dice_policy() {
+        let day = |policy| {
+            let file_path = std::env::temp_dir().join("es5e_rests.yaml");
+            fs::write(
+                &file_path,
+                format!("encounters: []\nhit_dice_policy: {policy}"),
+            )
+            .unwrap();
+            let day = read_day(&file_path);
+            fs::remove_file(&file_path).unwrap();
+            day.map(|(day, _)| day.hit_dice_policy)
+        };
+
+        assert_eq!(day("heal_to_full").unwrap(), HitDicePolicy::HealToFull);
+        assert_eq!(day("!heal_to 0.75").unwrap(), HitDicePolicy::HealTo(0.75));
+        match day("!heal_to 1.5").unwrap_err() {
+            LoadError::Invalid { problem, .. } => assert_eq!(problem, Problem::InvalidChance(1.5)),
+            e => panic!("unexpected error {e}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_day() {
+        let test_data = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test_data");
+        let file_path = test_data.join("day.yaml");
+        let (day, _) = read_day(&file_path).unwrap();
+        let encounters = read_day_encounters(day, &file_path, &[]).unwrap();
+        let rests: Vec<_> = encounters.iter().map(|(_, rest)| *rest).collect();
+        assert_eq!(rests, [Rest::Short, Rest::None, Rest::None]);
+        assert_eq!(encounters[1].0.settings.grid, Some(Grid { distance: 60 }));
+        let (dragon, _) = &encounters[2];
+        assert_eq!(dragon.enemies[0].name, "dragon");
+        assert!(dragon.lair.is_some() && dragon.settings.round_limit == Some(20));
+
+        let day = |encounter: &str| {
+            let file_path = std::env::temp_dir().join("es5e_escort_day.yaml");
+            fs::write(
+                &file_path,
+                format!("encounters:\n  - encounter: {encounter}"),
+            )
+            .unwrap();
+            let (day, _) = read_day(&file_path).unwrap();
+            let encounters = read_day_encounters(day, &file_path, &[String::from("merchant")]);
+            fs::remove_file(&file_path).unwrap();
+            encounters
+        };
+        let escort = day("{enemies: [{monster: orc}], objective: !protect merchant}").unwrap();
+        assert_eq!(
+            escort[0].0.settings.objective,
+            Some(Objective::Protect(String::from("merchant")))
+        );
+        let problem = |error| match error {
+            LoadError::Invalid { problem, .. } => problem,
+            error => panic!("unexpected error {error}"),
+        };
+        let players = day("{players: [{monster: veteran}], enemies: [{monster: orc}]}");
+        assert_eq!(problem(players.err().unwrap()), Problem::PlayersInDay);
+        let unknown = day("{enemies: [{monster: orc}], objective: !protect guard}");
+        assert_eq!(
+            problem(unknown.err().unwrap()),
+            Problem::UnknownCombatant(String::from("guard"))
+        );
+    }
+
+    #[test]
+    fn test_parse_objective() {
+        let encounter = |objective| {
+            let yaml = format!(
+                "
+name: escort
+players:
+  - monster: veteran
+    name: merchant
+enemies:
+  - monster: orc
+objective: {objective}
+"
+            );
+            let file_path = std::env::temp_dir().join("es5e_escort.yaml");
+            fs::write(&file_path, yaml).unwrap();
+            let encounter = read_encounter(&file_path);
+            fs::remove_file(&file_path).unwrap();
+            encounter.map(|e| e.settings.objective)
+        };
+        assert_eq!(
+            encounter("!survive 5").unwrap(),
+            Some(Objective::Survive(5))
+        );
+        assert_eq!(
+            encounter("!protect merchant").unwrap(),
+            Some(Objective::Protect(String::from("merchant")))
+        );
+        let error = encounter("!kill merchant").err().unwrap();
+        assert!(error.to_string().contains("no combatant named merchant"));
+    }
+
+    #[test]
+    fn test_parse_morale() {
+        let yaml = "
+- monster: bandit captain
+  leader: true
+- monster: bandit
+  morale: { hp_below: 0.5, leader_down: true, save_dc: 10, rout: surrender }
+- monster: bandit
+  morale: { hp_below: 1.5 }
+";
+        let entries: Vec<Value> = serde_yaml::from_str(yaml).unwrap();
+        let mut results = entries.into_iter().map(|entry| {
+            let entry = library::with_monster(entry, &Location::default()).unwrap();
+            read_entry(entry, Path::new("bandits.yaml"))
+        });
+        let captain = CombatantConfig::from(results.next().unwrap().unwrap().remove(0));
+        assert!(captain.leader && captain.morale.is_none());
+        let bandit = CombatantConfig::from(results.next().unwrap().unwrap().remove(0));
+        let morale = bandit.morale.unwrap();
+        assert_eq!((morale.hp_below, morale.save_dc), (Some(0.5), Some(10)));
+        assert!(morale.leader_down && morale.rout == Rout::Surrender);
+        match results.next().unwrap().unwrap_err() {
+            LoadError::Invalid { problem, .. } => assert_eq!(problem, Problem::InvalidChance(1.5)),
+            error => panic!("unexpected error {error}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_shelter() {
+        let yaml = "
+name: ambush in the fog
+shelter:
+  players: { obscured: heavily }
+  enemies: { cover: half, melee_steps_around: true }
+players:
+  - monster: veteran
+enemies:
+  - monster: bandit
+    cover: total
+  - monster: bandit
+";
+        let encounter: EncounterDto =
+            serde_yaml::from_str(yaml).expect("unable to parse test data");
+        assert_eq!(
+            encounter.shelter.players.obscured,
+            Some(Obscurement::Heavily)
+        );
+        assert_eq!(encounter.shelter.enemies.cover, Some(Cover::Half));
+        assert!(encounter.shelter.enemies.melee_steps_around);
+        let enemies = into_side(
+            encounter.enemies,
+            Path::new("ambush.yaml"),
+            encounter.targeting,
+        )
+        .unwrap();
+        let configs: Vec<CombatantConfig> =
+            enemies.into_iter().map(CombatantConfig::from).collect();
+        assert_eq!(configs[0].shelter.cover, Some(Cover::Total));
+        assert_eq!(configs[1].shelter, Shelter::default());
+    }
+
+    #[test]
+    fn test_templates() {
+        let test_data = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test_data");
+
+        let combatants: Vec<CombatantConfig> = read_combatants(&test_data.join("goblins.yaml"))
+            .unwrap()
+            .into_iter()
+            .map(|dto| dto.into())
+            .collect();
+
+        let names: Vec<_> = combatants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "goblin 1",
+                "goblin 2",
+                "goblin 3",
+                "goblin 4",
+                "goblin boss",
+                "goblin ogre"
+            ]
+        );
+        let boss = &combatants[4];
+        assert_eq!(
+            (boss.stats.max_hp, boss.stats.ac, boss.stats.initiative),
+            (21, 17, 2)
+        );
+        let ogre = &combatants[5];
+        assert_eq!(ogre.stats.max_hp, 59);
+        assert_eq!(ogre.stats.saves.modifier(&SaveType::STR), 4);
+        assert_eq!(ogre.stats.saves.modifier(&SaveType::WIS), -3);
+    }
+
+    #[test]
+    fn test_cyclic_templates_across_files() {
+        let dir = std::env::temp_dir();
+        let entry = |name, base, file| {
+            format!("- name: {name}\n  hp: 10\n  ac: 12\n  extends: {{file: {file}, name: {base}}}")
+        };
+        let (first, second) = (dir.join("es5e_knights.yaml"), dir.join("es5e_squires.yaml"));
+        fs::write(&first, entry("knight", "squire", "es5e_squires.yaml")).unwrap();
+        fs::write(&second, entry("squire", "knight", "es5e_knights.yaml")).unwrap();
+        let error = read_combatants(&first).unwrap_err();
+        fs::remove_file(&first).unwrap();
+        fs::remove_file(&second).unwrap();
+        match error {
+            LoadError::Invalid { location, problem } => {
+                assert_eq!(location.combatant.as_deref(), Some("knight"));
+                assert_eq!(problem, Problem::CyclicExtends(String::from("knight")));
+            }
+            error => panic!("unexpected error {error}"),
+        }
+    }
+
+    #[test]
+    fn test_derived_stats() {
+        let test_data = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test_data");
+        let file_path = test_data.join("rogues.yaml");
+
+        let rogues = read_combatants(&file_path).unwrap();
+        let (rogue, veteran) = (&rogues[0], &rogues[1]);
+        assert_eq!(rogue.init, Some(4));
+        let saves = rogue.saves.as_ref().unwrap();
+        assert_eq!((saves.str, saves.dex, saves.int), (0, 7, 4));
+        assert_eq!(rogue.skills.get(&Skill::Stealth), Some(&10));
+        assert_eq!(rogue.skills.get(&Skill::Perception), Some(&3));
+        let attack = |dto: &CombatantDto, idx: usize| match &dto.actions.default[idx] {
+            ActionDto::Attack { atk, dmg, .. } => (atk.unwrap(), dmg.clone().unwrap()),
+            action => panic!("unexpected action {action:?}"),
+        };
+        assert_eq!(attack(rogue, 0), (7, String::from("1d8+4")));
+        assert_eq!(attack(veteran, 0), (8, String::from("1d8+4")));
+        match &veteran.actions.special[0].actions[0] {
+            ActionDto::Attack { atk, dmg, .. } => {
+                assert_eq!((*atk, dmg.as_deref()), (Some(8), Some("1d6+3d6+4")))
+            }
+            action => panic!("unexpected action {action:?}"),
+        }
+        let config: CombatantConfig = veteran.clone().into();
+        assert_eq!(config.stats.skill_modifier(Skill::Stealth), 12);
+        assert_eq!(config.stats.skill_modifier(Skill::Acrobatics), 4);
+
+        let yaml = fs::read_to_string(&file_path).unwrap();
+        let file_path = std::env::temp_dir().join("es5e_rogues.yaml");
+        fs::write(&file_path, yaml.replace("  level: 5\n", "")).unwrap();
+        let error = read_combatants(&file_path).unwrap_err();
+        fs::remove_file(&file_path).unwrap();
+        match error {
+            LoadError::Invalid { location, problem } => {
+                assert_eq!(location.combatant.as_deref(), Some("rogue"));
+                assert_eq!(problem, Problem::MissingField("proficiency_bonus"));
+            }
+            error => panic!("unexpected error {error}"),
+        }
+    }
+
+    #[test]
+    fn test_library() {
+        let test_data = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test_data");
+
+        let combatants = read_combatants(&test_data.join("library.yaml")).unwrap();
+        let names: Vec<_> = combatants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            &names[4..],
+            ["goblin 5", "goblin 6", "court mage", "evoker"]
+        );
+        let goblin = &combatants[0];
+        assert_eq!((goblin.hp, goblin.ac, goblin.init), (7, 15, Some(2)));
+        let mage = &combatants[6];
+        assert_eq!(mage.hp, 52);
+        assert_eq!(mage.actions.special.len(), 3);
+
+        let evoker = &combatants[7];
+        match &evoker.actions.default[..] {
+            [ActionDto::Attack { atk, dmg, .. }] => {
+                assert_eq!((*atk, dmg.as_deref()), (Some(8), Some("3d10")))
+            }
+            actions => panic!("unexpected actions {actions:?}"),
+        }
+        let fireball = &evoker.actions.special[0];
+        assert_eq!(fireball.spell_level, Some(4));
+        match &fireball.actions[0] {
+            ActionDto::SaveBasedAttack {
+                save_dc, damage, ..
+            } => assert_eq!((*save_dc, damage.as_str()), (Some(16), "8d6+1d6")),
+            action => panic!("unexpected action {action:?}"),
+        }
+        let lightning_bolt = &evoker.actions.special[1];
+        assert_eq!(
+            (lightning_bolt.spell_level, lightning_bolt.per_day),
+            (Some(3), Some(1))
+        );
+
+        // every monster of the library can be referred to
+        let monsters: Vec<Value> =
+            serde_yaml::from_str(include_str!("../library/monsters.yaml")).unwrap();
+        for monster in monsters {
+            let name = monster.get("name").unwrap().clone();
+            let mut entry = serde_yaml::Mapping::new();
+            entry.insert(Value::from("monster"), name);
+            let entry = library::with_monster(Value::Mapping(entry), &Location::default()).unwrap();
+            read_entry(entry, Path::new("monsters.yaml")).unwrap();
+        }
+
+        let entry = serde_yaml::from_str("{ monster: beholder }").unwrap();
+        let error = library::with_monster(entry, &Location::default()).unwrap_err();
+        assert!(matches!(
+            error,
+            LoadError::Invalid { problem: Problem::UnknownMonster(name), .. } if name == "beholder"
+        ));
+        let yaml = "
+name: apprentice
+hp: 9
+ac: 12
+spell_save_dc: 12
+actions:
+  default: []
+  special:
+    - spell: fireball
+      slot: 2
+";
+        let mut entry = serde_yaml::from_str(yaml).unwrap();
+        let error = library::expand_spells(&mut entry, &Location::default()).unwrap_err();
+        assert!(matches!(
+            error,
+            LoadError::Invalid {
+                problem: Problem::InvalidSpellSlot { slot: 2, .. },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_validation() {
+        let yaml = "
+- name: kobold
+  hp: 5
+  ac: 12
+  init: 2
+  saves:
+    str: -2
+    dex: 2
+    con: -1
+    int: -1
+    wis: -2
+    cha: -1
+  actions:
+    default:
+      - !Attack
+        name: dagger
+        atk: 4
+        dmg: 1d4+2
+    special:
+      - recharge: 7
+        actions:
+          - !SaveBasedAttack
+            name: trap
+            save_dc: 12
+            save_type: DEX
+            targets: 4
+            damage: 2d6
+            half_on_success: true
+";
+        let file_path = std::env::temp_dir().join("es5e_validation_kobold.yaml");
+        fs::write(&file_path, yaml).unwrap();
+        let kobolds = read_combatants(&file_path).unwrap();
+
+        let warnings = validation::fight_warnings(&kobolds, &kobolds[..1]);
+        let kinds: Vec<_> = warnings.iter().map(|w| &w.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &WarningKind::MoreTargetsThanEnemies {
+                    targets: 4,
+                    enemies: 1
+                },
+                &WarningKind::NeverRecharges(7),
+                &WarningKind::MoreTargetsThanEnemies {
+                    targets: 4,
+                    enemies: 1
+                },
+                &WarningKind::NeverRecharges(7),
+            ]
+        );
+        assert_eq!(warnings[0].location.line, Some(23));
+
+        fs::write(&file_path, yaml.replace("1d4+2", "1d4+")).unwrap();
+        let error = read_combatants(&file_path).unwrap_err();
+        match error {
+            LoadError::Invalid { location, problem } => {
+                assert_eq!(location.combatant.as_deref(), Some("kobold"));
+                assert_eq!(location.action.as_deref(), Some("dagger"));
+                assert_eq!(location.line, Some(16));
+                assert_eq!(problem, Problem::InvalidDice(String::from("1d4+")));
+            }
+            error => panic!("unexpected error {error}"),
+        }
+
+        let area = "area: { shape: sphere, size: 10, density: 1.5 }";
+        for (replacement, expected) in [
+            (
+                format!("targets: 4\n            {area}"),
+                Problem::TargetsAndArea,
+            ),
+            (area.to_string(), Problem::InvalidChance(1.5)),
+            (
+                "area: { shape: cone, size: 15, spill: -0.5 }".to_string(),
+                Problem::InvalidChance(-0.5),
+            ),
+        ] {
+            fs::write(&file_path, yaml.replace("targets: 4", &replacement)).unwrap();
+            match read_combatants(&file_path).unwrap_err() {
+                LoadError::Invalid { problem, .. } => assert_eq!(problem, expected),
+                error => panic!("unexpected error {error}"),
+            }
+        }
+
+        let dodge = "
+  reactions:
+    - name: dodge
+      trigger: damaged
+      effect: !HalveDamage
+";
+        let shield = dodge
+            .replace("dodge", "shield")
+            .replace("damaged", "hit\n      spell_level: 1")
+            .replace("!HalveDamage", "!AcBonus { bonus: 5 }");
+        for (contents, action, expected) in [
+            (
+                format!("{yaml}{dodge}"),
+                "dodge",
+                Problem::UnfitTrigger {
+                    effect: "HalveDamage",
+                    trigger: ReactionTrigger::Damaged,
+                },
+            ),
+            (
+                format!("{yaml}{}", dodge.replace("HalveDamage", "AcBonus { bonus: 2 }")),
+                "dodge",
+                Problem::UnfitTrigger {
+                    effect: "AcBonus",
+                    trigger: ReactionTrigger::Damaged,
+                },
+            ),
+            (format!("{yaml}{shield}"), "shield", Problem::NoSpellSlot(1)),
+            (
+                yaml.replace("- recharge: 7", "- spell_level: 2"),
+                "trap",
+                Problem::NoSpellSlot(2),
+            ),
+        ] {
+            fs::write(&file_path, contents).unwrap();
+            match read_combatants(&file_path).unwrap_err() {
+                LoadError::Invalid { location, problem } => {
+                    assert_eq!(location.action.as_deref(), Some(action));
+                    assert_eq!(problem, expected);
+                }
+                error => panic!("unexpected error {error}"),
+            }
+        }
+        let counter = dodge
+            .replace("damaged", "spell_cast")
+            .replace("!HalveDamage", "!Counter { level: 3, ability_modifier: 4 }");
+        fs::write(&file_path, format!("{yaml}{counter}")).unwrap();
+        assert!(read_combatants(&file_path).is_ok());
+        fs::remove_file(&file_path).unwrap();
+    }
+
+    #[test]
+    fn test_import_srd_monsters() {
+        let test_data = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test_data");
+
+        let (monsters, warnings) =
+            import::read_imported(&test_data.join("srd_monsters.json")).unwrap();
+
+        let dragon = &monsters[0];
+        assert_eq!((dragon.hp, dragon.ac, dragon.init), (195, 19, Some(2)));
+        let saves = dragon.saves.as_ref().unwrap();
+        assert_eq!((saves.str, saves.dex), (6, 7));
+        assert_eq!(dragon.legendary.resistances, 3);
+        let names: Vec<_> = dragon.actions.default.iter().map(|a| a.name()).collect();
+        assert_eq!(names, vec!["Bite", "Claw", "Claw"]);
+        assert_eq!(
+            dragon.actions.special[0].recharge,
+            Some(RechargeDto::Roll(5))
+        );
+        match &dragon.actions.default[0] {
+            ActionDto::Attack { atk, dmg, .. } => {
+                assert_eq!((*atk, dmg.as_deref()), (Some(11), Some("2d10+6+1d8")))
+            }
+            action => panic!("unexpected action {action:?}"),
+        }
+
+        let goblin = &monsters[1];
+        assert_eq!(goblin.saves.as_ref().unwrap().dex, 2);
+        assert_eq!(goblin.actions.default.len(), 1);
+
+        let young_dragon: CombatantConfig = monsters[2].clone().into();
+        assert_eq!(young_dragon.stats.saves.modifier(&SaveType::CON), 9);
+        assert_eq!(young_dragon.legendary.resistances, 1);
+        match &monsters[2].actions.default[0] {
+            ActionDto::Attack { dmg, .. } => assert_eq!(dmg.as_deref(), Some("2d10+6+1d6")),
+            action => panic!("unexpected action {action:?}"),
+        }
+        match &monsters[2].actions.special[0].actions[0] {
+            ActionDto::SaveBasedAttack {
+                name,
+                save_dc,
+                area,
+                damage,
+                half_on_success,
+                ..
+            } => {
+                assert_eq!(name, "Fire Breath");
+                assert_eq!(
+                    (*save_dc, damage.as_str(), *half_on_success),
+                    (Some(17), "16d6", true)
+                );
+                let area = area.as_ref().unwrap();
+                assert_eq!((area.shape, area.size), (Shape::Cone, 30));
+            }
+            action => panic!("unexpected action {action:?}"),
+        }
+
+        let not_imported: Vec<_> = warnings
+            .iter()
+            .filter(|w| matches!(w.kind, WarningKind::NotImported(_)))
+            .map(|w| w.location.action.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            not_imported,
+            vec![
+                "Frightful Presence",
+                "Tail",
+                "Detect",
+                "Tail Attack",
+                "Amphibious",
+                "Shortbow",
+                "Nimble Escape"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_foundry_actors() {
+        let test_data = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test_data");
+
+        let (fighters, warnings) =
+            import::read_imported(&test_data.join("foundry_fighter.json")).unwrap();
+        let fighter = &fighters[0];
+        assert_eq!((fighter.hp, fighter.ac, fighter.init), (44, 18, Some(3)));
+        let saves = fighter.saves.as_ref().unwrap();
+        assert_eq!((saves.str, saves.dex, saves.con), (6, 2, 5));
+        assert_eq!(
+            fighter.skills.get(&Skill::Athletics),
+            Some(&6),
+            "proficiency"
+        );
+        assert_eq!(
+            fighter.skills.get(&Skill::Perception),
+            Some(&7),
+            "expertise"
+        );
+        assert_eq!(fighter.hit_dice.as_deref(), Some("5d10"));
+        assert!(fighter.spell_slots.is_empty());
+        assert_eq!(fighter.actions.default.len(), 2);
+        match &fighter.actions.default[0] {
+            ActionDto::Attack { name, atk, dmg, .. } => assert_eq!(
+                (name.as_str(), *atk, dmg.as_deref()),
+                ("Longsword", Some(7), Some("1d8+3+1"))
+            ),
+            action => panic!("unexpected action {action:?}"),
+        }
+        let not_imported: Vec<_> = warnings
+            .iter()
+            .map(|w| w.location.action.as_deref().unwrap())
+            .collect();
+        assert_eq!(not_imported, vec!["Second Wind", "Action Surge", "Longbow"]);
+
+        let (wizards, warnings) =
+            import::read_imported(&test_data.join("foundry_wizard.json")).unwrap();
+        let wizard = &wizards[0];
+        let saves = wizard.saves.as_ref().unwrap();
+        assert_eq!((wizard.ac, saves.int, saves.wis), (15, 6, 4));
+        assert_eq!(wizard.spell_slots, vec![4, 3, 2]);
+        match &wizard.actions.default[..] {
+            [ActionDto::Attack { name, atk, dmg, .. }] => assert_eq!(
+                (name.as_str(), *atk, dmg.as_deref()),
+                ("Fire Bolt", Some(6), Some("2d10"))
+            ),
+            actions => panic!("unexpected actions {actions:?}"),
+        }
+        let spells: Vec<_> = wizard
+            .actions
+            .special
+            .iter()
+            .map(|s| (s.actions[0].name(), s.spell_level))
+            .collect();
+        assert_eq!(
+            spells,
+            vec![("Fireball", Some(3)), ("Burning Hands", Some(1))]
+        );
+        match &wizard.actions.special[0].actions[0] {
+            ActionDto::SaveBasedAttack {
+                save_dc,
+                area: Some(area),
+                half_on_success,
+                ..
+            } => assert_eq!(
+                (*save_dc, area.shape, area.size, *half_on_success),
+                (Some(14), Shape::Sphere, 20, true)
+            ),
+            action => panic!("unexpected action {action:?}"),
+        }
+        assert_eq!(wizard.actions.special[0].upcast.as_deref(), Some("1d6"));
+        let not_imported: Vec<_> = warnings
+            .iter()
+            .filter(|w| matches!(w.kind, WarningKind::NotImported(_)))
+            .map(|w| w.location.action.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            not_imported,
+            vec!["Shield", "Witch Bolt", "Arcane Recovery", "Quarterstaff"]
+        );
+    }
 }
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use lib_es5e_core::{combatant::defences::ability::AbilityModifiers, utils::save::SaveType};
+
+use super::{
+    error::{LoadError, Problem},
+    AbilityModifiersDto, AbilityScoresDto, ActionDto, AttackAbilityDto, CombatantDto,
+    ReactionEffectDto, SaveModifiersDto,
+};
+
+/// Fills in the values of a combatant that were left out to be derived from its ability scores,
+/// proficiency bonus and proficiencies. Values that are given are kept.
+pub(super) fn derive_stats(dto: &mut CombatantDto) -> Result<(), LoadError> {
+    if let Some(scores) = &dto.ability_scores {
+        dto.abilities = ability_modifiers(scores);
+    }
+    let modifiers: AbilityModifiers = dto.abilities.clone().into();
+    let proficiency = proficiency_bonus(dto);
+    let required_proficiency = || {
+        proficiency.ok_or_else(|| {
+            LoadError::invalid(&dto.location, Problem::MissingField("proficiency_bonus"))
+        })
+    };
+
+    let init = dto.init.unwrap_or(modifiers.dex()) + dto.init_bonus;
+    let saves = match &dto.saves {
+        Some(saves) => saves.clone(),
+        None => {
+            let mut saves = dto.abilities.clone();
+            for ability in &dto.save_proficiencies {
+                let bonus = required_proficiency()?;
+                let save = match ability {
+                    SaveType::STR => &mut saves.str,
+                    SaveType::DEX => &mut saves.dex,
+                    SaveType::CON => &mut saves.con,
+                    SaveType::INT => &mut saves.int,
+                    SaveType::WIS => &mut saves.wis,
+                    SaveType::CHA => &mut saves.cha,
+                };
+                *save += bonus;
+            }
+            let AbilityModifiersDto {
+                str,
+                dex,
+                con,
+                int,
+                wis,
+                cha,
+            } = saves;
+            SaveModifiersDto {
+                str,
+                dex,
+                con,
+                int,
+                wis,
+                cha,
+            }
+        }
+    };
+    let mut skills = BTreeMap::new();
+    for (skill, multiplier) in dto
+        .skill_proficiencies
+        .iter()
+        .map(|skill| (skill, 1))
+        .chain(dto.expertise.iter().map(|skill| (skill, 2)))
+    {
+        let bonus = required_proficiency()? * multiplier;
+        skills.insert(*skill, modifiers.modifier(&skill.ability()) + bonus);
+    }
+
+    let location = dto.location.clone();
+    let spellcasting = dto.spellcasting.as_ref().map(|a| modifiers.modifier(a));
+    let spell_save_dc = dto
+        .spell_save_dc
+        .or(spellcasting.zip(proficiency).map(|(m, p)| 8 + p + m));
+    let spell_attack = dto
+        .spell_attack
+        .or(spellcasting.zip(proficiency).map(|(m, p)| p + m));
+    let missing = |name: &str, field| {
+        LoadError::invalid(&location.action(name), Problem::MissingField(field))
+    };
+    for action in all_actions_mut(dto) {
+        match action {
+            ActionDto::Attack {
+                name,
+                atk,
+                dmg,
+                ability,
+                proficient,
+                weapon,
+                ..
+            } => {
+                let modifier = match ability {
+                    None => Some(0),
+                    Some(AttackAbilityDto::Ability(ability)) => Some(modifiers.modifier(ability)),
+                    Some(AttackAbilityDto::Spellcasting) => spellcasting,
+                };
+                if atk.is_none() {
+                    *atk = match ability {
+                        None => None,
+                        Some(AttackAbilityDto::Spellcasting) => {
+                            Some(spell_attack.ok_or_else(|| {
+                                missing(
+                                    name,
+                                    match spellcasting {
+                                        Some(_) => "proficiency_bonus",
+                                        None => "spellcasting",
+                                    },
+                                )
+                            })?)
+                        }
+                        Some(AttackAbilityDto::Ability(_)) => {
+                            let bonus = match proficient {
+                                true => {
+                                    proficiency.ok_or_else(|| missing(name, "proficiency_bonus"))?
+                                }
+                                false => 0,
+                            };
+                            modifier.map(|modifier| modifier + bonus)
+                        }
+                    };
+                }
+                if let (None, Some(weapon)) = (&dmg, weapon) {
+                    let modifier = modifier.ok_or_else(|| missing(name, "spellcasting"))?;
+                    *dmg = Some(match modifier {
+                        0 => weapon.clone(),
+                        modifier => format!("{weapon}{modifier:+}"),
+                    });
+                }
+            }
+            ActionDto::SaveBasedAttack { save_dc, .. } => {
+                if save_dc.is_none() {
+                    *save_dc = spell_save_dc;
+                }
+            }
+        }
+    }
+
+    dto.init = Some(init);
+    dto.saves = Some(saves);
+    // skill modifiers that are given take precedence over derived ones
+    skills.append(&mut dto.skills);
+    dto.skills = skills;
+    Ok(())
+}
+
+/// Proficiency bonus as given, or derived from the level or challenge rating
+fn proficiency_bonus(dto: &CombatantDto) -> Option<i16> {
+    let by_level = |level: i16| 2 + (level.max(1) - 1) / 4;
+    dto.proficiency_bonus
+        .or(dto.level.map(|level| by_level(level as i16)))
+        .or(dto.cr.map(|cr| by_level(cr.ceil() as i16)))
+}
+
+fn ability_modifiers(scores: &AbilityScoresDto) -> AbilityModifiersDto {
+    let modifier = |score: u8| (score as i16 - 10).div_euclid(2);
+    AbilityModifiersDto {
+        str: modifier(scores.str),
+        dex: modifier(scores.dex),
+        con: modifier(scores.con),
+        int: modifier(scores.int),
+        wis: modifier(scores.wis),
+        cha: modifier(scores.cha),
+    }
+}
+
+fn all_actions_mut(dto: &mut CombatantDto) -> impl Iterator<Item = &mut ActionDto> {
+    let special = dto
+        .actions
+        .special
+        .iter_mut()
+        .flat_map(|s| s.actions.iter_mut());
+    let legendary = dto
+        .legendary
+        .actions
+        .iter_mut()
+        .flat_map(|l| l.actions.iter_mut());
+    let reactions = dto
+        .reactions
+        .iter_mut()
+        .filter_map(|r| match &mut r.effect {
+            ReactionEffectDto::Strike { action } => Some(action),
+            _ => None,
+        });
+    dto.actions
+        .default
+        .iter_mut()
+        .chain(special)
+        .chain(legendary)
+        .chain(reactions)
+}
@@ -0,0 +1,217 @@
+use std::{
+    fmt::{self, Display},
+    io,
+    path::{Path, PathBuf},
+};
+
+use lib_es5e_core::action::reaction::ReactionTrigger;
+
+/// Place in the input files a problem was found at
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Location {
+    pub file: PathBuf,
+    pub combatant: Option<String>,
+    pub action: Option<String>,
+    pub line: Option<usize>,
+}
+
+impl Location {
+    pub fn file(file: &Path) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            ..Default::default()
+        }
+    }
+
+    /// Location of a combatant, whose line is looked up in the file it is defined in
+    pub fn combatant(file: &Path, combatant: &str) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            combatant: Some(combatant.to_string()),
+            action: None,
+            line: find_line(file, &[combatant]),
+        }
+    }
+
+    pub fn action(&self, action: &str) -> Self {
+        let mut names: Vec<&str> = self.combatant.iter().map(String::as_str).collect();
+        names.push(action);
+        Self {
+            action: Some(action.to_string()),
+            line: find_line(&self.file, &names).or(self.line),
+            ..self.clone()
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        if let Some(combatant) = &self.combatant {
+            write!(f, ", combatant {combatant}")?;
+        }
+        if let Some(action) = &self.action {
+            write!(f, ", action {action}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Line of the first `name:` entry with the last of the given names,
+/// searching each name only after the line of the previous one
+fn find_line(file: &Path, names: &[&str]) -> Option<usize> {
+    let contents = std::fs::read_to_string(file).ok()?;
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut start = 0;
+    for name in names {
+        let offset = lines[start..].iter().position(|line| {
+            let line = line.trim_start().trim_start_matches("- ");
+            line.strip_prefix("name:")
+                .is_some_and(|value| value.trim().trim_matches(['"', '\'']) == *name)
+        })?;
+        start += offset;
+    }
+    Some(start + 1)
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io {
+        file: PathBuf,
+        source: io::Error,
+    },
+    /// The file is not valid YAML or does not match the expected format
+    Yaml {
+        location: Location,
+        source: serde_yaml::Error,
+    },
+    /// The file is not valid JSON, e.g. an imported stat block
+    Json {
+        file: PathBuf,
+        source: serde_json::Error,
+    },
+    Invalid {
+        location: Location,
+        problem: Problem,
+    },
+}
+
+impl LoadError {
+    pub fn invalid(location: &Location, problem: Problem) -> Self {
+        LoadError::Invalid {
+            location: location.clone(),
+            problem,
+        }
+    }
+
+    pub fn yaml(location: &Location, source: serde_yaml::Error) -> Self {
+        let mut location = location.clone();
+        if let Some(yaml_location) = source.location() {
+            location.line = Some(yaml_location.line());
+        }
+        LoadError::Yaml { location, source }
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io { file, source } => write!(f, "{}: {source}", file.display()),
+            LoadError::Yaml { location, source } => write!(f, "{location}: {source}"),
+            LoadError::Json { file, source } => {
+                write!(f, "{}:{}: {source}", file.display(), source.line())
+            }
+            LoadError::Invalid { location, problem } => write!(f, "{location}: {problem}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+#[derive(Debug, PartialEq)]
+pub enum Problem {
+    InvalidDice(String),
+    InvalidHitDice(String),
+    UndeclaredResource(String),
+    UnknownCombatant(String),
+    UnknownMonster(String),
+    UnknownSpell(String),
+    /// A spell is cast with a slot below its level, or a leveled spell is used as a default action
+    InvalidSpellSlot {
+        spell: String,
+        slot: u8,
+    },
+    /// A leveled spell is given, but no spell slot of its level or higher
+    NoSpellSlot(u8),
+    /// A reaction's effect never takes place on its trigger, e.g. halving damage when damaged
+    UnfitTrigger {
+        effect: &'static str,
+        trigger: ReactionTrigger,
+    },
+    CyclicExtends(String),
+    UnsupportedRecharge(u8),
+    MissingField(&'static str),
+    /// Both a number of targets and an area of effect are given
+    TargetsAndArea,
+    InvalidChance(f32),
+    /// An encounter of an adventuring day has players of its own instead of the day's party
+    PlayersInDay,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::InvalidDice(dice) => {
+                write!(f, "invalid dice roll {dice:?}, expected e.g. 2d6+3")
+            }
+            Problem::InvalidHitDice(dice) => {
+                write!(f, "invalid hit dice {dice:?}, expected e.g. 8d10")
+            }
+            Problem::UndeclaredResource(name) => {
+                write!(f, "resource {name} is not declared under resources")
+            }
+            Problem::UnknownCombatant(name) => write!(f, "no combatant named {name}"),
+            Problem::UnknownMonster(name) => write!(f, "no monster named {name} in the library"),
+            Problem::UnknownSpell(name) => write!(f, "no spell named {name} in the library"),
+            Problem::InvalidSpellSlot { spell, slot: 0 } => {
+                write!(
+                    f,
+                    "{spell} costs a spell slot and cannot be a default action"
+                )
+            }
+            Problem::InvalidSpellSlot { spell, slot } => {
+                write!(
+                    f,
+                    "{spell} cannot be cast with a spell slot of level {slot}"
+                )
+            }
+            Problem::NoSpellSlot(level) => {
+                write!(
+                    f,
+                    "no spell slot of level {level} or higher to cast it with"
+                )
+            }
+            Problem::UnfitTrigger { effect, trigger } => {
+                write!(f, "{effect} never takes effect on the trigger {trigger:?}")
+            }
+            Problem::CyclicExtends(name) => write!(f, "{name} extends itself"),
+            Problem::UnsupportedRecharge(value) => {
+                write!(f, "recharge on {value} is not supported, only on 5 and 6")
+            }
+            Problem::MissingField(field) => write!(f, "missing {field}"),
+            Problem::TargetsAndArea => write!(f, "targets and area cannot both be given"),
+            Problem::InvalidChance(chance) => {
+                write!(f, "invalid chance {chance}, expected a value from 0 to 1")
+            }
+            Problem::PlayersInDay => {
+                write!(
+                    f,
+                    "encounters of an adventuring day are fought by its party"
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::path::Path;
+
+use lib_es5e_core::{attack::area::Shape, combatant::skill::Skill, utils::save::SaveType};
+use serde_json::Value;
+
+use super::{
+    error::{LoadError, Location, Problem},
+    read_file,
+    validation::{self, Warning, WarningKind},
+    AreaDto, CombatantDto,
+};
+
+mod foundry;
+mod srd;
+
+/// Number of creatures assumed to be caught in an area of effect whose size is not stated
+const AREA_TARGETS: usize = 3;
+
+const ABILITIES: [(&str, &str); 6] = [
+    ("str", "strength"),
+    ("dex", "dexterity"),
+    ("con", "constitution"),
+    ("int", "intelligence"),
+    ("wis", "wisdom"),
+    ("cha", "charisma"),
+];
+
+/// Reads the combatants of a JSON file exported from another tool: monster stat blocks of the
+/// 5e SRD API or of Open5e, or actors exported from Foundry VTT. The file can hold a single entry,
+/// a list of them or an Open5e result page. Everything that cannot be translated is reported as a warning.
+pub(super) fn read_imported(
+    file_path: &Path,
+) -> Result<(Vec<CombatantDto>, Vec<Warning>), LoadError> {
+    let contents = read_file(file_path)?;
+    let json: Value =
+        serde_json::from_str(contents.as_str()).map_err(|source| LoadError::Json {
+            file: file_path.to_path_buf(),
+            source,
+        })?;
+    let entries = match json {
+        Value::Array(entries) => entries,
+        Value::Object(mut page) if page.contains_key("results") => match page.remove("results") {
+            Some(Value::Array(entries)) => entries,
+            _ => vec![],
+        },
+        entry => vec![entry],
+    };
+
+    let mut combatants = vec![];
+    let mut warnings = vec![];
+    for entry in &entries {
+        let mut report = Report::new(file_path, entry);
+        let dto = if foundry::is_actor(entry) {
+            foundry::import_actor(entry, &mut report)?
+        } else {
+            srd::import_monster(entry, &mut report)?
+        };
+        validation::validate_combatant(&dto)?;
+        combatants.push(dto);
+        warnings.append(&mut report.warnings);
+    }
+    Ok((combatants, warnings))
+}
+
+/// Location of an imported combatant and the warnings about it
+struct Report {
+    location: Location,
+    warnings: Vec<Warning>,
+}
+
+impl Report {
+    fn new(file_path: &Path, entry: &Value) -> Self {
+        let location = Location {
+            file: file_path.to_path_buf(),
+            combatant: entry.get("name").and_then(Value::as_str).map(String::from),
+            ..Default::default()
+        };
+        Self {
+            location,
+            warnings: vec![],
+        }
+    }
+
+    fn missing(&self, field: &'static str) -> LoadError {
+        LoadError::invalid(&self.location, Problem::MissingField(field))
+    }
+
+    fn not_imported(&mut self, part: &str, reason: &str) {
+        self.warn(part, WarningKind::NotImported(reason.to_string()));
+    }
+
+    fn approximated(&mut self, part: &str, reason: &str) {
+        self.warn(part, WarningKind::Approximated(reason.to_string()));
+    }
+
+    fn warn(&mut self, part: &str, kind: WarningKind) {
+        let location = Location {
+            action: Some(part.to_string()),
+            ..self.location.clone()
+        };
+        self.warnings.push(Warning { location, kind });
+    }
+}
+
+fn modifier(score: i64) -> i16 {
+    (score as i16 - 10).div_euclid(2)
+}
+
+/// Skill of a name such as "Sleight of Hand", "sleight-of-hand" or "sleight_of_hand"
+fn skill(name: &str) -> Option<Skill> {
+    let snake_case = name.to_lowercase().replace([' ', '-'], "_");
+    serde_json::from_value(Value::String(snake_case)).ok()
+}
+
+fn save_type(ability: &str) -> Option<SaveType> {
+    let short = ability.get(..3)?.to_lowercase();
+    Some(match short.as_str() {
+        "str" => SaveType::STR,
+        "dex" => SaveType::DEX,
+        "con" => SaveType::CON,
+        "int" => SaveType::INT,
+        "wis" => SaveType::WIS,
+        "cha" => SaveType::CHA,
+        _ => return None,
+    })
+}
+
+/// Area of effect of a shape as named in stat blocks or by Foundry VTT, e.g. "radius" or "square"
+fn area(shape: &str, size: i64) -> Option<AreaDto> {
+    let shape = match shape.to_lowercase().as_str() {
+        "cone" => Shape::Cone,
+        "cube" | "square" => Shape::Cube,
+        "cylinder" => Shape::Cylinder,
+        "line" | "wall" => Shape::Line,
+        "sphere" | "radius" | "emanation" => Shape::Sphere,
+        _ => return None,
+    };
+    Some(AreaDto {
+        shape,
+        size: size.max(0) as u32,
+        density: None,
+        friendly_fire: None,
+        spill: None,
+    })
+}
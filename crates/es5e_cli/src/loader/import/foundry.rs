@@ -0,0 +1,815 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use lib_es5e_core::{
+    action::action::ActionSlot,
+    attack::{area::Area, damage::DamageRoll},
+    combatant::{defences::ability::AbilityModifiers, skill::Skill, spell_slots::PactSlots},
+    utils::save::SaveType,
+};
+use serde_json::Value;
+
+use super::{area, modifier, save_type, Report, ABILITIES, AREA_TARGETS};
+use crate::loader::{
+    error::LoadError,
+    library::{cantrip_tier, multiply_dice},
+    AbilityModifiersDto, ActionDto, ActionSelectionDto, AreaDto, CombatantDto, DecisionDto,
+    LegendaryDto, RechargeActionDto, SaveModifiersDto,
+};
+
+/// Target types of the dnd5e system that describe an area rather than a number of creatures
+const AREA_TYPES: [&str; 8] = [
+    "cone", "cube", "cylinder", "line", "radius", "sphere", "square", "wall",
+];
+
+/// Keys of the skills in the dnd5e system
+const SKILLS: [(&str, Skill); 18] = [
+    ("acr", Skill::Acrobatics),
+    ("ani", Skill::AnimalHandling),
+    ("arc", Skill::Arcana),
+    ("ath", Skill::Athletics),
+    ("dec", Skill::Deception),
+    ("his", Skill::History),
+    ("ins", Skill::Insight),
+    ("itm", Skill::Intimidation),
+    ("inv", Skill::Investigation),
+    ("med", Skill::Medicine),
+    ("nat", Skill::Nature),
+    ("prc", Skill::Perception),
+    ("prf", Skill::Performance),
+    ("per", Skill::Persuasion),
+    ("rel", Skill::Religion),
+    ("slt", Skill::SleightOfHand),
+    ("ste", Skill::Stealth),
+    ("sur", Skill::Survival),
+];
+
+/// Whether a JSON object is an actor exported from Foundry VTT rather than a stat block
+pub(super) fn is_actor(entry: &Value) -> bool {
+    entry.get("items").is_some_and(Value::is_array)
+        && (entry.get("system").is_some() || entry.get("data").is_some())
+}
+
+/// Translates an actor of the dnd5e system of Foundry VTT, either a player character or an NPC
+pub(super) fn import_actor(actor: &Value, report: &mut Report) -> Result<CombatantDto, LoadError> {
+    let system = system(actor);
+    let mut modifiers = [0; 6];
+    for (value, (ability, _)) in modifiers.iter_mut().zip(ABILITIES) {
+        let score = number(system.pointer(&format!("/abilities/{ability}/value")))
+            .ok_or_else(|| report.missing(ability))?;
+        *value = modifier(score);
+    }
+    let items = actor
+        .get("items")
+        .and_then(Value::as_array)
+        .map_or(&[][..], Vec::as_slice);
+    let mut importer = ActorImporter {
+        system,
+        items,
+        report,
+        modifiers,
+        level: 0,
+        proficiency: 0,
+    };
+    importer.level = importer.level();
+    importer.proficiency = importer.proficiency();
+    importer.combatant(actor)
+}
+
+struct ActorImporter<'a, 'r> {
+    system: &'a Value,
+    items: &'a [Value],
+    report: &'r mut Report,
+    /// Ability modifiers in the order of [ABILITIES]
+    modifiers: [i16; 6],
+    /// Character level, or the spellcaster level of an NPC
+    level: u32,
+    proficiency: i16,
+}
+
+/// Translated weapon or spell that can be used every turn
+struct Candidate {
+    action: ActionDto,
+    count: usize,
+}
+
+/// Translated item, either usable every turn or limited by spell slots or uses
+enum ImportedItem {
+    Unlimited(Candidate),
+    Limited(u32, RechargeActionDto),
+}
+
+impl<'a> ActorImporter<'a, '_> {
+    fn combatant(&mut self, actor: &Value) -> Result<CombatantDto, LoadError> {
+        let name = actor
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| self.report.missing("name"))?
+            .to_string();
+        // characters of recent system versions derive the maximum from their classes
+        let hp = number(self.system.pointer("/attributes/hp/max"))
+            .or_else(|| number(self.system.pointer("/attributes/hp/value")))
+            .ok_or_else(|| self.report.missing("hit points"))? as u32;
+        let ac = self.armor_class();
+        let init = self.initiative();
+        let (spell_slots, pact_slots) = self.spell_slots();
+        let hit_dice = self.hit_dice();
+        let attacks = self.attacks_per_turn();
+
+        let mut candidates = vec![];
+        let mut limited = vec![];
+        for item in self.items {
+            let imported = match item.get("type").and_then(Value::as_str) {
+                Some("weapon") => self.weapon(item, attacks),
+                Some("spell") => self.spell(item),
+                Some("feat") => {
+                    let feat = item_name(item);
+                    if !feat.starts_with("Extra Attack") {
+                        self.report
+                            .not_imported(feat, "no equivalent in the simulation");
+                    }
+                    None
+                }
+                _ => None,
+            };
+            match imported {
+                Some(ImportedItem::Unlimited(candidate)) => candidates.push(candidate),
+                Some(ImportedItem::Limited(level, special)) => limited.push((level, special)),
+                None => {}
+            }
+        }
+        let default = self.default_actions(candidates);
+        // the highest spells are cast first
+        limited.sort_by_key(|(level, _)| std::cmp::Reverse(*level));
+        let special = limited.into_iter().map(|(_, special)| special).collect();
+
+        let [str, dex, con, int, wis, cha] = self.modifiers;
+        let saves = self.saves();
+        Ok(CombatantDto {
+            name,
+            count: 1,
+            template: false,
+            hp,
+            ac,
+            init: Some(init),
+            init_bonus: 0,
+            init_advantage: false,
+            saves: Some(saves),
+            actions: ActionSelectionDto { default, special },
+            decision: DecisionDto::default(),
+            reactions: vec![],
+            legendary: LegendaryDto::default(),
+            spell_slots,
+            pact_slots,
+            slot_preference: Default::default(),
+            resources: BTreeMap::new(),
+            abilities: AbilityModifiersDto {
+                str,
+                dex,
+                con,
+                int,
+                wis,
+                cha,
+            },
+            ability_scores: None,
+            proficiency_bonus: None,
+            level: None,
+            cr: None,
+            save_proficiencies: vec![],
+            skill_proficiencies: vec![],
+            expertise: vec![],
+            skills: self.skills(),
+            spellcasting: None,
+            spell_save_dc: None,
+            spell_attack: None,
+            hit_dice,
+            targeting: None,
+            speed: number(self.system.pointer("/attributes/movement/walk")).map(|s| s as u32),
+            position: None,
+            line: None,
+            slip_past: false,
+            cover: None,
+            obscured: None,
+            surprised: false,
+            initiative_group: None,
+            morale: None,
+            leader: false,
+            location: self.report.location.clone(),
+        })
+    }
+
+    fn modifier_of(&self, ability: &SaveType) -> i16 {
+        let [str, dex, con, int, wis, cha] = self.modifiers;
+        AbilityModifiers::new(str, dex, con, int, wis, cha).modifier(ability)
+    }
+
+    fn modifier(&self, ability: &str) -> i16 {
+        ABILITIES
+            .iter()
+            .position(|(short, _)| *short == ability)
+            .map_or(0, |idx| self.modifiers[idx])
+    }
+
+    /// Sum of the levels of the class items, or the level stored with older and NPC actors
+    fn level(&self) -> u32 {
+        let class_levels: i64 = self
+            .classes()
+            .filter_map(|class| number(system(class).get("levels")))
+            .sum();
+        if class_levels > 0 {
+            return class_levels as u32;
+        }
+        number(self.system.pointer("/details/level"))
+            .or_else(|| number(self.system.pointer("/details/spellLevel")))
+            .unwrap_or(1) as u32
+    }
+
+    /// Proficiency bonus as stored by older exports, or derived from the level or challenge rating
+    fn proficiency(&self) -> i16 {
+        if let Some(prof) = number(self.system.pointer("/attributes/prof")) {
+            return prof as i16;
+        }
+        let level = match self.classes().next() {
+            Some(_) => self.level as i64,
+            None => number(self.system.pointer("/details/cr")).unwrap_or(self.level as i64),
+        };
+        2 + (level.max(1) as i16 - 1) / 4
+    }
+
+    fn classes(&self) -> impl Iterator<Item = &'a Value> {
+        self.items
+            .iter()
+            .filter(|item| item.get("type").and_then(Value::as_str) == Some("class"))
+    }
+
+    fn saves(&self) -> SaveModifiersDto {
+        let mut saves = self.modifiers;
+        for (save, (ability, _)) in saves.iter_mut().zip(ABILITIES) {
+            let proficient = number(
+                self.system
+                    .pointer(&format!("/abilities/{ability}/proficient")),
+            );
+            *save += proficient.unwrap_or(0) as i16 * self.proficiency;
+        }
+        let [str, dex, con, int, wis, cha] = saves;
+        SaveModifiersDto {
+            str,
+            dex,
+            con,
+            int,
+            wis,
+            cha,
+        }
+    }
+
+    /// Modifiers of the skills with proficiency, where the system stores 1 for proficiency,
+    /// 2 for expertise and 0.5 for half proficiency
+    fn skills(&self) -> BTreeMap<Skill, i16> {
+        let Some(skills) = self.system.get("skills").and_then(Value::as_object) else {
+            return BTreeMap::new();
+        };
+        skills
+            .iter()
+            .filter_map(|(key, skill_data)| {
+                let skill = SKILLS.iter().find(|(k, _)| k == key)?.1;
+                let rank = skill_data.get("value")?.as_f64()?;
+                (rank > 0.0).then(|| {
+                    let bonus = (rank * self.proficiency as f64).floor() as i16;
+                    (skill, self.modifier_of(&skill.ability()) + bonus)
+                })
+            })
+            .collect()
+    }
+
+    /// Armor class of the chosen calculation, where exports before the calculations only have the value
+    fn armor_class(&mut self) -> i16 {
+        let ac = self.system.pointer("/attributes/ac");
+        let field = |key| ac.and_then(|ac| ac.get(key));
+        let calc = match field("calc").and_then(Value::as_str) {
+            Some(calc) => calc,
+            None => match number(field("value")) {
+                Some(value) => return value as i16,
+                None => "default",
+            },
+        };
+        let flat = number(field("flat")).unwrap_or(10) as i16;
+        let dex = self.modifier("dex");
+        let (armor, shield) = self.armor();
+        let base = match calc {
+            "flat" => return flat,
+            "natural" => flat,
+            "mage" | "draconic" => 13 + dex,
+            "unarmoredMonk" => 10 + dex + self.modifier("wis"),
+            "unarmoredBarb" => 10 + dex + self.modifier("con"),
+            "default" => armor.unwrap_or(10 + dex),
+            calc => {
+                self.report.approximated(
+                    "Armor Class",
+                    &format!("calculation {calc} is not supported, using the equipped armor"),
+                );
+                armor.unwrap_or(10 + dex)
+            }
+        };
+        base + shield
+    }
+
+    /// Armor class of the best equipped armor and the bonus of equipped shields
+    fn armor(&self) -> (Option<i16>, i16) {
+        let dex = self.modifier("dex");
+        let mut armor = None;
+        let mut shield = 0;
+        for item in self.items.iter().filter(|item| equipped(item)) {
+            let system = system(item);
+            let Some(value) = number(system.pointer("/armor/value")) else {
+                continue;
+            };
+            let value = (value + number(system.pointer("/armor/magicalBonus")).unwrap_or(0)) as i16;
+            // the armor type moved from the armor to the item type in version 3 of the system
+            let armor_type = system
+                .pointer("/armor/type")
+                .or_else(|| system.pointer("/type/value"))
+                .and_then(Value::as_str);
+            let max_dex = number(system.pointer("/armor/dex")).map(|dex| dex as i16);
+            let with_dex = |default_max: Option<i16>| {
+                value + max_dex.or(default_max).map_or(dex, |max| dex.min(max))
+            };
+            let ac = match armor_type {
+                Some("shield") => {
+                    shield += value;
+                    continue;
+                }
+                Some("light") => with_dex(None),
+                Some("medium") => with_dex(Some(2)),
+                Some("heavy") => with_dex(Some(0)),
+                _ => continue,
+            };
+            armor = armor.max(Some(ac));
+        }
+        (armor, shield)
+    }
+
+    fn initiative(&mut self) -> i16 {
+        let dex = self.modifier("dex");
+        match self.system.pointer("/attributes/init/bonus") {
+            None | Some(Value::Null) => dex,
+            Some(Value::String(bonus)) if bonus.trim().is_empty() => dex,
+            bonus => match number(bonus) {
+                Some(bonus) => dex + bonus as i16,
+                None => {
+                    self.report
+                        .approximated("Initiative", "the bonus formula is ignored");
+                    dex
+                }
+            },
+        }
+    }
+
+    /// Spell slots of every level and pact slots, whose level depends on the warlock levels
+    fn spell_slots(&self) -> (Vec<u32>, Option<PactSlots>) {
+        let slots = |key: &str| {
+            let slot = self.system.pointer(&format!("/spells/{key}"))?;
+            number(slot.get("override"))
+                .or_else(|| number(slot.get("max")))
+                .or_else(|| number(slot.get("value")))
+                .map(|count| count.max(0) as u32)
+        };
+        let mut spell_slots: Vec<u32> = (1..=9)
+            .map(|level| slots(&format!("spell{level}")).unwrap_or(0))
+            .collect();
+        while spell_slots.last() == Some(&0) {
+            spell_slots.pop();
+        }
+        let pact_slots = slots("pact").filter(|count| *count > 0).map(|count| {
+            let warlock_levels = self
+                .classes()
+                .find(|class| item_name(class).eq_ignore_ascii_case("warlock"))
+                .and_then(|class| number(system(class).get("levels")))
+                .unwrap_or(self.level as i64);
+            let level = number(self.system.pointer("/spells/pact/level"))
+                .unwrap_or((warlock_levels + 1) / 2)
+                .clamp(1, 5);
+            PactSlots {
+                count,
+                level: level as u8,
+            }
+        });
+        (spell_slots, pact_slots)
+    }
+
+    /// Hit dice of the classes, where the largest die is used for all of them
+    fn hit_dice(&mut self) -> Option<String> {
+        let mut dice = vec![];
+        for class in self.classes() {
+            let system = system(class);
+            let die = system
+                .get("hitDice")
+                .or_else(|| system.pointer("/hd/denomination"))
+                .and_then(Value::as_str)
+                .and_then(|die| die.trim_start_matches('d').parse::<u32>().ok());
+            let levels = number(system.get("levels")).unwrap_or(0);
+            if let Some(die) = die {
+                dice.push((die, levels));
+            }
+        }
+        let largest = dice.iter().map(|(die, _)| *die).max()?;
+        if dice.iter().any(|(die, _)| *die != largest) {
+            self.report.approximated(
+                "Hit Dice",
+                &format!("the hit dice of all classes are counted as d{largest}"),
+            );
+        }
+        let count: i64 = dice.iter().map(|(_, levels)| levels).sum();
+        Some(format!("{count}d{largest}"))
+    }
+
+    /// Attacks with the Attack action, e.g. "Extra Attack (2)" allows three of them
+    fn attacks_per_turn(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.get("type").and_then(Value::as_str) == Some("feat"))
+            .filter_map(|feat| {
+                let extra = item_name(feat).strip_prefix("Extra Attack")?.trim();
+                let extra = extra.trim_start_matches('(').trim_end_matches(')');
+                Some(1 + extra.parse::<usize>().unwrap_or(1))
+            })
+            .max()
+            .unwrap_or(1)
+    }
+
+    fn weapon(&mut self, item: &Value, attacks: usize) -> Option<ImportedItem> {
+        if !equipped(item) {
+            return None;
+        }
+        let name = item_name(item);
+        let system = system(item);
+        // the attack type moved from the action type to the item type in version 4 of the system
+        let ranged = system.get("actionType").and_then(Value::as_str) == Some("rwak")
+            || system
+                .pointer("/type/value")
+                .or_else(|| system.get("weaponType"))
+                .and_then(Value::as_str)
+                .is_some_and(|weapon_type| weapon_type.ends_with('R'));
+        let ability_modifier = match system.get("ability").and_then(Value::as_str) {
+            Some(ability) if !ability.is_empty() => self.modifier(ability),
+            _ if has_property(system, "fin") => self.modifier("str").max(self.modifier("dex")),
+            _ if ranged => self.modifier("dex"),
+            _ => self.modifier("str"),
+        };
+        // proficiency is left empty for the system to derive it, which is assumed to succeed
+        let proficient = match system.get("proficient") {
+            Some(Value::Bool(proficient)) => *proficient,
+            proficient => number(proficient).is_none_or(|p| p > 0),
+        };
+        let magical = number(system.get("magicalBonus")).unwrap_or(0) as i16;
+        let attack_bonus = self.attack_bonus(name, system)?;
+        let formula = match system.pointer("/damage/base") {
+            // version 4 of the system stores the dice instead of a formula
+            Some(base) => {
+                let dice = format!(
+                    "{}d{}",
+                    number(base.get("number")).unwrap_or(1),
+                    number(base.get("denomination")).unwrap_or(0)
+                );
+                match base.get("bonus").and_then(Value::as_str) {
+                    Some(bonus) if !bonus.is_empty() => format!("{dice}+@mod+{bonus}"),
+                    _ => format!("{dice}+@mod"),
+                }
+            }
+            None => damage_parts(system)?,
+        };
+        let dmg = self.damage(name, &format!("{formula}+{magical}"), ability_modifier)?;
+        let atk = ability_modifier
+            + if proficient { self.proficiency } else { 0 }
+            + magical
+            + attack_bonus;
+        Some(ImportedItem::Unlimited(Candidate {
+            action: ActionDto::attack(name, atk, dmg, range(system)),
+            count: attacks,
+        }))
+    }
+
+    fn spell(&mut self, item: &Value) -> Option<ImportedItem> {
+        let name = item_name(item);
+        let system = system(item);
+        let level = number(system.get("level")).unwrap_or(0) as u32;
+        let mode = system
+            .pointer("/preparation/mode")
+            .and_then(Value::as_str)
+            .unwrap_or("prepared");
+        let prepared = system
+            .pointer("/preparation/prepared")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        if level > 0 && mode == "prepared" && !prepared {
+            self.report.not_imported(name, "the spell is not prepared");
+            return None;
+        }
+        let slot = match system.pointer("/activation/type").and_then(Value::as_str) {
+            None | Some("action") | Some("") => ActionSlot::Action,
+            Some("bonus") => ActionSlot::BonusAction,
+            Some(_) => {
+                self.report.not_imported(
+                    name,
+                    "only spells cast with an action or a bonus action are used",
+                );
+                return None;
+            }
+        };
+        let Some(formula) = damage_parts(system) else {
+            self.report.not_imported(name, "no damage");
+            return None;
+        };
+        let casting_modifier = self.spellcasting_modifier();
+        let mut damage = self.damage(name, &formula, casting_modifier)?;
+        let scaling = system.pointer("/scaling/mode").and_then(Value::as_str);
+        if level == 0 && scaling == Some("cantrip") {
+            damage = multiply_dice(&damage, cantrip_tier(self.level));
+        }
+        let upcast = match system.pointer("/scaling/formula").and_then(Value::as_str) {
+            Some(formula) if scaling == Some("level") && !formula.is_empty() => {
+                Some(self.damage(name, formula, casting_modifier)?)
+            }
+            _ => None,
+        };
+
+        let action = match system.get("actionType").and_then(Value::as_str) {
+            Some("rsak") | Some("msak") => ActionDto::attack(
+                name,
+                casting_modifier + self.proficiency + self.attack_bonus(name, system)?,
+                damage,
+                range(system),
+            ),
+            Some("save") => {
+                let save = system
+                    .pointer("/save/ability")
+                    .and_then(Value::as_str)
+                    .and_then(save_type);
+                let Some(save_type) = save else {
+                    self.report.not_imported(name, "unknown saving throw");
+                    return None;
+                };
+                let save_dc = match system.pointer("/save/scaling").and_then(Value::as_str) {
+                    Some("flat") => number(system.pointer("/save/dc")).unwrap_or(10) as i16,
+                    _ => 8 + self.proficiency + casting_modifier,
+                };
+                let description = system
+                    .pointer("/description/value")
+                    .and_then(Value::as_str)
+                    .unwrap_or("");
+                let (targets, area) = self.targets(name, system);
+                ActionDto::SaveBasedAttack {
+                    name: name.to_string(),
+                    save_dc: Some(save_dc),
+                    save_type,
+                    targets,
+                    area,
+                    damage,
+                    half_on_success: description.contains("half as much"),
+                    range: range(system),
+                }
+            }
+            _ => {
+                self.report
+                    .not_imported(name, "neither an attack roll nor a saving throw");
+                return None;
+            }
+        };
+        let uses = number(system.pointer("/uses/max")).filter(|uses| *uses > 0);
+        let special = |spell_level, per_day| RechargeActionDto {
+            actions: vec![action.clone()],
+            recharge: None,
+            cost: BTreeMap::new(),
+            slot,
+            spell_level,
+            upcast: upcast.clone(),
+            per_day,
+        };
+        Some(match (mode, uses) {
+            ("innate", Some(uses)) => {
+                ImportedItem::Limited(level, special(None, Some(uses as u32)))
+            }
+            ("atwill", _) | ("innate", None) => {
+                ImportedItem::Unlimited(Candidate { action, count: 1 })
+            }
+            _ if level == 0 => ImportedItem::Unlimited(Candidate { action, count: 1 }),
+            _ => ImportedItem::Limited(level, special(Some(level as u8), None)),
+        })
+    }
+
+    /// Modifier of the spellcasting ability of the actor or of its first spellcasting class,
+    /// falling back to the best mental ability
+    fn spellcasting_modifier(&self) -> i16 {
+        let ability = self
+            .system
+            .pointer("/attributes/spellcasting")
+            .and_then(Value::as_str)
+            .filter(|ability| !ability.is_empty())
+            .or_else(|| {
+                self.classes().find_map(|class| {
+                    system(class)
+                        .pointer("/spellcasting/ability")
+                        .and_then(Value::as_str)
+                        .filter(|ability| !ability.is_empty())
+                })
+            });
+        match ability {
+            Some(ability) => self.modifier(ability),
+            None => ["int", "wis", "cha"]
+                .iter()
+                .map(|ability| self.modifier(ability))
+                .max()
+                .unwrap_or(0),
+        }
+    }
+
+    /// Flat attack bonus of an item, which can also be a formula that is not supported
+    fn attack_bonus(&mut self, name: &str, system: &Value) -> Option<i16> {
+        match system.get("attackBonus") {
+            None | Some(Value::Null) => Some(0),
+            Some(Value::String(bonus)) if bonus.trim().is_empty() => Some(0),
+            bonus => {
+                let resolved = number(bonus).or_else(|| {
+                    let bonus = bonus?
+                        .as_str()?
+                        .replace("@prof", &self.proficiency.to_string());
+                    number(Some(&Value::String(bonus)))
+                });
+                if resolved.is_none() {
+                    self.report
+                        .not_imported(name, "the attack bonus formula is not supported");
+                }
+                resolved.map(|bonus| bonus as i16)
+            }
+        }
+    }
+
+    /// Damage dice of a formula with the ability modifier and proficiency bonus filled in
+    fn damage(&mut self, name: &str, formula: &str, ability_modifier: i16) -> Option<String> {
+        let resolved = resolve_formula(formula, ability_modifier, self.proficiency);
+        if resolved.is_none() {
+            self.report.not_imported(
+                name,
+                &format!("the damage formula {formula:?} is not supported"),
+            );
+        }
+        resolved
+    }
+
+    /// Either the number of targets or the area of effect, e.g. `{"value": 20, "type": "sphere"}`
+    /// or the template of newer versions
+    fn targets(&mut self, name: &str, system: &Value) -> (Option<usize>, Option<AreaDto>) {
+        let (target_type, size) = match system.pointer("/target/template") {
+            Some(template) => (template.get("type"), template.get("size")),
+            None => (
+                system.pointer("/target/type"),
+                system.pointer("/target/value"),
+            ),
+        };
+        let target_type = target_type.and_then(Value::as_str).unwrap_or_default();
+        if !AREA_TYPES.contains(&target_type) {
+            let targets = number(system.pointer("/target/value")).map_or(1, |t| t.max(1));
+            return (Some(targets as usize), None);
+        }
+        match number(size).and_then(|size| area(target_type, size)) {
+            Some(area) => {
+                if target_type == "wall" {
+                    self.report
+                        .approximated(name, "treating the wall as a line");
+                }
+                (None, Some(area))
+            }
+            None => {
+                self.report.approximated(
+                    name,
+                    &format!("assuming {AREA_TARGETS} creatures in the area"),
+                );
+                (Some(AREA_TARGETS), None)
+            }
+        }
+    }
+
+    /// The candidate with the most damage per turn becomes the default action
+    fn default_actions(&mut self, candidates: Vec<Candidate>) -> Vec<ActionDto> {
+        let damage_per_turn = |candidate: &Candidate| {
+            let (damage, targets) = match &candidate.action {
+                ActionDto::Attack { dmg, .. } => (dmg.as_deref().unwrap_or(""), 1),
+                ActionDto::SaveBasedAttack {
+                    damage,
+                    targets,
+                    area,
+                    ..
+                } => {
+                    let area = area.clone().map(|area| Area::from(area).crowd_targets());
+                    (damage.as_str(), targets.or(area).unwrap_or(1))
+                }
+            };
+            let mean = DamageRoll::from_str(damage).map_or(0.0, |roll| roll.mean());
+            mean * (candidate.count * targets) as f32
+        };
+        let best = candidates
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| damage_per_turn(a).total_cmp(&damage_per_turn(b)))
+            .map(|(idx, _)| idx);
+        let mut default = vec![];
+        for (idx, candidate) in candidates.into_iter().enumerate() {
+            if Some(idx) == best {
+                default = vec![candidate.action; candidate.count];
+            } else {
+                self.report.not_imported(
+                    candidate.action.name(),
+                    "only the default attacks are used every turn",
+                );
+            }
+        }
+        default
+    }
+}
+
+/// Data of an actor or item, which exports before Foundry VTT 10 store under "data"
+fn system(entry: &Value) -> &Value {
+    entry
+        .get("system")
+        .or_else(|| entry.get("data"))
+        .unwrap_or(&Value::Null)
+}
+
+fn item_name(item: &Value) -> &str {
+    item.get("name").and_then(Value::as_str).unwrap_or("?")
+}
+
+/// Number of a field, which Foundry stores as a number or as a numeric string
+/// Normal range of a weapon or spell in feet, or the reach of a melee weapon with the reach property
+fn range(system: &Value) -> Option<u32> {
+    let units = system.pointer("/range/units").and_then(Value::as_str);
+    let range = number(system.pointer("/range/value"))
+        .filter(|range| *range > 0 && units.is_none_or(|units| units == "ft"));
+    match range {
+        Some(range) => Some(range as u32),
+        None if has_property(system, "rch") => Some(10),
+        None => None,
+    }
+}
+
+fn number(value: Option<&Value>) -> Option<i64> {
+    match value? {
+        Value::Number(number) => number
+            .as_i64()
+            .or_else(|| number.as_f64().map(|number| number as i64)),
+        Value::String(number) => number.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Items that cannot be equipped, such as spells, count as equipped
+fn equipped(item: &Value) -> bool {
+    system(item)
+        .get("equipped")
+        .and_then(Value::as_bool)
+        .unwrap_or(true)
+}
+
+/// Weapon property, which is a list in recent versions of the system and a map of flags before
+fn has_property(system: &Value, property: &str) -> bool {
+    match system.get("properties") {
+        Some(Value::Array(properties)) => properties.iter().any(|p| p == property),
+        Some(Value::Object(properties)) => properties
+            .get(property)
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Formulas of the damage parts of an item added up, e.g. `[["1d8 + @mod", "slashing"]]`
+fn damage_parts(system: &Value) -> Option<String> {
+    let parts: Vec<&str> = system
+        .pointer("/damage/parts")?
+        .as_array()?
+        .iter()
+        .filter_map(|part| part.get(0)?.as_str())
+        .filter(|formula| !formula.trim().is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("+"))
+}
+
+/// Damage dice of a formula such as "1d8 + @mod[slashing]", or none if it uses further roll data
+fn resolve_formula(formula: &str, ability_modifier: i16, proficiency: i16) -> Option<String> {
+    let mut plain = String::new();
+    let mut flavour = 0;
+    for c in formula.chars() {
+        match c {
+            '[' => flavour += 1,
+            ']' => flavour -= 1,
+            c if flavour == 0 && !c.is_whitespace() => plain.push(c),
+            _ => {}
+        }
+    }
+    let resolved = plain
+        .replace("@mod", &ability_modifier.to_string())
+        .replace("@prof", &proficiency.to_string())
+        .replace("+-", "-")
+        .replace("--", "+");
+    let resolved = resolved.trim_end_matches("+0").to_string();
+    DamageRoll::from_str(&resolved).ok()?;
+    Some(resolved)
+}
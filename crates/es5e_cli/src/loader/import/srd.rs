@@ -0,0 +1,609 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use lib_es5e_core::{
+    action::action::ActionSlot, attack::damage::DamageRoll, combatant::skill::Skill,
+    utils::save::SaveType,
+};
+use serde_json::Value;
+
+use super::{area, modifier, save_type, skill, Report, ABILITIES, AREA_TARGETS};
+use crate::loader::{
+    error::LoadError, AbilityModifiersDto, ActionDto, ActionSelectionDto, AreaDto, CombatantDto,
+    DecisionDto, LegendaryDto, RechargeActionDto, RechargeDto, SaveModifiersDto,
+};
+
+/// Translates a monster stat block in the JSON structure of the 5e SRD API or of Open5e
+pub(super) fn import_monster(
+    monster: &Value,
+    report: &mut Report,
+) -> Result<CombatantDto, LoadError> {
+    MonsterImporter { monster, report }.combatant()
+}
+
+struct MonsterImporter<'a, 'r> {
+    monster: &'a Value,
+    report: &'r mut Report,
+}
+
+/// Translated action of a stat block
+struct ImportedAction {
+    name: String,
+    action: ActionDto,
+    recharge: Option<RechargeDto>,
+    per_day: Option<u32>,
+}
+
+impl<'a> MonsterImporter<'a, '_> {
+    fn combatant(&mut self) -> Result<CombatantDto, LoadError> {
+        let name = self
+            .str("name")
+            .ok_or_else(|| self.report.missing("name"))?
+            .to_string();
+        let hp = self
+            .int("hit_points")
+            .ok_or_else(|| self.report.missing("hit_points"))? as u32;
+        let ac = match self.monster.get("armor_class") {
+            // the 5e SRD API lists every kind of armor, Open5e only the value
+            Some(Value::Array(armor)) => armor.first().and_then(|a| a.get("value")),
+            armor => armor,
+        }
+        .and_then(Value::as_i64)
+        .ok_or_else(|| self.report.missing("armor_class"))? as i16;
+        let abilities = self.ability_modifiers()?;
+        let saves = self.saves(&abilities);
+
+        let mut imported = vec![];
+        let mut multiattack = None;
+        for action in self.list("actions") {
+            let action_name = action.get("name").and_then(Value::as_str).unwrap_or("?");
+            if action_name.to_lowercase().starts_with("multiattack") {
+                multiattack = Some(action);
+            } else if let Some(action) = self.action(action) {
+                imported.push(action);
+            }
+        }
+        let (default, special) = self.action_selection(multiattack, imported);
+        let legendary = self.legendary();
+        for part in ["reactions", "special_abilities"] {
+            for ability in self.list(part) {
+                let ability_name = ability.get("name").and_then(Value::as_str).unwrap_or("?");
+                if !ability_name.starts_with("Legendary Resistance") {
+                    self.report
+                        .not_imported(ability_name, "no equivalent in the simulation");
+                }
+            }
+        }
+
+        Ok(CombatantDto {
+            name,
+            count: 1,
+            template: false,
+            hp,
+            ac,
+            init: Some(abilities.dex),
+            init_bonus: 0,
+            init_advantage: false,
+            saves: Some(saves),
+            actions: ActionSelectionDto { default, special },
+            decision: DecisionDto::default(),
+            reactions: vec![],
+            legendary,
+            spell_slots: vec![],
+            pact_slots: None,
+            slot_preference: Default::default(),
+            resources: BTreeMap::new(),
+            abilities,
+            ability_scores: None,
+            proficiency_bonus: None,
+            level: None,
+            cr: None,
+            save_proficiencies: vec![],
+            skill_proficiencies: vec![],
+            expertise: vec![],
+            skills: self.skills(),
+            spellcasting: None,
+            spell_save_dc: None,
+            spell_attack: None,
+            hit_dice: None,
+            targeting: None,
+            speed: self.speed(),
+            position: None,
+            line: None,
+            slip_past: false,
+            cover: None,
+            obscured: None,
+            surprised: false,
+            initiative_group: None,
+            morale: None,
+            leader: false,
+            location: self.report.location.clone(),
+        })
+    }
+
+    /// Walking speed, e.g. "walk": "40 ft." in the 5e SRD API or "walk": 40 in Open5e
+    fn speed(&self) -> Option<u32> {
+        let walk = self.monster.get("speed")?.get("walk")?;
+        match walk {
+            Value::String(speed) => speed.split_whitespace().next()?.parse().ok(),
+            speed => speed.as_u64().map(|speed| speed as u32),
+        }
+    }
+
+    fn ability_modifiers(&self) -> Result<AbilityModifiersDto, LoadError> {
+        let mut modifiers = [0; 6];
+        for (value, (_, ability)) in modifiers.iter_mut().zip(ABILITIES) {
+            let score = self
+                .int(ability)
+                .ok_or_else(|| self.report.missing(ability))?;
+            *value = modifier(score);
+        }
+        let [str, dex, con, int, wis, cha] = modifiers;
+        Ok(AbilityModifiersDto {
+            str,
+            dex,
+            con,
+            int,
+            wis,
+            cha,
+        })
+    }
+
+    /// Saving throw proficiencies, falling back to the ability modifiers
+    fn saves(&self, abilities: &AbilityModifiersDto) -> SaveModifiersDto {
+        let mut saves = [
+            abilities.str,
+            abilities.dex,
+            abilities.con,
+            abilities.int,
+            abilities.wis,
+            abilities.cha,
+        ];
+        for (save, (short, ability)) in saves.iter_mut().zip(ABILITIES) {
+            // Open5e has e.g. "dexterity_save", the 5e SRD API a "saving-throw-dex" proficiency
+            let open5e = self.int(&format!("{ability}_save"));
+            let srd = self.list("proficiencies").iter().find_map(|p| {
+                let index = p.get("proficiency")?.get("index")?.as_str()?;
+                if index != format!("saving-throw-{short}") {
+                    return None;
+                }
+                p.get("value")?.as_i64()
+            });
+            if let Some(value) = open5e.or(srd) {
+                *save = value as i16;
+            }
+        }
+        let [str, dex, con, int, wis, cha] = saves;
+        SaveModifiersDto {
+            str,
+            dex,
+            con,
+            int,
+            wis,
+            cha,
+        }
+    }
+
+    /// Skill modifiers, e.g. "stealth": 6 in Open5e or a "skill-stealth" proficiency in the 5e SRD API
+    fn skills(&self) -> BTreeMap<Skill, i16> {
+        let open5e = self
+            .monster
+            .get("skills")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, value)| Some((skill(name)?, value.as_i64()? as i16)));
+        let srd = self.list("proficiencies").iter().filter_map(|p| {
+            let index = p.get("proficiency")?.get("index")?.as_str()?;
+            let name = index.strip_prefix("skill-")?;
+            Some((skill(name)?, p.get("value")?.as_i64()? as i16))
+        });
+        open5e.chain(srd).collect()
+    }
+
+    fn action(&mut self, action: &Value) -> Option<ImportedAction> {
+        let full_name = action.get("name").and_then(Value::as_str).unwrap_or("?");
+        let (name, usage_text) = split_usage(full_name);
+        let desc = action.get("desc").and_then(Value::as_str).unwrap_or("");
+        let Some(damage) = damage(action, desc) else {
+            self.report.not_imported(name, "no damage");
+            return None;
+        };
+        // Open5e has an attack bonus of 0 for actions without attack roll
+        let attack_bonus = action
+            .get("attack_bonus")
+            .and_then(Value::as_i64)
+            .filter(|&bonus| bonus != 0)
+            .map(|bonus| bonus as i16)
+            .or_else(|| attack_bonus_in_description(desc));
+        let action_dto = if let Some(atk) = attack_bonus {
+            ActionDto::attack(name, atk, damage, range_in_description(desc))
+        } else if let Some((save_type, save_dc, half_on_success)) = save(action, desc) {
+            let lowercase = desc.to_lowercase();
+            let area = area_in_description(&lowercase);
+            let targets = if area.is_some() {
+                None
+            } else if lowercase.contains("each creature") || lowercase.contains("each target") {
+                self.report.approximated(
+                    name,
+                    &format!("assuming {AREA_TARGETS} creatures in the area"),
+                );
+                Some(AREA_TARGETS)
+            } else {
+                Some(1)
+            };
+            ActionDto::SaveBasedAttack {
+                name: name.to_string(),
+                save_dc: Some(save_dc),
+                save_type,
+                targets,
+                area,
+                damage,
+                half_on_success,
+                range: None,
+            }
+        } else {
+            self.report
+                .not_imported(name, "neither an attack roll nor a saving throw");
+            return None;
+        };
+
+        let (recharge, per_day) = match action.get("usage") {
+            Some(usage) => self.structured_usage(name, usage),
+            None => match usage_text {
+                Some(usage) => self.usage(name, usage),
+                None => (None, None),
+            },
+        };
+        Some(ImportedAction {
+            name: name.to_string(),
+            action: action_dto,
+            recharge,
+            per_day,
+        })
+    }
+
+    /// Usage limit of the 5e SRD API, e.g. `{"type": "recharge on roll", "min_value": 5}`
+    fn structured_usage(
+        &mut self,
+        name: &str,
+        usage: &Value,
+    ) -> (Option<RechargeDto>, Option<u32>) {
+        let int = |key| usage.get(key).and_then(Value::as_u64);
+        match usage.get("type").and_then(Value::as_str) {
+            Some("recharge on roll") => match int("min_value") {
+                Some(min) => (Some(self.recharge(name, min as u8)), None),
+                None => (None, None),
+            },
+            Some("per day") => (None, int("times").map(|times| times as u32)),
+            Some("recharge after rest") => (Some(RechargeDto::ShortRest), None),
+            _ => {
+                self.report
+                    .not_imported(name, "unknown usage limit, usable every turn");
+                (None, None)
+            }
+        }
+    }
+
+    /// Usage limit in the name of an action, e.g. "Recharge 5–6" or "3/Day"
+    fn usage(&mut self, name: &str, usage: &str) -> (Option<RechargeDto>, Option<u32>) {
+        let lowercase = usage.to_lowercase();
+        let first_number = lowercase
+            .split(|c: char| !c.is_ascii_digit())
+            .find_map(|number| number.parse::<u32>().ok());
+        if lowercase.starts_with("recharges after") {
+            let recharge = if lowercase.contains("short") {
+                RechargeDto::ShortRest
+            } else {
+                RechargeDto::LongRest
+            };
+            (Some(recharge), None)
+        } else if lowercase.starts_with("recharge") {
+            match first_number {
+                Some(min) => (Some(self.recharge(name, min as u8)), None),
+                None => (None, None),
+            }
+        } else if lowercase.contains("/day") {
+            (None, first_number)
+        } else {
+            (None, None)
+        }
+    }
+
+    fn recharge(&mut self, name: &str, min: u8) -> RechargeDto {
+        if min < 5 {
+            self.report
+                .approximated(name, &format!("recharges on 5–6 instead of {min}–6"));
+            RechargeDto::Roll(5)
+        } else {
+            RechargeDto::Roll(min)
+        }
+    }
+
+    /// The multiattack, or else the first attack without a usage limit, becomes the default action.
+    /// Actions with a usage limit are used whenever they are available.
+    fn action_selection(
+        &mut self,
+        multiattack: Option<&Value>,
+        imported: Vec<ImportedAction>,
+    ) -> (Vec<ActionDto>, Vec<RechargeActionDto>) {
+        let (limited, unlimited): (Vec<_>, Vec<_>) = imported
+            .into_iter()
+            .partition(|a| a.recharge.is_some() || a.per_day.is_some());
+        let default = match multiattack.map(|m| self.multiattack(m, &unlimited)) {
+            Some(attacks) if !attacks.is_empty() => attacks,
+            _ => unlimited
+                .first()
+                .map(|a| a.action.clone())
+                .into_iter()
+                .collect(),
+        };
+        for unused in unlimited
+            .iter()
+            .filter(|a| !default.iter().any(|d| d.name() == a.name))
+        {
+            self.report
+                .not_imported(&unused.name, "only the default attacks are used every turn");
+        }
+        let special = limited
+            .into_iter()
+            .map(|a| RechargeActionDto {
+                actions: vec![a.action],
+                recharge: a.recharge,
+                cost: BTreeMap::new(),
+                slot: ActionSlot::Action,
+                spell_level: None,
+                upcast: None,
+                per_day: a.per_day,
+            })
+            .collect();
+        (default, special)
+    }
+
+    fn multiattack(&mut self, multiattack: &Value, attacks: &[ImportedAction]) -> Vec<ActionDto> {
+        let desc = multiattack
+            .get("desc")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        // the 5e SRD API lists the attacks, Open5e only describes them
+        let counts: Vec<(String, usize)> =
+            match multiattack.get("actions").and_then(Value::as_array) {
+                Some(actions) => actions
+                    .iter()
+                    .filter_map(|a| {
+                        let name = a.get("action_name")?.as_str()?.to_string();
+                        let count = match a.get("count")? {
+                            Value::String(count) => count.parse().ok()?,
+                            count => count.as_u64()? as usize,
+                        };
+                        Some((name, count))
+                    })
+                    .collect(),
+                None => attacks_in_description(desc),
+            };
+        let mut default = vec![];
+        for (name, count) in counts {
+            match attacks.iter().find(|a| same_attack(&a.name, &name)) {
+                Some(attack) => default.extend(vec![attack.action.clone(); count]),
+                None => self.report.not_imported(
+                    &format!("Multiattack ({name})"),
+                    "the attack is not imported",
+                ),
+            }
+        }
+        if default.is_empty() {
+            self.report
+                .not_imported("Multiattack", "the attacks could not be recognised");
+        }
+        default
+    }
+
+    fn legendary(&mut self) -> LegendaryDto {
+        // "Legendary Resistance (3/Day)" in Open5e, a usage of 3 times per day in the 5e SRD API
+        let resistances = self
+            .list("special_abilities")
+            .iter()
+            .find(|a| {
+                a.get("name")
+                    .and_then(Value::as_str)
+                    .is_some_and(|name| name.starts_with("Legendary Resistance"))
+            })
+            .and_then(|ability| {
+                let usage = ability.get("usage").and_then(|u| u.get("times")?.as_u64());
+                let name = ability.get("name").and_then(Value::as_str).unwrap_or("");
+                usage
+                    .map(|times| times as u32)
+                    .or_else(|| split_usage(name).1?.split('/').next()?.trim().parse().ok())
+            })
+            .unwrap_or(0);
+        for action in self.list("legendary_actions") {
+            let name = action.get("name").and_then(Value::as_str).unwrap_or("?");
+            self.report
+                .not_imported(name, "legendary actions have to be configured by hand");
+        }
+        LegendaryDto {
+            points: 0,
+            resistances,
+            actions: vec![],
+        }
+    }
+
+    fn str(&self, key: &str) -> Option<&'a str> {
+        self.monster.get(key).and_then(Value::as_str)
+    }
+
+    fn int(&self, key: &str) -> Option<i64> {
+        self.monster.get(key).and_then(Value::as_i64)
+    }
+
+    fn list(&self, key: &str) -> &'a [Value] {
+        self.monster
+            .get(key)
+            .and_then(Value::as_array)
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Splits "Fire Breath (Recharge 5–6)" into the name and the usage limit in parentheses
+fn split_usage(name: &str) -> (&str, Option<&str>) {
+    match name.split_once('(') {
+        Some((name, usage)) => (name.trim(), Some(usage.trim_end_matches(')').trim())),
+        None => (name.trim(), None),
+    }
+}
+
+/// Damage dice of an action. The 5e SRD API lists the dice of every damage type, Open5e has dice and a bonus,
+/// and otherwise the description is searched, e.g. "Hit: 17 (2d10 + 6) piercing damage plus 4 (1d8) acid damage."
+fn damage(action: &Value, desc: &str) -> Option<String> {
+    let listed: Vec<&str> = action
+        .get("damage")
+        .and_then(Value::as_array)
+        .map(|damage| {
+            damage
+                .iter()
+                .filter_map(|d| d.get("damage_dice")?.as_str())
+                .collect()
+        })
+        .unwrap_or_default();
+    if !listed.is_empty() {
+        return Some(listed.join("+"));
+    }
+    if let Some(dice) = action.get("damage_dice").and_then(Value::as_str) {
+        let bonus = action
+            .get("damage_bonus")
+            .and_then(Value::as_i64)
+            .unwrap_or(0);
+        return Some(format!("{dice}{bonus:+}"));
+    }
+    dice_in_description(desc)
+}
+
+/// Dice in parentheses, where further dice only count if they are added with "plus",
+/// e.g. not the two-handed damage of a versatile weapon
+fn dice_in_description(desc: &str) -> Option<String> {
+    let mut rest = desc.split_once("Hit:").map_or(desc, |(_, hit)| hit);
+    let mut dice: Vec<String> = vec![];
+    while let Some((before, after)) = rest.split_once('(') {
+        let Some((group, after)) = after.split_once(')') else {
+            break;
+        };
+        if group.contains('d') && DamageRoll::from_str(group).is_ok() {
+            if !dice.is_empty() && !before.contains("plus") {
+                break;
+            }
+            dice.push(group.replace(' ', ""));
+        }
+        rest = after;
+    }
+    (!dice.is_empty()).then(|| dice.join("+"))
+}
+
+/// Attack bonus in a description such as "Melee Weapon Attack: +4 to hit"
+fn attack_bonus_in_description(desc: &str) -> Option<i16> {
+    let (before, _) = desc.split_once(" to hit")?;
+    before.split_whitespace().last()?.parse().ok()
+}
+
+/// Reach or normal range of an attack, e.g. "reach 10 ft." or "range 80/320 ft."
+fn range_in_description(desc: &str) -> Option<u32> {
+    let mut words = desc.split_whitespace();
+    words.find(|w| *w == "reach" || *w == "range")?;
+    let distance = words.next()?;
+    distance.split('/').next()?.parse().ok()
+}
+
+/// Saving throw of an action, from the structured DC of the 5e SRD API or from a description
+/// such as "DC 18 Dexterity saving throw, taking 54 (12d8) acid damage on a failed save, or half as much"
+fn save(action: &Value, desc: &str) -> Option<(SaveType, i16, bool)> {
+    if let Some(dc) = action.get("dc") {
+        let ability = dc.get("dc_type")?.get("index")?.as_str()?;
+        let value = dc.get("dc_value")?.as_i64()? as i16;
+        let half = dc.get("success_type").and_then(Value::as_str) == Some("half");
+        return Some((save_type(ability)?, value, half));
+    }
+    let (_, after) = desc.split_once("DC ")?;
+    let mut words = after.split_whitespace();
+    let value = words.next()?.parse().ok()?;
+    let ability = words.next()?;
+    let half = desc.contains("half as much");
+    Some((save_type(ability)?, value, half))
+}
+
+/// Area of effect in a lowercase description, e.g. "exhales acid in a 60-foot line", "a 20-foot-radius sphere"
+/// or "each creature within 10 feet of the golem"
+fn area_in_description(desc: &str) -> Option<AreaDto> {
+    let words: Vec<&str> = desc
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '.')
+        .collect();
+    for (idx, word) in words.iter().enumerate() {
+        if let Some((size, shape)) = word.split_once("-foot") {
+            let shape = match shape.strip_prefix('-') {
+                Some(shape) => shape,
+                None => words.get(idx + 1)?,
+            };
+            if let Some(area) = size.parse().ok().and_then(|size| area(shape, size)) {
+                return Some(area);
+            }
+        }
+        if words.get(idx.saturating_sub(2)..idx) == Some(&["each", "creature"])
+            && *word == "within"
+            && words.get(idx + 2) == Some(&"feet")
+        {
+            let size = words.get(idx + 1)?.parse().ok()?;
+            return area("emanation", size);
+        }
+    }
+    None
+}
+
+/// Attacks of a multiattack description, e.g. "The dragon makes three attacks: one with its bite
+/// and two with its claws." or "The ogre makes two greatclub attacks."
+fn attacks_in_description(desc: &str) -> Vec<(String, usize)> {
+    let lowercase = desc.to_lowercase();
+    let words: Vec<&str> = lowercase
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '.' || c == ':')
+        .filter(|w| !w.is_empty())
+        .collect();
+    let mut attacks = vec![];
+    for (idx, word) in words.iter().enumerate() {
+        let Some(count) = number(word) else {
+            continue;
+        };
+        if words.get(idx + 1..idx + 3) == Some(&["with", "its"]) {
+            let name: Vec<&str> = words[idx + 3..]
+                .iter()
+                .take_while(|w| !["and", "or"].contains(w))
+                .copied()
+                .collect();
+            attacks.push((name.join(" "), count));
+        } else if let Some(end) = words[idx + 1..].iter().position(|w| *w == "attacks") {
+            let name = words[idx + 1..idx + 1 + end].join(" ");
+            let generic = [
+                "",
+                "melee",
+                "ranged",
+                "weapon",
+                "melee weapon",
+                "ranged weapon",
+            ];
+            if !generic.contains(&name.as_str()) && end <= 3 {
+                attacks.push((name, count));
+            }
+        }
+    }
+    attacks
+}
+
+fn number(word: &str) -> Option<usize> {
+    let numbers = ["one", "two", "three", "four", "five", "six"];
+    numbers
+        .iter()
+        .position(|n| *n == word)
+        .map(|idx| idx + 1)
+        .or_else(|| word.parse().ok())
+}
+
+/// Whether an action name refers to the attack, ignoring case and plurals
+fn same_attack(attack: &str, name: &str) -> bool {
+    let normalise = |s: &str| s.to_lowercase().trim_end_matches('s').to_string();
+    normalise(attack) == normalise(name)
+}
@@ -0,0 +1,182 @@
+use std::sync::OnceLock;
+
+use serde_yaml::{Mapping, Value};
+
+use super::{
+    error::{LoadError, Location, Problem},
+    merge,
+};
+
+const MONSTERS: &str = include_str!("../../library/monsters.yaml");
+const SPELLS: &str = include_str!("../../library/spells.yaml");
+const VALID_LIBRARY: &str = "the built-in library is valid";
+
+/// Character levels at which cantrips deal another die of damage
+const CANTRIP_LEVELS: [u32; 3] = [5, 11, 17];
+
+fn monsters() -> &'static [Value] {
+    static MONSTERS_LIBRARY: OnceLock<Vec<Value>> = OnceLock::new();
+    MONSTERS_LIBRARY.get_or_init(|| serde_yaml::from_str(MONSTERS).expect(VALID_LIBRARY))
+}
+
+fn spells() -> &'static Mapping {
+    static SPELLS_LIBRARY: OnceLock<Mapping> = OnceLock::new();
+    SPELLS_LIBRARY.get_or_init(|| serde_yaml::from_str(SPELLS).expect(VALID_LIBRARY))
+}
+
+/// Merges an entry that refers to a monster of the library, e.g. `monster: goblin`, with that monster.
+/// The other fields of the entry override those of the monster.
+pub(super) fn with_monster(mut entry: Value, location: &Location) -> Result<Value, LoadError> {
+    let Some(monster) = entry.as_mapping_mut().and_then(|e| e.remove("monster")) else {
+        return Ok(entry);
+    };
+    let name = monster.as_str().unwrap_or_default();
+    let base = monsters()
+        .iter()
+        .find(|m| {
+            m.get("name")
+                .and_then(Value::as_str)
+                .is_some_and(|m| m.eq_ignore_ascii_case(name))
+        })
+        .ok_or_else(|| LoadError::invalid(location, Problem::UnknownMonster(name.to_string())))?;
+    Ok(merge(base.clone(), entry))
+}
+
+/// Replaces the references to spells of the library among the actions of an entry.
+/// A default action `spell: fire bolt` is replaced by the actions of the cantrip, whose dice
+/// increase with the `level` of the entry. A special action `spell: fireball` is replaced by the
+/// spell, which with `slot: 4` is always cast with a slot of that level. The other fields of a
+/// special action override those of the spell, e.g. `per_day: 1`.
+pub(super) fn expand_spells(entry: &mut Value, location: &Location) -> Result<(), LoadError> {
+    let level = entry.get("level").and_then(Value::as_u64).unwrap_or(1) as u32;
+    let Some(actions) = entry.get_mut("actions") else {
+        return Ok(());
+    };
+    if let Some(Value::Sequence(default)) = actions.get_mut("default") {
+        let mut expanded = vec![];
+        for action in default.drain(..) {
+            let Some(name) = action.get("spell").and_then(Value::as_str) else {
+                expanded.push(action);
+                continue;
+            };
+            let mut spell = spell(name, level, location)?;
+            if spell_level(&spell) > 0 {
+                let problem = Problem::InvalidSpellSlot {
+                    spell: name.to_string(),
+                    slot: 0,
+                };
+                return Err(LoadError::invalid(&location.action(name), problem));
+            }
+            if let Some(Value::Sequence(actions)) = spell.get_mut("actions") {
+                expanded.append(actions);
+            }
+        }
+        *default = expanded;
+    }
+    if let Some(Value::Sequence(special)) = actions.get_mut("special") {
+        for action in special.iter_mut() {
+            let Some(mut reference) = action.as_mapping().cloned() else {
+                continue;
+            };
+            let Some(name) = reference.remove("spell") else {
+                continue;
+            };
+            let name = name.as_str().unwrap_or_default();
+            let mut spell = spell(name, level, location)?;
+            // a spell slot level rather than the action slot, e.g. `slot: bonus_action`
+            if let Some(slot) = reference.get("slot").and_then(Value::as_u64) {
+                reference.remove("slot");
+                upcast(&mut spell, name, slot as u8, location)?;
+            }
+            *action = merge(spell, Value::Mapping(reference));
+        }
+    }
+    Ok(())
+}
+
+/// Spell of the library, with the dice of cantrips increased for the caster's level
+fn spell(name: &str, level: u32, location: &Location) -> Result<Value, LoadError> {
+    let mut spell = spells()
+        .iter()
+        .find(|(key, _)| {
+            key.as_str()
+                .is_some_and(|key| key.eq_ignore_ascii_case(name))
+        })
+        .map(|(_, spell)| spell.clone())
+        .ok_or_else(|| {
+            LoadError::invalid(
+                &location.action(name),
+                Problem::UnknownSpell(name.to_string()),
+            )
+        })?;
+    if spell_level(&spell) == 0 {
+        let tier = cantrip_tier(level);
+        map_damage(&mut spell, |dice| multiply_dice(dice, tier));
+    }
+    Ok(spell)
+}
+
+/// Casts a spell with a higher slot, which adds its upcast damage for every level above the spell's
+fn upcast(spell: &mut Value, name: &str, slot: u8, location: &Location) -> Result<(), LoadError> {
+    let level = spell_level(spell);
+    if slot < level.max(1) {
+        let problem = Problem::InvalidSpellSlot {
+            spell: name.to_string(),
+            slot,
+        };
+        return Err(LoadError::invalid(&location.action(name), problem));
+    }
+    let upcast = spell.get("upcast").and_then(Value::as_str);
+    if let Some(upcast) = upcast.filter(|_| slot > level) {
+        let extra = multiply_dice(upcast, (slot - level) as u32);
+        map_damage(spell, |dice| format!("{dice}+{extra}"));
+    }
+    spell["spell_level"] = Value::from(slot);
+    Ok(())
+}
+
+fn spell_level(spell: &Value) -> u8 {
+    spell
+        .get("spell_level")
+        .and_then(Value::as_u64)
+        .unwrap_or_default() as u8
+}
+
+/// Changes the damage dice of every action of a spell
+fn map_damage(spell: &mut Value, change: impl Fn(&str) -> String) {
+    let Some(Value::Sequence(actions)) = spell.get_mut("actions") else {
+        return;
+    };
+    for action in actions {
+        // the actions are tagged with their kind, e.g. `!Attack`
+        let action = match action {
+            Value::Tagged(tagged) => &mut tagged.value,
+            action => action,
+        };
+        for key in ["dmg", "damage"] {
+            if let Some(Value::String(dice)) = action.get_mut(key) {
+                *dice = change(dice);
+            }
+        }
+    }
+}
+
+/// Multiplier of the damage dice of cantrips cast by a character of the given level
+pub(super) fn cantrip_tier(level: u32) -> u32 {
+    1 + CANTRIP_LEVELS.iter().filter(|l| level >= **l).count() as u32
+}
+
+/// Multiplies the dice of a formula, e.g. for the damage of cantrips at higher levels
+pub(super) fn multiply_dice(formula: &str, factor: u32) -> String {
+    formula
+        .split('+')
+        .map(|term| match term.split_once('d') {
+            Some((count, sides)) => match count.parse::<u32>() {
+                Ok(count) => format!("{}d{sides}", count * factor),
+                Err(_) => term.to_string(),
+            },
+            None => term.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
@@ -0,0 +1,311 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    slice,
+    str::FromStr,
+};
+
+use lib_es5e_core::{
+    action::reaction::ReactionTrigger,
+    attack::damage::DamageRoll,
+    combatant::{hit_dice::HitDice, spell_slots::SpellSlots},
+};
+
+use super::{
+    error::{LoadError, Location, Problem},
+    ActionDto, CombatantDto, ReactionEffectDto, RechargeDto,
+};
+
+/// Suspicious value that does not keep the simulation from running
+#[derive(Debug, PartialEq)]
+pub struct Warning {
+    pub location: Location,
+    pub kind: WarningKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum WarningKind {
+    MoreTargetsThanEnemies {
+        targets: usize,
+        enemies: usize,
+    },
+    NeverRecharges(u8),
+    NoHp,
+    /// Part of an imported stat block that has no equivalent in the simulation
+    NotImported(String),
+    /// Part of an imported stat block that is only imported approximately
+    Approximated(String),
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.location)?;
+        match &self.kind {
+            WarningKind::MoreTargetsThanEnemies { targets, enemies } => {
+                write!(f, "{targets} targets, but only {enemies} enemies")
+            }
+            WarningKind::NeverRecharges(value) => {
+                write!(f, "recharge on {value} never succeeds on a d6")
+            }
+            WarningKind::NoHp => write!(f, "no hit points"),
+            WarningKind::NotImported(reason) => write!(f, "not imported, {reason}"),
+            WarningKind::Approximated(reason) => write!(f, "approximated, {reason}"),
+        }
+    }
+}
+
+/// Checks the values of a combatant that cannot be converted for the simulation
+pub(super) fn validate_combatant(dto: &CombatantDto) -> Result<(), LoadError> {
+    let location = &dto.location;
+    if let Some(hit_dice) = &dto.hit_dice {
+        HitDice::from_str(hit_dice)
+            .map_err(|_| LoadError::invalid(location, Problem::InvalidHitDice(hit_dice.clone())))?;
+    }
+    if let Some(fraction) = dto.morale.and_then(|morale| morale.hp_below) {
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(LoadError::invalid(
+                location,
+                Problem::InvalidChance(fraction),
+            ));
+        }
+    }
+    for resource in dto.resources.values() {
+        validate_recharge(resource.recharge, location)?;
+    }
+    validate_actions(&dto.actions.default, location)?;
+    let slots = SpellSlots {
+        per_level: dto.spell_slots.clone(),
+        pact: dto.pact_slots,
+        preference: dto.slot_preference,
+    };
+    for special in &dto.actions.special {
+        validate_actions(&special.actions, location)?;
+        let location = special
+            .actions
+            .first()
+            .map_or(location.clone(), |action| location.action(action.name()));
+        if let Some(upcast) = &special.upcast {
+            validate_dice(upcast, &location)?;
+        }
+        if let Some(recharge) = special.recharge {
+            validate_recharge(recharge, &location)?;
+        }
+        validate_costs(&special.cost, dto, &location)?;
+        // innate spells are paid for with uses per day instead
+        if special.per_day.is_none() {
+            validate_slots(special.spell_level, &slots, &location)?;
+        }
+    }
+    for legendary in &dto.legendary.actions {
+        validate_actions(&legendary.actions, location)?;
+    }
+    for reaction in &dto.reactions {
+        let location = location.action(&reaction.name);
+        validate_trigger(reaction.trigger, &reaction.effect, &location)?;
+        validate_slots(reaction.spell_level, &slots, &location)?;
+        match &reaction.effect {
+            ReactionEffectDto::ReduceDamage { amount } => validate_dice(amount, &location)?,
+            ReactionEffectDto::Strike { action } => {
+                validate_actions(slice::from_ref(action), &location)?
+            }
+            _ => {}
+        }
+        validate_costs(&reaction.cost, dto, &location)?;
+    }
+    Ok(())
+}
+
+pub(super) fn validate_actions(
+    actions: &[ActionDto],
+    location: &Location,
+) -> Result<(), LoadError> {
+    for action in actions {
+        let location = location.action(action.name());
+        let missing = |field| LoadError::invalid(&location, Problem::MissingField(field));
+        let damage = match action {
+            ActionDto::Attack { atk, dmg, .. } => {
+                // attacks without an ability to derive them from have to give their numbers
+                atk.ok_or_else(|| missing("atk"))?;
+                dmg.as_ref().ok_or_else(|| missing("dmg"))?
+            }
+            ActionDto::SaveBasedAttack {
+                save_dc,
+                targets,
+                area,
+                damage,
+                ..
+            } => {
+                // without a spellcasting ability there is no spell save DC to fall back to
+                save_dc.ok_or_else(|| missing("save_dc"))?;
+                match (targets, area) {
+                    (None, None) => return Err(missing("targets")),
+                    (Some(_), Some(_)) => {
+                        let problem = Problem::TargetsAndArea;
+                        return Err(LoadError::invalid(&location, problem));
+                    }
+                    (None, Some(area)) => {
+                        let chances = [area.density, area.friendly_fire, area.spill];
+                        for chance in chances.iter().flatten() {
+                            if !(0.0..=1.0).contains(chance) {
+                                let problem = Problem::InvalidChance(*chance);
+                                return Err(LoadError::invalid(&location, problem));
+                            }
+                        }
+                    }
+                    (Some(_), None) => {}
+                }
+                damage
+            }
+        };
+        validate_dice(damage, &location)?;
+    }
+    Ok(())
+}
+
+fn validate_dice(dice: &str, location: &Location) -> Result<(), LoadError> {
+    DamageRoll::from_str(dice)
+        .map(|_| ())
+        .map_err(|_| LoadError::invalid(location, Problem::InvalidDice(dice.to_string())))
+}
+
+fn validate_recharge(recharge: RechargeDto, location: &Location) -> Result<(), LoadError> {
+    match recharge {
+        RechargeDto::Roll(value) if value < 5 => Err(LoadError::invalid(
+            location,
+            Problem::UnsupportedRecharge(value),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Only the triggers the simulation checks for an effect let it take place
+fn validate_trigger(
+    trigger: ReactionTrigger,
+    effect: &ReactionEffectDto,
+    location: &Location,
+) -> Result<(), LoadError> {
+    use ReactionTrigger::*;
+    let (name, fits) = match effect {
+        ReactionEffectDto::AcBonus { .. } => ("AcBonus", trigger == Hit),
+        ReactionEffectDto::HalveDamage => ("HalveDamage", matches!(trigger, Hit | AllyHit)),
+        ReactionEffectDto::ReduceDamage { .. } => {
+            ("ReduceDamage", matches!(trigger, Hit | AllyHit))
+        }
+        ReactionEffectDto::Counter { .. } => ("Counter", trigger == SpellCast),
+        ReactionEffectDto::Strike { .. } => ("Strike", matches!(trigger, Damaged | LeavesReach)),
+    };
+    if fits {
+        Ok(())
+    } else {
+        let problem = Problem::UnfitTrigger {
+            effect: name,
+            trigger,
+        };
+        Err(LoadError::invalid(location, problem))
+    }
+}
+
+/// Leveled spells without a slot to cast them with would never be available
+fn validate_slots(
+    spell_level: Option<u8>,
+    slots: &SpellSlots,
+    location: &Location,
+) -> Result<(), LoadError> {
+    match spell_level {
+        Some(level) if level > 0 && slots.usable_for(level).is_empty() => {
+            Err(LoadError::invalid(location, Problem::NoSpellSlot(level)))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_costs(
+    cost: &BTreeMap<String, u32>,
+    dto: &CombatantDto,
+    location: &Location,
+) -> Result<(), LoadError> {
+    match cost.keys().find(|name| !dto.resources.contains_key(*name)) {
+        Some(name) => Err(LoadError::invalid(
+            location,
+            Problem::UndeclaredResource(name.clone()),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Warnings about the combatants of both sides of a fight. Copies of the same combatant are only reported once.
+pub(super) fn fight_warnings(players: &[CombatantDto], enemies: &[CombatantDto]) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = players
+        .iter()
+        .flat_map(|player| combatant_warnings(player, enemies.len()))
+        .chain(
+            enemies
+                .iter()
+                .flat_map(|enemy| combatant_warnings(enemy, players.len())),
+        )
+        .collect();
+    warnings.dedup();
+    warnings
+}
+
+fn combatant_warnings(dto: &CombatantDto, nr_enemies: usize) -> Vec<Warning> {
+    let warning = |location: &Location, kind| Warning {
+        location: location.clone(),
+        kind,
+    };
+    let mut warnings = vec![];
+    if dto.hp == 0 {
+        warnings.push(warning(&dto.location, WarningKind::NoHp));
+    }
+    for action in all_actions(dto) {
+        // areas of effect only catch the enemies there are
+        if let ActionDto::SaveBasedAttack {
+            targets: Some(targets),
+            ..
+        } = action
+        {
+            if *targets > nr_enemies {
+                let kind = WarningKind::MoreTargetsThanEnemies {
+                    targets: *targets,
+                    enemies: nr_enemies,
+                };
+                warnings.push(warning(&dto.location.action(action.name()), kind));
+            }
+        }
+    }
+    let recharges = dto
+        .resources
+        .values()
+        .map(|resource| (resource.recharge, None))
+        .chain(dto.actions.special.iter().filter_map(|special| {
+            special
+                .recharge
+                .map(|recharge| (recharge, special.actions.first()))
+        }));
+    for (recharge, action) in recharges {
+        if let RechargeDto::Roll(value) = recharge {
+            if value > 6 {
+                let location = action.map_or(dto.location.clone(), |action| {
+                    dto.location.action(action.name())
+                });
+                warnings.push(warning(&location, WarningKind::NeverRecharges(value)));
+            }
+        }
+    }
+    warnings
+}
+
+fn all_actions(dto: &CombatantDto) -> impl Iterator<Item = &ActionDto> {
+    let special = dto.actions.special.iter().flat_map(|s| s.actions.iter());
+    let legendary = dto.legendary.actions.iter().flat_map(|l| l.actions.iter());
+    let reactions = dto.reactions.iter().filter_map(|r| match &r.effect {
+        ReactionEffectDto::Strike { action } => Some(action),
+        _ => None,
+    });
+    dto.actions
+        .default
+        .iter()
+        .chain(special)
+        .chain(legendary)
+        .chain(reactions)
+}
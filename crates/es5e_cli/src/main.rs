@@ -1,9 +1,16 @@
-use clap::Parser;
-use lib_es5e_core::{combat::encounter::Encounter, statistics::Statistics};
-use loader::load_combatants_from_file;
+use clap::{Parser, Subcommand};
+use lib_es5e_core::{
+    combat::{adventuring_day::AdventuringDay, encounter::Encounter},
+    statistics::{BaseDayStatistics, BaseStatistics, Statistics},
+};
+use loader::{
+    imported_combatants_to_yaml, load_combatants_from_file, load_day_from_file,
+    load_encounter_from_file, load_lair_from_file, validate_day, validate_encounter,
+    validate_fight, LoadError,
+};
 use rayon::prelude::*;
 use statistics::MultiThreadStatistics;
-use std::path::Path;
+use std::{fs, path::Path, process};
 
 mod loader;
 mod statistics;
@@ -21,22 +28,134 @@ struct Args {
     /// Path to file containing players
     #[arg(short, long, default_value = "test_data/players.yaml")]
     players_yaml_path: String,
+    /// Path to file containing the lair actions of the enemies' lair, if the encounter takes place in one
+    #[arg(short, long)]
+    lair_yaml_path: Option<String>,
+    /// Path to file containing the encounters of an adventuring day, which are fought instead of the enemies
+    #[arg(short, long)]
+    day_yaml_path: Option<String>,
+    /// Path to an encounter file defining both sides, used instead of the players and enemies files
+    #[arg(short = 'c', long)]
+    encounter_yaml_path: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Checks the files that would be simulated without simulating them, reporting errors and suspicious values
+    Validate,
+    /// Translates monsters in the JSON structure of the 5e SRD API or of Open5e, or actors exported
+    /// from Foundry VTT, into a combatant file, reporting everything that could not be translated
+    Import {
+        /// Path to the JSON file
+        json_path: String,
+        /// Path to the combatant file to write, printed if not given
+        #[arg(short, long)]
+        output: Option<String>,
+    },
 }
 
 impl Args {
-    fn load_encounter(&self) -> Encounter {
-        let players = load_combatants_from_file(Path::new(self.players_yaml_path.as_str()));
-        let enemies = load_combatants_from_file(Path::new(self.enemies_yaml_path.as_str()));
+    fn load_encounter(&self) -> Result<Encounter, LoadError> {
+        if let Some(path) = &self.encounter_yaml_path {
+            let (name, encounter) = load_encounter_from_file(Path::new(path.as_str()))?;
+            println!("Running encounter {name}");
+            return Ok(encounter);
+        }
+        let players = load_combatants_from_file(Path::new(self.players_yaml_path.as_str()))?;
+        let enemies = load_combatants_from_file(Path::new(self.enemies_yaml_path.as_str()))?;
+        let lair = match &self.lair_yaml_path {
+            Some(path) => Some(load_lair_from_file(Path::new(path.as_str()))?),
+            None => None,
+        };
+
+        Ok(Encounter::new_with_lair(players, enemies, lair))
+    }
+
+    fn load_day(&self, day_yaml_path: &str) -> Result<AdventuringDay, LoadError> {
+        let players = load_combatants_from_file(Path::new(self.players_yaml_path.as_str()))?;
+        let (encounters, hit_dice_policy) = load_day_from_file(Path::new(day_yaml_path), &players)?;
 
-        Encounter::new(players, enemies)
+        Ok(AdventuringDay::new_with_hit_dice_policy(
+            players,
+            encounters,
+            hit_dice_policy,
+        ))
+    }
+
+    fn validate(&self) -> Result<(), LoadError> {
+        let players = Path::new(self.players_yaml_path.as_str());
+        let warnings = if let Some(path) = &self.day_yaml_path {
+            validate_day(Path::new(path.as_str()), players)?
+        } else if let Some(path) = &self.encounter_yaml_path {
+            validate_encounter(Path::new(path.as_str()))?
+        } else {
+            let enemies = Path::new(self.enemies_yaml_path.as_str());
+            let lair = self.lair_yaml_path.as_deref().map(Path::new);
+            validate_fight(players, enemies, lair)?
+        };
+        for warning in &warnings {
+            println!("Warning: {warning}");
+        }
+        println!("No errors found, {} warnings", warnings.len());
+        Ok(())
+    }
+}
+
+fn import(json_path: &str, output: Option<&str>) -> Result<(), LoadError> {
+    let (yaml, warnings) = imported_combatants_to_yaml(Path::new(json_path))?;
+    for warning in &warnings {
+        eprintln!("Warning: {warning}");
+    }
+    match output {
+        Some(output) => fs::write(output, yaml).map_err(|source| LoadError::Io {
+            file: output.into(),
+            source,
+        })?,
+        None => print!("{yaml}"),
     }
+    Ok(())
 }
 
 fn main() {
-    let stats = MultiThreadStatistics::new();
     let args = Args::parse();
+    if let Err(error) = run(&args) {
+        eprintln!("Error: {error}");
+        process::exit(1);
+    }
+}
+
+fn run(args: &Args) -> Result<(), LoadError> {
+    match &args.command {
+        Some(Command::Validate) => return args.validate(),
+        Some(Command::Import { json_path, output }) => return import(json_path, output.as_deref()),
+        None => {}
+    }
     let repetitions = args.repetitions;
-    let encounter = args.load_encounter();
+    if let Some(day_yaml_path) = &args.day_yaml_path {
+        let stats = MultiThreadStatistics::<BaseDayStatistics>::new();
+        let day = args.load_day(day_yaml_path)?;
+        (0..repetitions)
+            .into_par_iter()
+            .for_each(|_| day.run(&mut stats.clone()));
+
+        stats.print(repetitions);
+        return Ok(());
+    }
+
+    let encounter = args.load_encounter()?;
+    simulate(&encounter, repetitions);
+    if encounter.has_surprise() {
+        println!();
+        println!("Without surprise:");
+        simulate(&encounter.without_surprise(), repetitions);
+    }
+    Ok(())
+}
+
+fn simulate(encounter: &Encounter, repetitions: usize) {
+    let stats = MultiThreadStatistics::<BaseStatistics>::new();
     (0..repetitions)
         .into_par_iter()
         .for_each(|_| encounter.run(&mut stats.clone()));
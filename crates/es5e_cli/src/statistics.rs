@@ -1,21 +1,31 @@
 use std::sync::{Arc, Mutex};
 
-use lib_es5e_core::{statistics::BaseStatistics, statistics::Statistics};
+use lib_es5e_core::{
+    combat::outcome::Outcome,
+    statistics::{BaseStatistics, DayStatistics, EnemyFates, PartySnapshot, Statistics},
+};
 
-#[derive(Clone)]
-pub struct MultiThreadStatistics {
-    stats: Arc<Mutex<BaseStatistics>>,
+pub struct MultiThreadStatistics<S = BaseStatistics> {
+    stats: Arc<Mutex<S>>,
 }
 
-impl MultiThreadStatistics {
+impl<S> Clone for MultiThreadStatistics<S> {
+    fn clone(&self) -> Self {
+        Self {
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl<S: Default> MultiThreadStatistics<S> {
     pub fn new() -> Self {
         Self {
-            stats: Arc::new(Mutex::new(BaseStatistics::new())),
+            stats: Arc::new(Mutex::new(S::default())),
         }
     }
 }
 
-impl Statistics for MultiThreadStatistics {
+impl<S: Statistics> Statistics for MultiThreadStatistics<S> {
     fn record_round(&mut self) {
         self.stats.lock().unwrap().record_round()
     }
@@ -24,7 +34,32 @@ impl Statistics for MultiThreadStatistics {
         self.stats.lock().unwrap().record_win(nr_survivors)
     }
 
+    fn record_initiative(&mut self, order: &[String], boss: &str) {
+        self.stats.lock().unwrap().record_initiative(order, boss)
+    }
+
+    fn record_enemy_fates(&mut self, fates: &EnemyFates) {
+        self.stats.lock().unwrap().record_enemy_fates(fates)
+    }
+
+    fn record_outcome(&mut self, outcome: Outcome) {
+        self.stats.lock().unwrap().record_outcome(outcome)
+    }
+
     fn print(&self, nr_repetitions: usize) {
         self.stats.lock().unwrap().print(nr_repetitions)
     }
 }
+
+impl<S: DayStatistics> DayStatistics for MultiThreadStatistics<S> {
+    fn record_fight_won(&mut self, encounter: usize, party: &PartySnapshot) {
+        self.stats
+            .lock()
+            .unwrap()
+            .record_fight_won(encounter, party)
+    }
+
+    fn record_day_survived(&mut self) {
+        self.stats.lock().unwrap().record_day_survived()
+    }
+}
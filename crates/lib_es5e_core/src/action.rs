@@ -1,7 +1,10 @@
+#[allow(clippy::module_inception)]
 pub mod action;
 pub mod attack;
 pub mod effect;
 pub mod multi;
 pub mod negative_effect;
 pub mod positive_effect;
+pub mod reaction;
 pub mod single;
+pub mod spell;
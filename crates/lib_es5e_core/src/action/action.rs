@@ -1,11 +1,40 @@
 use crate::combat::encounter::IntMutCombatant;
 #[cfg(test)]
 use mockall::automock;
+use serde::{Deserialize, Serialize};
 
-use crate::combatant::state::ResourceCosts;
+use crate::{combat::grid::SQUARE, combatant::state::ResourceCosts};
+
+/// Part of the action economy of a turn that an action uses up
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ActionSlot {
+    #[default]
+    #[serde(rename = "action")]
+    Action,
+    #[serde(rename = "bonus")]
+    BonusAction,
+}
 
 #[cfg_attr(test, automock)]
 pub trait Action {
-    fn execute(&self, _allies: &[IntMutCombatant], enemies: &[IntMutCombatant]);
+    fn execute(
+        &self,
+        actor: &IntMutCombatant,
+        allies: &[IntMutCombatant],
+        enemies: &[IntMutCombatant],
+    );
     fn resource_costs(&self) -> &ResourceCosts;
+    /// Whether the action can damage the enemies at all
+    fn deals_damage(&self) -> bool;
+    fn slot(&self) -> ActionSlot {
+        ActionSlot::Action
+    }
+    /// Level of the spell cast by this action, 0 for cantrips and `None` if no spell is cast
+    fn spell_level(&self) -> Option<u8> {
+        None
+    }
+    /// Distance in feet up to which the action reaches its targets on a grid
+    fn range(&self) -> u32 {
+        SQUARE
+    }
 }
@@ -1,9 +1,14 @@
 use crate::{
-    action::effect::Effect,
+    action::{
+        effect::Effect,
+        reaction::{avert_hit, deal_damage, mitigate_hit},
+    },
     attack::damage::{Damage, DamageRoll},
+    combat::{encounter::IntMutCombatant, grid::SQUARE},
     combatant::combatant::Combatant,
+    targeting::strategy::Reach,
     utils::{
-        dice::{beats_dc, is_natural_20, Die},
+        dice::{beats_dc, is_natural_20},
         rollable::Rollable,
     },
 };
@@ -19,25 +24,43 @@ pub enum HitResult {
 pub struct Attack {
     attack_bonus: i16,
     damage: DamageRoll,
+    /// Reach or range in feet
+    range: u32,
 }
 
 impl Attack {
     pub fn new(attack_bonus: i16, damage: DamageRoll) -> Self {
         Attack {
             attack_bonus,
-            damage: damage,
+            damage,
+            range: SQUARE,
+        }
+    }
+
+    pub fn with_range(self, range: u32) -> Self {
+        Self { range, ..self }
+    }
+
+    pub fn with_extra_damage(&self, extra: &DamageRoll) -> Self {
+        Self {
+            damage: self.damage.plus(extra),
+            ..self.clone()
         }
     }
 
-    pub fn roll_attack(&self, ac: i16) -> HitResult {
-        let hit_roll = Die::D20.roll();
+    /// Rolls against the target's AC, giving the target the chance to avert a regular hit with its reaction
+    pub fn roll_attack(&self, target: &mut Combatant) -> HitResult {
+        let hit_roll = target.attacked_with().roll();
         let is_crit = is_natural_20(hit_roll);
         let is_critical_miss = hit_roll == 1;
-        let effective_ac = ac as i32 - self.attack_bonus as i32;
-        let is_hit = !is_critical_miss && beats_dc(hit_roll as i32, effective_ac);
+        // cover raises the AC, which is the same as lowering the attack roll
+        let attack_roll =
+            hit_roll as i16 + self.attack_bonus - target.cover_bonus(Reach::of(self.range));
+        let is_hit = !is_critical_miss && beats_dc(attack_roll, target.ac());
 
         match (is_hit, is_crit) {
             (_, true) => HitResult::Critical,
+            (true, _) if avert_hit(target, attack_roll) => HitResult::Miss,
             (true, _) => HitResult::Hit,
             (false, _) => HitResult::Miss,
         }
@@ -50,22 +73,32 @@ impl Attack {
             HitResult::Critical => self.damage.calculate_crit(),
         }
     }
-
-    pub(crate) fn roll_attack_with_damage(&self, ac: i16) -> Damage {
-        let hit_result = self.roll_attack(ac);
-        self.calculate_damage(hit_result)
-    }
 }
 
 impl Effect for Attack {
-    fn number_of_targets(&self) -> usize {
+    fn number_of_targets(&self, _nr_conscious: usize) -> usize {
         1
     }
 
-    fn apply(&self, target: &mut Combatant) {
-        let damage: crate::attack::damage::Damage = self.roll_attack_with_damage(target.ac());
-        if damage.amount() > 0 {
-            target.take_damage(damage)
+    fn range(&self) -> u32 {
+        self.range
+    }
+
+    fn deals_damage(&self) -> bool {
+        self.damage.max_crit().amount() > 0
+    }
+
+    fn apply(
+        &self,
+        actor: &IntMutCombatant,
+        target: &IntMutCombatant,
+        target_allies: &[IntMutCombatant],
+    ) {
+        let hit_result = self.roll_attack(&mut target.borrow_mut());
+        if hit_result == HitResult::Miss {
+            return;
         }
+        let damage = mitigate_hit(target, target_allies, self.calculate_damage(hit_result));
+        deal_damage(actor, target, damage);
     }
 }
@@ -1,6 +1,16 @@
-use crate::combatant::combatant::Combatant;
+use crate::combat::encounter::IntMutCombatant;
 
 pub trait Effect {
-    fn number_of_targets(&self) -> usize;
-    fn apply(&self, target: &mut Combatant);
+    /// Number of targets among the given number of conscious enemies
+    fn number_of_targets(&self, nr_conscious: usize) -> usize;
+    /// Distance in feet up to which targets can be reached on a grid
+    fn range(&self) -> u32;
+    /// Whether the effect can damage its targets at all
+    fn deals_damage(&self) -> bool;
+    fn apply(
+        &self,
+        actor: &IntMutCombatant,
+        target: &IntMutCombatant,
+        target_allies: &[IntMutCombatant],
+    );
 }
@@ -1,18 +1,31 @@
 use crate::combat::encounter::IntMutCombatant;
 
+use crate::{combat::grid::SQUARE, combatant::state::ResourceCosts};
 
-use crate::combatant::{state::ResourceCosts};
-
-use super::{action::Action, single::SingleAction};
+use super::{
+    action::{Action, ActionSlot},
+    reaction::is_countered,
+    single::SingleAction,
+};
 
 #[derive(Debug, Clone)]
 pub struct MultiAction {
     actions: Vec<SingleAction>,
     resource_cost: ResourceCosts,
+    slot: ActionSlot,
+    spell_level: Option<u8>,
 }
 
 impl MultiAction {
     pub fn new(actions: Vec<SingleAction>) -> Self {
+        Self::new_with_slot(actions, ActionSlot::Action, None)
+    }
+
+    pub fn new_with_slot(
+        actions: Vec<SingleAction>,
+        slot: ActionSlot,
+        spell_level: Option<u8>,
+    ) -> Self {
         let resource_cost = actions.iter().map(SingleAction::resource_costs).fold(
             ResourceCosts::new(),
             |mut acc, e| {
@@ -23,18 +36,48 @@ impl MultiAction {
         Self {
             actions,
             resource_cost,
+            slot,
+            spell_level,
         }
     }
 }
 
 impl Action for MultiAction {
-    fn execute(&self, allies: &[IntMutCombatant], enemies: &[IntMutCombatant]) {
+    fn execute(
+        &self,
+        actor: &IntMutCombatant,
+        allies: &[IntMutCombatant],
+        enemies: &[IntMutCombatant],
+    ) {
+        if self
+            .spell_level
+            .is_some_and(|level| is_countered(enemies, level))
+        {
+            return;
+        }
         self.actions
             .iter()
-            .for_each(|action| action.execute(allies, enemies));
+            .for_each(|action| action.execute(actor, allies, enemies));
     }
 
     fn resource_costs(&self) -> &ResourceCosts {
         &self.resource_cost
     }
+
+    fn deals_damage(&self) -> bool {
+        self.actions.iter().any(|action| action.deals_damage())
+    }
+
+    fn slot(&self) -> ActionSlot {
+        self.slot
+    }
+
+    fn spell_level(&self) -> Option<u8> {
+        self.spell_level
+    }
+
+    /// The combatant moves within range of the action with the farthest reach
+    fn range(&self) -> u32 {
+        self.actions.iter().map(Action::range).max().unwrap_or(SQUARE)
+    }
 }
@@ -1,7 +1,14 @@
 use crate::{
     action::effect::Effect,
-    attack::{damage::Damage, save_based::SaveBasedAttack},
-    combatant::combatant::Combatant,
+    attack::area::Area,
+    attack::{
+        damage::{Damage, DamageRoll},
+        save_based::SaveBasedAttack,
+    },
+    combat::{
+        encounter::IntMutCombatant,
+        grid::{Position, SQUARE},
+    },
 };
 
 #[derive(Clone, Debug)]
@@ -12,17 +19,81 @@ pub enum NegativeEffect {
     Multi(Vec<NegativeEffect>),
 }
 
+impl NegativeEffect {
+    /// The same effect, with the extra damage added to every damage roll in it
+    pub fn with_extra_damage(&self, extra: &DamageRoll) -> Self {
+        match self {
+            Self::Saveable(atk) => Self::Saveable(atk.with_extra_damage(extra)),
+            Self::Multi(effects) => Self::Multi(
+                effects
+                    .iter()
+                    .map(|effect| effect.with_extra_damage(extra))
+                    .collect(),
+            ),
+            Self::Condition | Self::Damage(_) => self.clone(),
+        }
+    }
+
+    /// Area of the effect, if it has one
+    pub fn area(&self) -> Option<Area> {
+        match self {
+            Self::Saveable(atk) => atk.area(),
+            Self::Multi(effects) => effects.iter().find_map(NegativeEffect::area),
+            Self::Condition | Self::Damage(_) => None,
+        }
+    }
+
+    /// Applies the effect to the allies of the attacker caught in its area, if it has one.
+    /// On a grid, the area is aimed at the given square.
+    pub fn catch_allies(
+        &self,
+        attacker: &IntMutCombatant,
+        allies: &[IntMutCombatant],
+        aim: Option<Position>,
+    ) {
+        match self {
+            Self::Saveable(atk) => atk.catch_allies(attacker, allies, aim),
+            Self::Multi(effects) => effects
+                .iter()
+                .for_each(|effect| effect.catch_allies(attacker, allies, aim)),
+            Self::Condition | Self::Damage(_) => {}
+        }
+    }
+}
+
 impl Effect for NegativeEffect {
-    fn number_of_targets(&self) -> usize {
+    fn number_of_targets(&self, nr_conscious: usize) -> usize {
         match &self {
-            Self::Saveable(atk) => atk.number_of_targets(),
+            Self::Saveable(atk) => atk.number_of_targets(nr_conscious),
             _ => todo!(),
         }
     }
 
-    fn apply(&self, target: &mut Combatant) {
+    fn range(&self) -> u32 {
+        match &self {
+            Self::Saveable(atk) => atk.range(),
+            Self::Multi(effects) => effects.iter().map(|e| e.range()).max().unwrap_or(SQUARE),
+            Self::Condition | Self::Damage(_) => SQUARE,
+        }
+    }
+
+    fn deals_damage(&self) -> bool {
+        match &self {
+            Self::Condition => false,
+            Self::Damage(damage) => damage.amount() > 0,
+            Self::Saveable(atk) => atk.deals_damage(),
+            Self::Multi(effects) => effects.iter().any(NegativeEffect::deals_damage),
+        }
+    }
+
+    fn apply(
+        &self,
+        actor: &IntMutCombatant,
+        target: &IntMutCombatant,
+        target_allies: &[IntMutCombatant],
+    ) {
         match &self {
-            Self::Saveable(atk) => atk.apply(target),
+            Self::Saveable(atk) => atk.apply(actor, target, target_allies),
             _ => todo!(),
         }
     }
@@ -0,0 +1,267 @@
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attack::damage::{Damage, DamageRoll},
+    combat::encounter::IntMutCombatant,
+    combatant::{combatant::Combatant, state::ResourceCosts},
+    utils::{
+        dice::{beats_dc, Die},
+        rollable::Rollable,
+    },
+};
+
+use super::single::Execution;
+
+/// Something a combatant can do outside of its own turn, at most once per round
+#[derive(Clone, Debug)]
+pub struct Reaction {
+    pub trigger: ReactionTrigger,
+    pub effect: ReactionEffect,
+    pub resource_cost: ResourceCosts,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ReactionTrigger {
+    /// The owner is hit by an attack
+    #[serde(rename = "hit")]
+    Hit,
+    /// Another creature on the owner's side is hit by an attack
+    #[serde(rename = "ally_hit")]
+    AllyHit,
+    /// The owner takes damage from another creature
+    #[serde(rename = "damaged")]
+    Damaged,
+    /// An enemy of the owner casts a spell
+    #[serde(rename = "spell_cast")]
+    SpellCast,
+    /// A creature leaves the owner's reach
+    #[serde(rename = "leaves_reach")]
+    LeavesReach,
+}
+
+#[derive(Clone, Debug)]
+pub enum ReactionEffect {
+    /// Raises the AC against the triggering attack, e.g. Parry, or until the start of the owner's next turn, e.g. Shield
+    AcBonus { bonus: i16, until_turn_start: bool },
+    /// E.g. Uncanny Dodge
+    HalveDamage,
+    /// E.g. Interception or Deflect Missiles
+    ReduceDamage(DamageRoll),
+    /// Negates a spell, automatically if its level does not exceed `level` and otherwise on a successful ability check
+    Counter { level: u8, ability_modifier: i16 },
+    /// Attack or effect against the triggering creature, e.g. Hellish Rebuke or an opportunity attack
+    Strike(Execution),
+}
+
+/// Lets the target of a hit avert it by raising its AC, but only if that actually turns the hit into a miss
+pub(crate) fn avert_hit(target: &mut Combatant, attack_total: i16) -> bool {
+    let ac = target.ac();
+    let used = target.react(ReactionTrigger::Hit, |effect| {
+        matches!(effect, ReactionEffect::AcBonus { bonus, .. } if attack_total < ac + bonus)
+    });
+    match used {
+        Some(ReactionEffect::AcBonus {
+            bonus,
+            until_turn_start,
+        }) => {
+            if until_turn_start {
+                target.raise_ac_until_turn_start(bonus);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Lets the target of a hit, or one of its allies, reduce the damage it takes
+pub(crate) fn mitigate_hit(
+    target: &IntMutCombatant,
+    target_allies: &[IntMutCombatant],
+    damage: Damage,
+) -> Damage {
+    let is_mitigation = |effect: &ReactionEffect| {
+        matches!(
+            effect,
+            ReactionEffect::HalveDamage | ReactionEffect::ReduceDamage(_)
+        )
+    };
+    if let Some(effect) = target
+        .borrow_mut()
+        .react(ReactionTrigger::Hit, is_mitigation)
+    {
+        return mitigate(effect, damage);
+    }
+    let protector = target_allies
+        .iter()
+        .filter(|ally| !Rc::ptr_eq(ally, target))
+        .find_map(|ally| {
+            ally.borrow_mut()
+                .react(ReactionTrigger::AllyHit, is_mitigation)
+        });
+    match protector {
+        Some(effect) => mitigate(effect, damage),
+        None => damage,
+    }
+}
+
+fn mitigate(effect: ReactionEffect, damage: Damage) -> Damage {
+    match effect {
+        ReactionEffect::HalveDamage => damage.half(),
+        ReactionEffect::ReduceDamage(roll) => damage.reduce(roll.calculate_regular().amount()),
+        _ => damage,
+    }
+}
+
+/// Applies damage dealt by the attacker and lets the target strike back
+pub(crate) fn deal_damage(attacker: &IntMutCombatant, target: &IntMutCombatant, damage: Damage) {
+    if damage.amount() == 0 {
+        return;
+    }
+    target.borrow_mut().take_damage(damage);
+    strike_back(target, attacker, ReactionTrigger::Damaged);
+}
+
+/// Whether one of the caster's enemies counters a spell of the given level
+pub(crate) fn is_countered(enemies: &[IntMutCombatant], spell_level: u8) -> bool {
+    if spell_level == 0 {
+        return false;
+    }
+    enemies.iter().any(|enemy| {
+        let counter = enemy
+            .borrow_mut()
+            .react(ReactionTrigger::SpellCast, |effect| {
+                matches!(effect, ReactionEffect::Counter { .. })
+            });
+        match counter {
+            Some(ReactionEffect::Counter {
+                level,
+                ability_modifier,
+            }) => {
+                spell_level <= level
+                    || beats_dc(
+                        Die::D20.roll() as i16 + ability_modifier,
+                        10 + spell_level as i16,
+                    )
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Every enemy whose reach the mover leaves may make an opportunity attack against it
+pub fn provoke_opportunity_attacks(mover: &IntMutCombatant, threatening: &[IntMutCombatant]) {
+    for enemy in threatening {
+        if !mover.borrow().is_fighting() {
+            break;
+        }
+        strike_back(enemy, mover, ReactionTrigger::LeavesReach);
+    }
+}
+
+fn strike_back(reactor: &IntMutCombatant, target: &IntMutCombatant, trigger: ReactionTrigger) {
+    if !target.borrow().is_fighting() {
+        return;
+    }
+    let strike = reactor.borrow_mut().react(trigger, |effect| {
+        matches!(effect, ReactionEffect::Strike(_))
+    });
+    if let Some(ReactionEffect::Strike(execution)) = strike {
+        execution.apply_to(reactor, target, &[]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        attack::damage::DamageRoll,
+        combat::action_selection::ActionSelection,
+        combatant::{combatant::Combatant, defences::save::SaveModifiers, state::ResourceCosts},
+    };
+
+    use super::*;
+
+    fn combatant_with_reaction(trigger: ReactionTrigger, effect: ReactionEffect) -> Combatant {
+        let mut combatant = Combatant::new(
+            20,
+            15,
+            0,
+            SaveModifiers::default(),
+            ActionSelection::default(),
+        );
+        combatant.reactions = vec![Reaction {
+            trigger,
+            effect,
+            resource_cost: ResourceCosts::new(),
+        }];
+        combatant
+    }
+
+    #[test]
+    fn shield_is_only_used_when_it_turns_hit_into_miss() {
+        let mut target = combatant_with_reaction(
+            ReactionTrigger::Hit,
+            ReactionEffect::AcBonus {
+                bonus: 5,
+                until_turn_start: true,
+            },
+        );
+
+        assert!(!avert_hit(&mut target, 25));
+        assert!(avert_hit(&mut target, 19));
+        assert_eq!(target.ac(), 20);
+    }
+
+    #[test]
+    fn only_one_reaction_per_round() {
+        let mut target = combatant_with_reaction(
+            ReactionTrigger::Hit,
+            ReactionEffect::AcBonus {
+                bonus: 2,
+                until_turn_start: false,
+            },
+        );
+
+        assert!(avert_hit(&mut target, 15));
+        assert!(!avert_hit(&mut target, 15));
+        target.update_resources_on_start();
+        assert!(avert_hit(&mut target, 15));
+        assert_eq!(target.ac(), 15);
+    }
+
+    #[test]
+    fn ally_reduces_damage() {
+        let target = Rc::new(RefCell::new(combatant_with_reaction(
+            ReactionTrigger::Damaged,
+            ReactionEffect::HalveDamage,
+        )));
+        let protector = Rc::new(RefCell::new(combatant_with_reaction(
+            ReactionTrigger::AllyHit,
+            ReactionEffect::ReduceDamage(DamageRoll::new(vec![], 4)),
+        )));
+        let damage = DamageRoll::new(vec![], 10).calculate_regular();
+
+        let mitigated = mitigate_hit(&target, &[target.clone(), protector.clone()], damage);
+
+        assert_eq!(mitigated.amount(), 6);
+    }
+
+    #[test]
+    fn counterspell_automatically_counters_lower_level_spells() {
+        let counterspeller = vec![Rc::new(RefCell::new(combatant_with_reaction(
+            ReactionTrigger::SpellCast,
+            ReactionEffect::Counter {
+                level: 3,
+                ability_modifier: -100,
+            },
+        )))];
+
+        assert!(!is_countered(&counterspeller, 0));
+        assert!(!is_countered(&counterspeller, 4));
+        counterspeller[0].borrow_mut().update_resources_on_start();
+        assert!(is_countered(&counterspeller, 3));
+    }
+}
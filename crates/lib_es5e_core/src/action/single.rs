@@ -1,9 +1,7 @@
-
-
-use crate::combat::encounter::IntMutCombatant;
+use crate::combat::{encounter::IntMutCombatant, grid, lines};
 use crate::{
-    combatant::{combatant::Combatant, state::ResourceCosts},
-    targeting::strategy::target_selection_strategy,
+    attack::damage::DamageRoll, combatant::state::ResourceCosts,
+    targeting::strategy::{target_selection_strategy, Reach},
 };
 
 use super::{
@@ -23,21 +21,109 @@ pub enum Execution {
     Attack(Attack),
 }
 
+impl Execution {
+    pub fn with_extra_damage(&self, extra: &DamageRoll) -> Self {
+        match self {
+            Execution::ApplyNegativeEffect(effect) => {
+                Execution::ApplyNegativeEffect(effect.with_extra_damage(extra))
+            }
+            Execution::Attack(attack) => Execution::Attack(attack.with_extra_damage(extra)),
+        }
+    }
+
+    pub(crate) fn deals_damage(&self) -> bool {
+        match self {
+            Execution::ApplyNegativeEffect(effect) => effect.deals_damage(),
+            Execution::Attack(attack) => attack.deals_damage(),
+        }
+    }
+
+    /// Applies the execution to a specific target instead of selecting the targets itself
+    pub(crate) fn apply_to(
+        &self,
+        actor: &IntMutCombatant,
+        target: &IntMutCombatant,
+        target_allies: &[IntMutCombatant],
+    ) {
+        match self {
+            Execution::ApplyNegativeEffect(effect) => effect.apply(actor, target, target_allies),
+            Execution::Attack(attack) => attack.apply(actor, target, target_allies),
+        }
+    }
+}
+
+/// Reach of the actor with an action of the given range, which includes the enemy back line once
+/// the actor has slipped past the front line
+fn reach(actor: &IntMutCombatant, range: u32) -> Reach {
+    let reach = Reach::of(range);
+    if actor.borrow().is_past_front_line() {
+        reach.past_front_line()
+    } else {
+        reach
+    }
+}
+
 impl Action for SingleAction {
-    fn execute(&self, _allies: &[IntMutCombatant], enemies: &[IntMutCombatant]) {
-        let strategy = target_selection_strategy();
+    fn execute(
+        &self,
+        actor: &IntMutCombatant,
+        allies: &[IntMutCombatant],
+        enemies: &[IntMutCombatant],
+    ) {
+        let strategy = target_selection_strategy(actor.borrow().targeting);
         match &self.execution {
-            Execution::ApplyNegativeEffect(effect) => strategy
-                .select_multiple_targets(enemies, effect.number_of_targets())
-                .iter()
-                .for_each(|enemy| effect.apply(&mut enemy.borrow_mut() as &mut Combatant)),
-            Execution::Attack(attack) => strategy
-                .select_single_target(enemies)
-                .iter()
-                .for_each(|enemy| attack.apply(&mut enemy.borrow_mut() as &mut Combatant)),
+            Execution::ApplyNegativeEffect(effect) => {
+                let in_range = grid::within_range(actor, enemies, effect.range());
+                let reach = reach(actor, effect.range());
+                let origin = actor.borrow().position();
+                let estimated = || {
+                    let nr_conscious = in_range.iter().filter(|e| e.borrow().is_fighting()).count();
+                    let nr_targets = effect.number_of_targets(nr_conscious);
+                    strategy.select_multiple_targets(&in_range, nr_targets, reach)
+                };
+                let mut aim = None;
+                let targets = match (effect.area(), origin) {
+                    // on a grid, the area is aimed at a single target and catches whoever stands in it
+                    (Some(area), Some(origin)) => {
+                        let aimed_at = strategy.select_single_target(&in_range, reach);
+                        aim = aimed_at.as_ref().and_then(|t| t.borrow().position());
+                        aim.map_or(vec![], |aim| area.caught(origin, aim, enemies))
+                    }
+                    (Some(area), None) => {
+                        lines::area_targets(strategy.as_ref(), &area, &in_range, reach)
+                            .unwrap_or_else(estimated)
+                    }
+                    _ => estimated(),
+                };
+                targets
+                    .iter()
+                    .for_each(|enemy| effect.apply(actor, enemy, enemies));
+                // an area that catches no enemy is not cast at all
+                if !targets.is_empty() {
+                    effect.catch_allies(actor, allies, aim);
+                }
+            }
+            Execution::Attack(attack) => {
+                let in_range = grid::within_range(actor, enemies, attack.range());
+                strategy
+                    .select_single_target(&in_range, reach(actor, attack.range()))
+                    .iter()
+                    .for_each(|enemy| attack.apply(actor, enemy, enemies))
+            }
         }
     }
     fn resource_costs(&self) -> &ResourceCosts {
         &self.resource_cost
     }
+
+    fn deals_damage(&self) -> bool {
+        self.execution.deals_damage()
+    }
+
+    fn range(&self) -> u32 {
+        match &self.execution {
+            Execution::ApplyNegativeEffect(effect) => effect.range(),
+            Execution::Attack(attack) => attack.range(),
+        }
+    }
 }
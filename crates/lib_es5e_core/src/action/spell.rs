@@ -0,0 +1,142 @@
+use crate::{
+    attack::damage::DamageRoll,
+    combatant::{spell_slots::SpellSlots, state::ResourceCosts},
+};
+
+use super::{action::ActionSlot, multi::MultiAction, single::SingleAction};
+
+/// Leveled spell that is cast by spending a spell slot of at least its level
+#[derive(Clone, Debug)]
+pub struct Spell {
+    pub level: u8,
+    /// Extra damage of every damaging part of the spell per slot level above the spell's level
+    pub upcast: Option<DamageRoll>,
+    pub actions: Vec<SingleAction>,
+    pub slot: ActionSlot,
+    /// Resources spent in addition to the spell slot
+    pub resource_cost: ResourceCosts,
+}
+
+impl Spell {
+    /// One action per slot the caster could spend on the spell, in the caster's order of preference
+    pub fn castings(&self, slots: &SpellSlots) -> Vec<MultiAction> {
+        slots
+            .usable_for(self.level)
+            .into_iter()
+            .map(|(key, cast_level)| {
+                let extra = self.extra_damage(cast_level);
+                let mut actions: Vec<SingleAction> = self
+                    .actions
+                    .iter()
+                    .map(|action| SingleAction {
+                        resource_cost: ResourceCosts::new(),
+                        execution: match &extra {
+                            Some(extra) => action.execution.with_extra_damage(extra),
+                            None => action.execution.clone(),
+                        },
+                    })
+                    .collect();
+                if let Some(first) = actions.first_mut() {
+                    first.resource_cost = self.resource_cost.clone();
+                    first.resource_cost.insert(key, 1);
+                }
+                MultiAction::new_with_slot(actions, self.slot, Some(cast_level))
+            })
+            .collect()
+    }
+
+    fn extra_damage(&self, cast_level: u8) -> Option<DamageRoll> {
+        self.upcast.as_ref().map(|upcast| {
+            (self.level..cast_level).fold(DamageRoll::default(), |acc, _| acc.plus(upcast))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        action::{
+            action::{Action, ActionSlot},
+            attack::Attack,
+            single::{Execution, SingleAction},
+        },
+        attack::damage::DamageRoll,
+        combatant::{
+            spell_slots::{PactSlots, SlotPreference, SpellSlots},
+            state::{ResourceCosts, ResourceKey},
+        },
+    };
+
+    use super::Spell;
+
+    fn spell(level: u8) -> Spell {
+        Spell {
+            level,
+            upcast: Some(DamageRoll::new(vec![], 5)),
+            actions: vec![SingleAction {
+                resource_cost: ResourceCosts::new(),
+                execution: Execution::Attack(Attack::new(5, DamageRoll::new(vec![], 10))),
+            }],
+            slot: ActionSlot::Action,
+            resource_cost: ResourceCosts::new(),
+        }
+    }
+
+    fn cast_with(action: &dyn Action) -> Vec<(&ResourceKey, Option<u8>)> {
+        action
+            .resource_costs()
+            .keys()
+            .map(|key| (key, action.spell_level()))
+            .collect()
+    }
+
+    #[test]
+    fn spell_can_be_upcast_with_higher_slots() {
+        let slots = SpellSlots {
+            per_level: vec![4, 0, 2, 1],
+            pact: None,
+            preference: SlotPreference::Lowest,
+        };
+
+        let castings = spell(2).castings(&slots);
+
+        assert_eq!(castings.len(), 2);
+        assert_eq!(
+            cast_with(&castings[0]),
+            vec![(&ResourceKey::SpellSlot(3), Some(3))]
+        );
+        assert_eq!(
+            cast_with(&castings[1]),
+            vec![(&ResourceKey::SpellSlot(4), Some(4))]
+        );
+    }
+
+    #[test]
+    fn highest_slot_is_preferred_if_configured() {
+        let slots = SpellSlots {
+            per_level: vec![2, 2],
+            pact: Some(PactSlots { count: 2, level: 3 }),
+            preference: SlotPreference::Highest,
+        };
+
+        let castings = spell(1).castings(&slots);
+
+        assert_eq!(castings.len(), 3);
+        assert_eq!(
+            cast_with(&castings[0]),
+            vec![(&ResourceKey::PactSlot, Some(3))]
+        );
+        assert_eq!(
+            cast_with(&castings[2]),
+            vec![(&ResourceKey::SpellSlot(1), Some(1))]
+        );
+    }
+
+    #[test]
+    fn upcasting_adds_damage_per_level() {
+        let spell = spell(3);
+
+        assert_eq!(spell.extra_damage(3).unwrap().mean(), 0.0);
+        assert_eq!(spell.extra_damage(5).unwrap().mean(), 10.0);
+    }
+}
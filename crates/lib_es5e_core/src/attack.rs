@@ -1,2 +1,3 @@
+pub mod area;
 pub mod damage;
 pub mod save_based;
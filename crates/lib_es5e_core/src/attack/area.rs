@@ -0,0 +1,253 @@
+use std::rc::Rc;
+
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::combat::{
+    encounter::IntMutCombatant,
+    grid::{Position, SQUARE},
+};
+
+/// Creatures affected by a save-based attack
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Targets {
+    /// A fixed number of enemies, e.g. 1 for a spell that targets a single creature
+    Number(usize),
+    Area(Area),
+}
+
+impl Targets {
+    /// Number of the given conscious enemies that are affected
+    pub fn number_of_targets(&self, nr_conscious: usize) -> usize {
+        match self {
+            Targets::Number(nr_targets) => *nr_targets,
+            Targets::Area(area) => area.number_of_targets(nr_conscious),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Shape {
+    #[serde(rename = "cone")]
+    Cone,
+    #[serde(rename = "cube")]
+    Cube,
+    #[serde(rename = "cylinder")]
+    Cylinder,
+    #[serde(rename = "line")]
+    Line,
+    #[serde(rename = "sphere")]
+    Sphere,
+}
+
+/// Area of effect, whose targets are estimated from the number of conscious enemies unless the
+/// fight takes place on a grid
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Area {
+    shape: Shape,
+    /// Size in feet: the length of cones and lines, the edge of cubes and the radius of cylinders and spheres
+    size: u32,
+    /// Chance of every further enemy to stand close enough to the first one to be caught as well.
+    /// Without it the enemies are assumed to be crowded together.
+    density: Option<f32>,
+    /// Chance of every ally of the attacker to be caught as well
+    friendly_fire: f32,
+    /// Chance of every enemy in the other line than the one the area is aimed at to be caught as well
+    spill: f32,
+}
+
+impl Area {
+    pub fn new(shape: Shape, size: u32) -> Self {
+        Self {
+            shape,
+            size,
+            density: None,
+            friendly_fire: 0.0,
+            spill: 0.0,
+        }
+    }
+
+    pub fn with_density(self, density: f32) -> Self {
+        Self {
+            density: Some(density),
+            ..self
+        }
+    }
+
+    pub fn with_friendly_fire(self, friendly_fire: f32) -> Self {
+        Self {
+            friendly_fire,
+            ..self
+        }
+    }
+
+    pub fn with_spill(self, spill: f32) -> Self {
+        Self { spill, ..self }
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn spill(&self) -> f32 {
+        self.spill
+    }
+
+    /// Number of creatures of a crowd caught in the area, following the table
+    /// "Targets in Areas of Effect" of the Dungeon Master's Guide
+    pub fn crowd_targets(&self) -> usize {
+        let feet_per_target = match self.shape {
+            Shape::Cone => 10,
+            Shape::Cube => 5,
+            Shape::Cylinder => 5,
+            Shape::Line => 30,
+            Shape::Sphere => 5,
+        };
+        (self.size / feet_per_target).max(1) as usize
+    }
+
+    /// Number of the given conscious enemies caught in the area, which never exceeds a crowd's
+    pub fn number_of_targets(&self, nr_conscious: usize) -> usize {
+        let caught = match self.density {
+            None => nr_conscious,
+            Some(_) if nr_conscious == 0 => 0,
+            Some(density) => {
+                let mut rng = thread_rng();
+                1 + (1..nr_conscious)
+                    .filter(|_| rng.gen_bool(density as f64))
+                    .count()
+            }
+        };
+        caught.min(self.crowd_targets())
+    }
+
+    /// Conscious allies of the attacker that are caught in the area as well
+    pub fn caught_allies(
+        &self,
+        attacker: &IntMutCombatant,
+        allies: &[IntMutCombatant],
+    ) -> Vec<IntMutCombatant> {
+        if self.friendly_fire == 0.0 {
+            return vec![];
+        }
+        let mut rng = thread_rng();
+        allies
+            .iter()
+            .filter(|ally| !Rc::ptr_eq(ally, attacker) && ally.borrow().is_fighting())
+            .filter(|_| rng.gen_bool(self.friendly_fire as f64))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether the square lies within the area cast from the origin and aimed at another square.
+    /// Spheres, cylinders and cubes are centred on the aimed square, whereas cones and lines
+    /// spread from the origin towards it.
+    pub fn contains(&self, origin: Position, aim: Position, square: Position) -> bool {
+        match self.shape {
+            Shape::Sphere | Shape::Cylinder => aim.distance(&square) <= self.size,
+            Shape::Cube => aim.distance(&square) <= self.size / 2,
+            Shape::Cone | Shape::Line => {
+                let direction = ((aim.x - origin.x) as f32, (aim.y - origin.y) as f32);
+                let offset = ((square.x - origin.x) as f32, (square.y - origin.y) as f32);
+                let norm = direction.0.hypot(direction.1);
+                if norm == 0.0 {
+                    return false;
+                }
+                let along = (offset.0 * direction.0 + offset.1 * direction.1) / norm;
+                let across = (offset.0 * direction.1 - offset.1 * direction.0).abs() / norm;
+                // a cone is as wide as it is far from its origin, a line is one square wide
+                let max_across = match self.shape {
+                    Shape::Cone => along / 2.0,
+                    _ => 0.5,
+                };
+                along > 0.0 && along * SQUARE as f32 <= self.size as f32 && across <= max_across
+            }
+        }
+    }
+
+    /// Conscious combatants that stand within the area cast from the origin and aimed at another square
+    pub fn caught(
+        &self,
+        origin: Position,
+        aim: Position,
+        combatants: &[IntMutCombatant],
+    ) -> Vec<IntMutCombatant> {
+        combatants
+            .iter()
+            .filter(|c| {
+                let c = c.borrow();
+                c.is_fighting()
+                    && c.position()
+                        .is_some_and(|square| self.contains(origin, aim, square))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::targeting::strategy::tests::init_identical_test_targets;
+
+    use crate::combat::grid::Position;
+
+    use super::{Area, Shape, Targets};
+
+    #[test]
+    fn crowd_targets_follow_the_dmg_table() {
+        assert_eq!(Area::new(Shape::Sphere, 20).crowd_targets(), 4);
+        assert_eq!(Area::new(Shape::Cone, 15).crowd_targets(), 1);
+        assert_eq!(Area::new(Shape::Cone, 60).crowd_targets(), 6);
+        assert_eq!(Area::new(Shape::Line, 100).crowd_targets(), 3);
+        assert_eq!(Area::new(Shape::Cube, 15).crowd_targets(), 3);
+    }
+
+    #[test]
+    fn area_catches_at_most_the_conscious_enemies() {
+        let fireball = Targets::Area(Area::new(Shape::Sphere, 20));
+        assert_eq!(fireball.number_of_targets(10), 4);
+        assert_eq!(fireball.number_of_targets(2), 2);
+        assert_eq!(Targets::Number(8).number_of_targets(2), 8);
+    }
+
+    #[test]
+    fn spread_out_enemies_are_caught_by_chance() {
+        let fireball = Area::new(Shape::Sphere, 20);
+        assert_eq!(fireball.with_density(0.0).number_of_targets(10), 1);
+        assert_eq!(fireball.with_density(1.0).number_of_targets(10), 4);
+        assert_eq!(fireball.with_density(0.5).number_of_targets(0), 0);
+    }
+
+    #[test]
+    fn areas_on_a_grid_cover_their_shape() {
+        let (origin, aim) = (Position::new(0, 0), Position::new(0, 4));
+        let fireball = Area::new(Shape::Sphere, 20);
+        assert!(fireball.contains(origin, aim, Position::new(4, 8)));
+        assert!(!fireball.contains(origin, aim, Position::new(5, 4)));
+
+        let burning_hands = Area::new(Shape::Cone, 15);
+        assert!(burning_hands.contains(origin, aim, Position::new(1, 3)));
+        assert!(!burning_hands.contains(origin, aim, Position::new(2, 2)));
+        assert!(!burning_hands.contains(origin, aim, Position::new(0, 4)));
+        assert!(!burning_hands.contains(origin, aim, Position::new(0, -1)));
+
+        let lightning_bolt = Area::new(Shape::Line, 100);
+        assert!(lightning_bolt.contains(origin, aim, Position::new(0, 20)));
+        assert!(!lightning_bolt.contains(origin, aim, Position::new(1, 4)));
+    }
+
+    #[test]
+    fn friendly_fire_spares_the_attacker() {
+        let allies = init_identical_test_targets(4);
+        let fireball = Area::new(Shape::Sphere, 20);
+        assert!(fireball.caught_allies(&allies[0], &allies).is_empty());
+        let caught = fireball
+            .with_friendly_fire(1.0)
+            .caught_allies(&allies[0], &allies);
+        assert_eq!(caught.len(), 3);
+    }
+}
@@ -3,6 +3,7 @@ use std::str::FromStr;
 use crate::utils::{
     dice::Die,
     dice_roll::{DiceRoll, ParseDiceRoll},
+    probability::Meanable,
 };
 
 #[derive(Clone, Debug, Default)]
@@ -19,11 +20,25 @@ impl DamageRoll {
         }
     }
 
+    pub fn mean(&self) -> f32 {
+        self.0.mean()
+    }
+
+    pub fn plus(&self, other: &DamageRoll) -> DamageRoll {
+        Self(self.0.plus(&other.0))
+    }
+
+    /// Highest damage the roll deals, which is on a critical hit
+    pub fn max_crit(&self) -> Damage {
+        Damage {
+            amount: self.0.max_with_mod() + self.0.max_without_mod(),
+        }
+    }
+
     pub fn calculate_crit(&self) -> Damage {
         let regular_damage = self.calculate_regular();
         Damage {
             amount: regular_damage.amount + self.0.without_mod(),
-            ..regular_damage
         }
     }
 }
@@ -38,7 +53,12 @@ impl Damage {
     pub fn half(self) -> Self {
         Self {
             amount: self.amount / 2,
-            ..self
+        }
+    }
+
+    pub fn reduce(self, amount: u32) -> Self {
+        Self {
+            amount: self.amount.saturating_sub(amount),
         }
     }
 
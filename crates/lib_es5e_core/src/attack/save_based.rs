@@ -1,43 +1,75 @@
 // This should include spells, spell-like effects, special abilities, etc.
 
+use std::rc::Rc;
+
 use crate::{
-    action::effect::Effect,
+    action::{effect::Effect, reaction::deal_damage},
+    combat::{
+        encounter::IntMutCombatant,
+        grid::{Position, SQUARE},
+    },
     combatant::combatant::Combatant,
     utils::{
         dice::{beats_dc, Die},
         rollable::Rollable,
-        save::Save,
+        save::{Save, SaveType},
     },
 };
 
-use super::damage::{Damage, DamageRoll};
+use super::{
+    area::{Area, Shape, Targets},
+    damage::{Damage, DamageRoll},
+};
 
 #[derive(Clone, Debug)]
 pub struct SaveBasedAttack {
     save: Save,
-    nr_targets: usize,
+    targets: Targets,
     half_on_success: bool,
     damage: DamageRoll,
+    /// Range in feet, which only matters on a grid
+    range: Option<u32>,
 }
 
 impl SaveBasedAttack {
     pub fn new(
         save: Save,
-        nr_targets: usize,
+        targets: Targets,
         half_on_success: bool,
         damage: DamageRoll,
     ) -> SaveBasedAttack {
         Self {
             save,
-            nr_targets,
+            targets,
             half_on_success,
             damage,
+            range: None,
         }
     }
 
-    pub fn roll_save(&self, save_bonus: i16) -> Damage {
+    pub fn with_range(self, range: u32) -> Self {
+        Self {
+            range: Some(range),
+            ..self
+        }
+    }
+
+    pub fn with_extra_damage(&self, extra: &DamageRoll) -> Self {
+        Self {
+            damage: self.damage.plus(extra),
+            ..self.clone()
+        }
+    }
+
+    /// Rolls the target's save, which it may turn into a success with a legendary resistance
+    pub fn roll_save(&self, target: &mut Combatant) -> Damage {
+        let mut save_bonus = target.saves().modifier(self.save().save_type());
+        if matches!(self.save().save_type(), SaveType::DEX) {
+            save_bonus += target.cover_save_bonus();
+        }
         let roll = Die::D20.roll();
-        let take_full_damage = !beats_dc(roll as i16, self.save.dc() - save_bonus);
+        let take_full_damage = !beats_dc(roll as i16, self.save.dc() - save_bonus)
+            && !target.resist_failed_save(self.prevented_by_success());
 
         if take_full_damage {
             self.damage.calculate_regular()
@@ -48,25 +80,118 @@ impl SaveBasedAttack {
         }
     }
 
+    fn prevented_by_success(&self) -> f32 {
+        if self.half_on_success {
+            self.damage.mean() / 2.0
+        } else {
+            self.damage.mean()
+        }
+    }
+
     pub fn save(&self) -> &Save {
         &self.save
     }
 
-    pub fn nr_targets(&self) -> usize {
-        self.nr_targets
+    pub fn targets(&self) -> &Targets {
+        &self.targets
+    }
+
+    pub fn area(&self) -> Option<Area> {
+        match self.targets {
+            Targets::Area(area) => Some(area),
+            Targets::Number(_) => None,
+        }
+    }
+
+    /// Lets the allies of the attacker that are caught in its area of effect save against it.
+    /// They take the damage without striking back. On a grid, the allies within the area aimed
+    /// at the given square are caught instead of those that are caught by chance, and none are
+    /// caught if the area is not aimed anywhere.
+    pub fn catch_allies(
+        &self,
+        attacker: &IntMutCombatant,
+        allies: &[IntMutCombatant],
+        aim: Option<Position>,
+    ) {
+        let Targets::Area(area) = &self.targets else {
+            return;
+        };
+        let origin = attacker.borrow().position();
+        let caught = match (origin, aim) {
+            (Some(origin), Some(aim)) => {
+                let mut caught = area.caught(origin, aim, allies);
+                caught.retain(|ally| !Rc::ptr_eq(ally, attacker));
+                caught
+            }
+            (Some(_), None) => vec![],
+            (None, _) => area.caught_allies(attacker, allies),
+        };
+        for ally in caught {
+            let damage = self.roll_save(&mut ally.borrow_mut());
+            ally.borrow_mut().take_damage(damage);
+        }
     }
 }
 
 impl Effect for SaveBasedAttack {
-    fn number_of_targets(&self) -> usize {
-        self.nr_targets()
+    fn number_of_targets(&self, nr_conscious: usize) -> usize {
+        self.targets.number_of_targets(nr_conscious)
     }
 
-    fn apply(&self, target: &mut Combatant) {
-        let save_modifier: i16 = target.saves().modifier(self.save().save_type());
-        let damage = self.roll_save(save_modifier);
-        if damage.amount() > 0 {
-            target.take_damage(damage)
-        }
+    /// Cones and lines start at the attacker and reach as far as they are long
+    fn range(&self) -> u32 {
+        self.range.unwrap_or(match self.targets {
+            Targets::Area(area) if matches!(area.shape(), Shape::Cone | Shape::Line) => area.size(),
+            _ => SQUARE,
+        })
+    }
+
+    fn deals_damage(&self) -> bool {
+        self.damage.max_crit().amount() > 0
+    }
+
+    fn apply(
+        &self,
+        actor: &IntMutCombatant,
+        target: &IntMutCombatant,
+        _target_allies: &[IntMutCombatant],
+    ) {
+        let damage = self.roll_save(&mut target.borrow_mut());
+        deal_damage(actor, target, damage);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        attack::{
+            area::{Area, Shape, Targets},
+            damage::DamageRoll,
+        },
+        combat::grid::Position,
+        targeting::strategy::tests::init_identical_test_targets,
+        utils::save::{Save, SaveType},
+    };
+
+    use super::SaveBasedAttack;
+
+    #[test]
+    fn areas_on_a_grid_only_catch_allies_where_they_are_aimed() {
+        let fireball = SaveBasedAttack::new(
+            Save::new(SaveType::DEX, 30),
+            Targets::Area(Area::new(Shape::Sphere, 20).with_friendly_fire(1.0)),
+            false,
+            DamageRoll::new(vec![], 10),
+        );
+        let allies = init_identical_test_targets(2);
+        allies[0].borrow_mut().move_to(Position::new(0, 0));
+        allies[1].borrow_mut().move_to(Position::new(0, 1));
+
+        fireball.catch_allies(&allies[0], &allies, None);
+        assert_eq!(allies[1].borrow().hp(), 100);
+
+        fireball.catch_allies(&allies[0], &allies, Some(Position::new(0, 2)));
+        assert_eq!(allies[1].borrow().hp(), 90);
+        assert_eq!(allies[0].borrow().hp(), 100);
     }
 }
@@ -1,2 +1,11 @@
 pub mod action_selection;
+pub mod adventuring_day;
 pub mod encounter;
+pub mod grid;
+pub mod initiative;
+pub mod lair;
+pub mod lines;
+mod lookahead;
+pub mod morale;
+pub mod outcome;
+pub mod reinforcements;
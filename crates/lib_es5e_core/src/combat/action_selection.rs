@@ -1,18 +1,149 @@
 use std::{fmt::Debug, rc::Rc};
 
-use crate::action::action::Action;
+use crate::{
+    action::action::{Action, ActionSlot},
+    combatant::legendary::LegendaryAction,
+};
 
 #[derive(Default, Clone)]
 pub struct ActionSelection {
     pub actions: Vec<Rc<dyn Action>>,
+    pub mode: SelectionMode,
+    pub legendary: Vec<LegendaryAction>,
+}
+
+/// Determines how a combatant picks one of its available actions on its turn
+#[derive(Default, Clone, Debug)]
+pub enum SelectionMode {
+    /// Use the first action whose resources are available
+    #[default]
+    FirstAvailable,
+    /// Evaluate each available action by simulating the rest of the fight from a copy of the battle
+    Lookahead(LookaheadConfig),
+}
+
+#[derive(Clone, Debug)]
+pub struct LookaheadConfig {
+    /// Number of simulated continuations per candidate action
+    pub rollouts: usize,
+    /// Number of full rounds to simulate after the remainder of the current round
+    pub rounds: usize,
 }
 
 impl Debug for ActionSelection {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ActionSelection")
             .field("actions_len", &self.actions.len())
+            .field("mode", &self.mode)
+            .field("legendary_len", &self.legendary.len())
             .finish()
     }
 }
 
 impl ActionSelection {}
+
+/// Tracks the actions taken during a single turn to enforce the rules of the action economy
+#[derive(Default, Clone, Debug)]
+pub struct TurnEconomy {
+    used_action: bool,
+    used_bonus_action: bool,
+    cast_bonus_action_spell: bool,
+    cast_leveled_action_spell: bool,
+    moved: bool,
+}
+
+impl TurnEconomy {
+    /// Whether the action's slot is still unused and it does not break the spellcasting rules:
+    /// after casting a spell as a bonus action, only cantrips may be cast with the action and vice versa
+    pub fn permits(&self, action: &dyn Action) -> bool {
+        match action.slot() {
+            ActionSlot::Action => {
+                let is_leveled_spell = action.spell_level().is_some_and(|l| l > 0);
+                !self.used_action && (!is_leveled_spell || !self.cast_bonus_action_spell)
+            }
+            ActionSlot::BonusAction => {
+                let is_spell = action.spell_level().is_some();
+                !self.used_bonus_action && (!is_spell || !self.cast_leveled_action_spell)
+            }
+        }
+    }
+
+    /// Whether the combatant may still move on the grid, which it does once before its first action
+    pub fn may_move(&self) -> bool {
+        !self.moved
+    }
+
+    pub fn record_move(&mut self) {
+        self.moved = true;
+    }
+
+    /// Uses up the action on something other than one of the combatant's actions
+    pub fn spend_action(&mut self) {
+        self.used_action = true;
+    }
+
+    pub fn record(&mut self, action: &dyn Action) {
+        match action.slot() {
+            ActionSlot::Action => {
+                self.used_action = true;
+                self.cast_leveled_action_spell |= action.spell_level().is_some_and(|l| l > 0);
+            }
+            ActionSlot::BonusAction => {
+                self.used_bonus_action = true;
+                self.cast_bonus_action_spell |= action.spell_level().is_some();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::action::{action::ActionSlot, multi::MultiAction};
+
+    use super::TurnEconomy;
+
+    fn action(slot: ActionSlot, spell_level: Option<u8>) -> MultiAction {
+        MultiAction::new_with_slot(vec![], slot, spell_level)
+    }
+
+    #[test]
+    fn each_slot_can_only_be_used_once() {
+        let mut economy = TurnEconomy::default();
+        let attack = action(ActionSlot::Action, None);
+        let off_hand = action(ActionSlot::BonusAction, None);
+
+        economy.record(&attack);
+
+        assert!(!economy.permits(&attack));
+        assert!(economy.permits(&off_hand));
+        economy.record(&off_hand);
+        assert!(!economy.permits(&off_hand));
+    }
+
+    #[test]
+    fn bonus_action_spell_only_allows_cantrip_as_action() {
+        let mut economy = TurnEconomy::default();
+        economy.record(&action(ActionSlot::BonusAction, Some(1)));
+
+        assert!(economy.permits(&action(ActionSlot::Action, Some(0))));
+        assert!(economy.permits(&action(ActionSlot::Action, None)));
+        assert!(!economy.permits(&action(ActionSlot::Action, Some(3))));
+    }
+
+    #[test]
+    fn leveled_action_spell_forbids_bonus_action_spell() {
+        let mut economy = TurnEconomy::default();
+        economy.record(&action(ActionSlot::Action, Some(3)));
+
+        assert!(!economy.permits(&action(ActionSlot::BonusAction, Some(1))));
+        assert!(economy.permits(&action(ActionSlot::BonusAction, None)));
+    }
+
+    #[test]
+    fn cantrip_as_action_allows_bonus_action_spell() {
+        let mut economy = TurnEconomy::default();
+        economy.record(&action(ActionSlot::Action, Some(0)));
+
+        assert!(economy.permits(&action(ActionSlot::BonusAction, Some(2))));
+    }
+}
@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    combat::{
+        encounter::{fight, instantiate_for_run, EncounterSettings, IntMutCombatant},
+        lair::LairCfg,
+    },
+    combatant::{config::CombatantConfig, hit_dice::HitDicePolicy},
+    statistics::{DayStatistics, PartySnapshot},
+};
+
+/// Rest the party takes after an encounter
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Rest {
+    #[default]
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "short")]
+    Short,
+    #[serde(rename = "long")]
+    Long,
+}
+
+#[derive(Clone, Debug)]
+pub struct DayEncounter {
+    pub enemies: Vec<CombatantConfig>,
+    pub lair: Option<LairCfg>,
+    pub settings: EncounterSettings,
+    pub rest_after: Rest,
+}
+
+/// Sequence of encounters fought by the same party, which keeps its hit points and resources between them
+pub struct AdventuringDay {
+    players: Vec<CombatantConfig>,
+    encounters: Vec<DayEncounter>,
+    hit_dice_policy: HitDicePolicy,
+}
+
+impl AdventuringDay {
+    pub fn new(players: Vec<CombatantConfig>, encounters: Vec<DayEncounter>) -> Self {
+        Self::new_with_hit_dice_policy(players, encounters, HitDicePolicy::default())
+    }
+
+    /// Adventuring day in which the players spend hit dice on short rests according to the policy
+    pub fn new_with_hit_dice_policy(
+        players: Vec<CombatantConfig>,
+        encounters: Vec<DayEncounter>,
+        hit_dice_policy: HitDicePolicy,
+    ) -> Self {
+        Self {
+            players,
+            encounters,
+            hit_dice_policy,
+        }
+    }
+
+    /// Plays the encounters in order until the party is defeated or has played all of them. After a
+    /// draw or stalemate the party moves on to the next encounter without having won this one.
+    pub fn run<T: DayStatistics>(&self, stats: &mut T) {
+        let party = instantiate_for_run(&self.players);
+        for (idx, encounter) in self.encounters.iter().enumerate() {
+            let outcome = fight(
+                &party,
+                &encounter.enemies,
+                encounter.lair.as_ref(),
+                &encounter.settings,
+                stats,
+            );
+            if outcome.is_loss() {
+                return;
+            }
+            if outcome.is_win() {
+                stats.record_fight_won(idx, &snapshot(&party));
+            }
+            rest(&party, encounter.rest_after, self.hit_dice_policy);
+        }
+        stats.record_day_survived();
+    }
+}
+
+fn snapshot(party: &[IntMutCombatant]) -> PartySnapshot {
+    let mut snapshot = PartySnapshot::default();
+    for member in party {
+        let member = member.borrow();
+        if member.is_conscious() {
+            snapshot.nr_conscious += 1;
+        }
+        snapshot.hp += member.hp();
+        snapshot.max_hp += member.max_hp();
+        snapshot.hit_dice += member.remaining_hit_dice();
+        for (key, charges) in member.remaining_resources() {
+            *snapshot.resources.entry(key).or_default() += charges;
+        }
+    }
+    snapshot
+}
+
+fn rest(party: &[IntMutCombatant], rest: Rest, hit_dice_policy: HitDicePolicy) {
+    for member in party {
+        match rest {
+            Rest::None => {}
+            Rest::Short => member.borrow_mut().short_rest(hit_dice_policy),
+            Rest::Long => member.borrow_mut().long_rest(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        action::{
+            attack::Attack,
+            multi::MultiAction,
+            negative_effect::negative_effect::NegativeEffect,
+            single::{Execution, SingleAction},
+        },
+        attack::{area::Targets, damage::DamageRoll, save_based::SaveBasedAttack},
+        combat::{action_selection::SelectionMode, encounter::EncounterSettings, lines::Line},
+        combatant::{
+            config::{ActionType, CombatantConfig},
+            defences::{cover::Shelter, save::SaveModifiers},
+            legendary::LegendaryCfg,
+            state::{Recharge, ResourceCfg, ResourceCfgs, ResourceCosts, ResourceKey},
+            stats::CombatantStats,
+        },
+        statistics::{DayStatistics, PartySnapshot, Statistics},
+        targeting::Targeting,
+        utils::save::{Save, SaveType},
+    };
+
+    use super::{AdventuringDay, DayEncounter, Rest};
+
+    #[derive(Default)]
+    struct RecordedDay {
+        snapshots: Vec<PartySnapshot>,
+        survived: bool,
+    }
+
+    impl Statistics for RecordedDay {
+        fn record_round(&mut self) {}
+        fn record_win(&mut self, _nr_survivors: usize) {}
+        fn print(&self, _nr_repetitions: usize) {}
+    }
+
+    impl DayStatistics for RecordedDay {
+        fn record_fight_won(&mut self, _encounter: usize, party: &PartySnapshot) {
+            self.snapshots.push(party.clone());
+        }
+
+        fn record_day_survived(&mut self) {
+            self.survived = true;
+        }
+    }
+
+    fn combatant(hp: u32, resources: ResourceCfgs, actions: Vec<ActionType>) -> CombatantConfig {
+        CombatantConfig {
+            name: String::from("test"),
+            resources,
+            actions,
+            stats: CombatantStats {
+                max_hp: hp,
+                ac: 10,
+                initiative: 0,
+                saves: SaveModifiers::default(),
+                ..Default::default()
+            },
+            selection_mode: SelectionMode::FirstAvailable,
+            reactions: vec![],
+            legendary: LegendaryCfg::default(),
+            targeting: Targeting::default(),
+            position: None,
+            line: Line::default(),
+            slip_past: false,
+            shelter: Shelter::default(),
+            surprised: false,
+            initiative_group: None,
+            morale: None,
+            leader: false,
+        }
+    }
+
+    #[test]
+    fn resources_carry_over_until_rest() {
+        let surge = ResourceKey::Named("surge".to_string());
+        let finisher = SingleAction {
+            resource_cost: ResourceCosts::from([(surge.clone(), 1)]),
+            execution: Execution::ApplyNegativeEffect(NegativeEffect::Saveable(
+                SaveBasedAttack::new(
+                    Save::new(SaveType::DEX, 10),
+                    Targets::Number(1),
+                    true,
+                    DamageRoll::new(vec![], 50),
+                ),
+            )),
+        };
+        let player = combatant(
+            20,
+            ResourceCfgs::from([(
+                surge.clone(),
+                ResourceCfg::new(2, Some(Recharge::ShortRest)),
+            )]),
+            vec![ActionType::MultiAction(MultiAction::new(vec![finisher]))],
+        );
+        let encounter = |rest_after| DayEncounter {
+            enemies: vec![combatant(10, ResourceCfgs::new(), vec![])],
+            lair: None,
+            settings: Default::default(),
+            rest_after,
+        };
+        let day = AdventuringDay::new(
+            vec![player],
+            vec![
+                encounter(Rest::None),
+                encounter(Rest::Short),
+                encounter(Rest::None),
+            ],
+        );
+        let mut stats = RecordedDay::default();
+
+        day.run(&mut stats);
+
+        let remaining: Vec<u32> = stats
+            .snapshots
+            .iter()
+            .map(|s| s.resources[&surge])
+            .collect();
+        assert_eq!(remaining, vec![1, 0, 1]);
+        assert!(stats.survived);
+    }
+
+    #[test]
+    fn party_moves_on_after_a_draw() {
+        let strike = SingleAction {
+            resource_cost: ResourceCosts::new(),
+            execution: Execution::Attack(Attack::new(20, DamageRoll::new(vec![], 5))),
+        };
+        let player = combatant(
+            20,
+            ResourceCfgs::new(),
+            vec![ActionType::MultiAction(MultiAction::new(vec![strike]))],
+        );
+        let encounter = |hp, round_limit| DayEncounter {
+            enemies: vec![combatant(hp, ResourceCfgs::new(), vec![])],
+            lair: None,
+            settings: EncounterSettings {
+                round_limit,
+                ..Default::default()
+            },
+            rest_after: Rest::None,
+        };
+        let day = AdventuringDay::new(
+            vec![player],
+            vec![encounter(1000, Some(2)), encounter(5, None)],
+        );
+        let mut stats = RecordedDay::default();
+
+        day.run(&mut stats);
+
+        assert_eq!(stats.snapshots.len(), 1);
+        assert!(stats.survived);
+    }
+}
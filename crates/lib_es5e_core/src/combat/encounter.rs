@@ -1,76 +1,326 @@
 use std::{cell::RefCell, rc::Rc};
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    combatant::{combatant::Combatant, config::CombatantConfig},
-    statistics::Statistics,
+    action::action::{Action, ActionSlot},
+    combat::{
+        action_selection::{SelectionMode, TurnEconomy},
+        grid::{self, Grid},
+        initiative::{roll_initiative, InitiativeRules},
+        lair::{Lair, LairCfg, LAIR_INITIATIVE},
+        lines,
+        lookahead,
+        morale::{test_morale, Rout},
+        outcome::{end_of_round, is_stalemate, Objective, Outcome},
+        reinforcements::{reinforce, Reinforcement},
+    },
+    combatant::{combatant::Combatant, config::CombatantConfig, defences::cover::Shelter},
+    statistics::{EnemyFates, Statistics},
 };
-use crate::utils::dice::Die::D20;
-use crate::utils::rollable::Rollable;
 
 pub struct Encounter {
     players: Vec<CombatantConfig>,
     enemies: Vec<CombatantConfig>,
+    lair: Option<LairCfg>,
+    settings: EncounterSettings,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Side {
+    #[serde(rename = "players")]
+    Players,
+    #[serde(rename = "enemies")]
+    Enemies,
+}
+
+/// Rules of an encounter that do not belong to any single combatant
+#[derive(Clone, Debug, Default)]
+pub struct EncounterSettings {
+    /// The fight is called off as a draw after this many rounds
+    pub round_limit: Option<usize>,
+    /// Goal of the players that can end the fight before either side is defeated
+    pub objective: Option<Objective>,
+    /// Side that is caught by surprise and loses its first turn
+    pub surprised: Option<Side>,
+    /// Side that lies in ambush. Every combatant of the other side that notices none of the
+    /// ambushers with its passive Perception against their Stealth checks is surprised.
+    pub ambush: Option<Side>,
+    /// Grid on which the combatants move and reach only the targets in range of their actions
+    pub grid: Option<Grid>,
+    /// Cover and obscurement each side enjoys against its attackers
+    pub shelter: SideShelters,
+    /// How the combatants roll initiative
+    pub initiative: InitiativeRules,
+    /// Combatants that join either side once their trigger fires
+    pub reinforcements: Vec<Reinforcement>,
+}
+
+/// Cover and obscurement of the players and of the enemies
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SideShelters {
+    #[serde(default)]
+    pub players: Shelter,
+    #[serde(default)]
+    pub enemies: Shelter,
 }
 
 pub type IntMutCombatant = Rc<RefCell<Combatant>>;
 
 pub struct CombatantWithRelations {
-    combatant: IntMutCombatant,
-    allies: Vec<IntMutCombatant>,
-    enemies: Vec<IntMutCombatant>,
+    pub(crate) combatant: IntMutCombatant,
+    pub(crate) allies: Vec<IntMutCombatant>,
+    pub(crate) enemies: Vec<IntMutCombatant>,
+}
+
+/// State of a single fight
+pub(crate) struct Battle {
+    /// Combatants in initiative order
+    pub(crate) combatants: Vec<CombatantWithRelations>,
+    pub(crate) lair: Option<Lair>,
 }
 
 impl Encounter {
     pub fn new(players: Vec<CombatantConfig>, enemies: Vec<CombatantConfig>) -> Encounter {
-        Encounter { players, enemies }
+        Self::new_with_lair(players, enemies, None)
+    }
+
+    /// Encounter in the lair of the enemies
+    pub fn new_with_lair(
+        players: Vec<CombatantConfig>,
+        enemies: Vec<CombatantConfig>,
+        lair: Option<LairCfg>,
+    ) -> Encounter {
+        Self::new_with_settings(players, enemies, lair, EncounterSettings::default())
+    }
+
+    pub fn new_with_settings(
+        players: Vec<CombatantConfig>,
+        enemies: Vec<CombatantConfig>,
+        lair: Option<LairCfg>,
+        settings: EncounterSettings,
+    ) -> Encounter {
+        Encounter {
+            players,
+            enemies,
+            lair,
+            settings,
+        }
+    }
+
+    /// Whether any combatant may be caught by surprise
+    pub fn has_surprise(&self) -> bool {
+        self.settings.surprised.is_some()
+            || self.settings.ambush.is_some()
+            || self.players.iter().chain(&self.enemies).any(|c| c.surprised)
+    }
+
+    /// The same encounter, in which nobody is caught by surprise
+    pub fn without_surprise(&self) -> Encounter {
+        let unsurprised = |side: &[CombatantConfig]| {
+            side.iter()
+                .map(|c| CombatantConfig {
+                    surprised: false,
+                    ..c.clone()
+                })
+                .collect()
+        };
+        Encounter {
+            players: unsurprised(&self.players),
+            enemies: unsurprised(&self.enemies),
+            lair: self.lair.clone(),
+            settings: EncounterSettings {
+                surprised: None,
+                ambush: None,
+                ..self.settings.clone()
+            },
+        }
     }
 
     pub fn run<T: Statistics>(&self, stats: &mut T) {
-        let players = self.instantiate_for_run(&self.players);
-        let enemies = self.instantiate_for_run(&self.enemies);
-        let all_combatants = self.setup_combatants(&players, &enemies);
-
-        loop {
-            run_round(&all_combatants);
-            stats.record_round();
-            if all_defeated(&players) {
-                break;
-            }
-            if all_defeated(&enemies) {
-                let nr_survivors = count_survivors(&players);
-                stats.record_win(nr_survivors);
-                break;
-            }
+        let players = instantiate_for_run(&self.players);
+        fight(
+            &players,
+            &self.enemies,
+            self.lair.as_ref(),
+            &self.settings,
+            stats,
+        );
+    }
+}
+
+/// Lets the given players fight fresh instances of the enemies until one side is defeated
+/// or the round limit is reached. Returns how the fight ended.
+pub(crate) fn fight<T: Statistics>(
+    players: &[IntMutCombatant],
+    enemies: &[CombatantConfig],
+    lair: Option<&LairCfg>,
+    settings: &EncounterSettings,
+    stats: &mut T,
+) -> Outcome {
+    let mut players = players.to_vec();
+    let mut enemies = instantiate_for_run(enemies);
+    let mut battle = setup_battle(&players, &enemies, lair, settings.initiative);
+    if let Some(boss) = boss(&enemies) {
+        let order: Vec<_> = battle
+            .combatants
+            .iter()
+            .map(|x| x.combatant.borrow().name().to_string())
+            .collect();
+        stats.record_initiative(&order, boss.borrow().name());
+    }
+    // the players may come from an earlier fight of the adventuring day
+    players.iter().for_each(|p| {
+        let mut player = p.borrow_mut();
+        player.reset_position();
+        player.rally();
+    });
+    if let Some(grid) = &settings.grid {
+        grid.place(&players, &enemies);
+    }
+    let shelters = [
+        (&players, settings.shelter.players),
+        (&enemies, settings.shelter.enemies),
+    ];
+    for (side, shelter) in shelters {
+        side.iter().for_each(|c| c.borrow_mut().take_shelter(shelter));
+    }
+    surprise(&players, &enemies, settings);
+
+    let mut pending: Vec<_> = settings.reinforcements.iter().collect();
+    let mut round = 0;
+    let outcome = loop {
+        round += 1;
+        if settings.round_limit.is_some_and(|limit| round > limit) {
+            break Outcome::Draw;
         }
+        reinforce(
+            &mut battle,
+            &mut players,
+            &mut enemies,
+            &mut pending,
+            round,
+            settings,
+        );
+        // reinforcements still to come might break the stalemate
+        if pending.is_empty() && is_stalemate(&battle) {
+            break match settings.objective {
+                Some(Objective::Survive(_)) => Outcome::Survived,
+                _ => Outcome::Stalemate,
+            };
+        }
+        run_round(&battle);
+        stats.record_round();
+        let objective = settings.objective.as_ref();
+        if let Some(outcome) = end_of_round(round, &players, &enemies, objective) {
+            break outcome;
+        }
+    };
+    if outcome.is_win() {
+        stats.record_win(count_survivors(&players));
     }
+    stats.record_outcome(outcome);
+    stats.record_enemy_fates(&fates(&enemies));
+    outcome
+}
 
-    fn setup_combatants(&self, players: &Vec<IntMutCombatant>, enemies: &Vec<IntMutCombatant>) -> Vec<CombatantWithRelations> {
-        let players_with_relations = map_to_combatants_with_relations(players, enemies);
-        let mut enemies_with_relations = map_to_combatants_with_relations(enemies, players);
+fn fates(enemies: &[IntMutCombatant]) -> EnemyFates {
+    let mut fates = EnemyFates::default();
+    for enemy in enemies {
+        let enemy = enemy.borrow();
+        match enemy.routed() {
+            _ if !enemy.is_conscious() => fates.killed += 1,
+            Some(Rout::Flee) => fates.fled += 1,
+            Some(Rout::Surrender) => fates.surrendered += 1,
+            None => {}
+        }
+    }
+    fates
+}
 
-        let mut all_combatants = players_with_relations;
-        all_combatants.append(&mut enemies_with_relations);
-        all_combatants.sort_by_key(|x| -(D20.roll() as i16 + x.combatant.borrow().stats.initiative));
-        all_combatants
+/// Surprises the combatants that are surprised on their own, those of the surprised side and
+/// those of the ambushed side that notice none of the ambushers
+fn surprise(
+    players: &[IntMutCombatant],
+    enemies: &[IntMutCombatant],
+    settings: &EncounterSettings,
+) {
+    let sides = |side| match side {
+        Side::Players => (players, enemies),
+        Side::Enemies => (enemies, players),
+    };
+    let mut surprised: Vec<_> = players
+        .iter()
+        .chain(enemies)
+        .filter(|c| c.borrow().starts_surprised)
+        .collect();
+    if let Some((side, _)) = settings.surprised.map(sides) {
+        surprised.extend(side);
+    }
+    if let Some((ambushers, ambushed)) = settings.ambush.map(sides) {
+        let stealth: Vec<_> = ambushers
+            .iter()
+            .map(|a| (a, a.borrow().stealth_check()))
+            .collect();
+        surprised.extend(ambushed.iter().filter(|c| {
+            let c = c.borrow();
+            !stealth
+                .iter()
+                .any(|(ambusher, check)| c.notices(&ambusher.borrow(), *check))
+        }));
+    }
+    for combatant in surprised {
+        combatant.borrow_mut().surprise();
+    }
+}
+
+/// The enemy with the most hit points
+pub(crate) fn boss(enemies: &[IntMutCombatant]) -> Option<&IntMutCombatant> {
+    // the last of several enemies with the same hit points is the maximum, so iterate in reverse
+    enemies.iter().rev().max_by_key(|e| e.borrow().max_hp())
+}
+
+fn setup_battle(
+    players: &[IntMutCombatant],
+    enemies: &[IntMutCombatant],
+    lair: Option<&LairCfg>,
+    rules: InitiativeRules,
+) -> Battle {
+    let players_with_relations = map_to_combatants_with_relations(players, enemies);
+    let enemies_with_relations = map_to_combatants_with_relations(enemies, players);
+    let initiative_order = roll_initiative(players_with_relations, enemies_with_relations, rules);
+    for (initiative, x) in &initiative_order {
+        x.combatant.borrow_mut().enter_initiative(*initiative);
     }
 
-    fn instantiate_for_run(&self, combatants: &[CombatantConfig]) -> Vec<IntMutCombatant> {
-        combatants
+    let lair = lair.map(|lair| {
+        let position = initiative_order
             .iter()
-            .map(|x| Rc::new(RefCell::new(x.to_combatant())))
-            .collect()
+            .filter(|(initiative, _)| *initiative >= LAIR_INITIATIVE)
+            .count();
+        Lair::new(lair, enemies.to_vec(), players.to_vec(), position)
+    });
+    Battle {
+        combatants: initiative_order.into_iter().map(|(_, x)| x).collect(),
+        lair,
     }
 }
 
-fn map_to_combatants_with_relations(
+pub(crate) fn instantiate_for_run(combatants: &[CombatantConfig]) -> Vec<IntMutCombatant> {
+    combatants
+        .iter()
+        .map(|x| Rc::new(RefCell::new(x.to_combatant())))
+        .collect()
+}
+
+pub(crate) fn map_to_combatants_with_relations(
     allies: &[IntMutCombatant],
     enemies: &[IntMutCombatant],
 ) -> Vec<CombatantWithRelations> {
     let allies_with_relations: Vec<_> = allies
-        .to_vec()
-        .into_iter()
+        .iter()
         .map(|combatant| CombatantWithRelations {
-            combatant,
+            combatant: combatant.clone(),
             allies: allies.to_vec(),
             enemies: enemies.to_vec(),
         })
@@ -78,28 +328,113 @@ fn map_to_combatants_with_relations(
     allies_with_relations
 }
 
-fn run_round(combatants: &[CombatantWithRelations]) {
-    for combatant in combatants {
-        if combatant.combatant.borrow().is_conscious() {
-            take_turn(combatant);
+pub(crate) fn run_round(battle: &Battle) {
+    run_turns_from(battle, 0);
+}
+
+/// Runs the turns from the given position in the initiative order until the end of the round
+pub(crate) fn run_turns_from(battle: &Battle, start: usize) {
+    let combatants = &battle.combatants;
+    for idx in start..=combatants.len() {
+        if let Some(lair) = battle.lair.as_ref().filter(|lair| lair.position == idx) {
+            lair.take_turn();
+        }
+        if idx < combatants.len() && combatants[idx].combatant.borrow().is_fighting() {
+            take_turn(battle, idx);
+            take_legendary_actions(combatants, idx);
         }
     }
 }
 
-fn take_turn(combatant: &CombatantWithRelations) {
+fn take_turn(battle: &Battle, idx: usize) {
+    let combatant = &battle.combatants[idx];
     combatant.combatant.borrow_mut().update_resources_on_start();
-    let maybe_action = combatant.combatant.borrow().first_available_action();
-    if let Some(action) = maybe_action {
-        action.execute(&combatant.allies, &combatant.enemies);
-        combatant
-            .combatant
-            .borrow_mut()
-            .use_resources(action.as_ref());
+    if combatant.combatant.borrow_mut().end_surprise() || test_morale(combatant) {
+        return;
+    }
+    let mut economy = TurnEconomy::default();
+    if lines::slip_past_front_line(combatant) {
+        economy.spend_action();
+    }
+    cast_leveled_bonus_action_spell(combatant, &mut economy);
+    if let Some(action) = choose_action(battle, idx, &economy) {
+        perform_action(combatant, action.as_ref(), &mut economy);
+    }
+    perform_bonus_action(combatant, &mut economy);
+}
+
+/// At the end of a creature's turn, every other creature with legendary actions may take one of them
+fn take_legendary_actions(combatants: &[CombatantWithRelations], after_turn_of: usize) {
+    for (idx, combatant) in combatants.iter().enumerate() {
+        if idx == after_turn_of {
+            continue;
+        }
+        let maybe_action = combatant.combatant.borrow_mut().take_legendary_action();
+        if let Some(action) = maybe_action {
+            action.execute(&combatant.combatant, &combatant.allies, &combatant.enemies);
+            combatant.combatant.borrow_mut().use_resources(action.as_ref());
+        }
+    }
+}
+
+fn choose_action(battle: &Battle, idx: usize, economy: &TurnEconomy) -> Option<Rc<dyn Action>> {
+    let combatant = battle.combatants[idx].combatant.borrow();
+    match combatant.selection_mode() {
+        SelectionMode::FirstAvailable => {
+            combatant.first_available_action(ActionSlot::Action, economy)
+        }
+        SelectionMode::Lookahead(config) => {
+            let config = config.clone();
+            let candidates = combatant.available_actions(ActionSlot::Action, economy);
+            drop(combatant);
+            lookahead::select_action(battle, idx, candidates, &config, economy)
+        }
+    }
+}
+
+pub(crate) fn perform_action(
+    combatant: &CombatantWithRelations,
+    action: &dyn Action,
+    economy: &mut TurnEconomy,
+) {
+    if economy.may_move() {
+        grid::move_within_range(combatant, action.range());
+        economy.record_move();
+    }
+    combatant.combatant.borrow_mut().leave_total_cover();
+    action.execute(&combatant.combatant, &combatant.allies, &combatant.enemies);
+    combatant.combatant.borrow_mut().use_resources(action);
+    economy.record(action);
+}
+
+/// Casts a leveled spell that takes a bonus action, e.g. Healing Word, before choosing the action,
+/// which can then only be a cantrip or no spell at all
+fn cast_leveled_bonus_action_spell(combatant: &CombatantWithRelations, economy: &mut TurnEconomy) {
+    let spell = combatant
+        .combatant
+        .borrow()
+        .first_available_action(ActionSlot::BonusAction, economy)
+        .filter(|action| action.spell_level().is_some_and(|level| level > 0));
+    if let Some(spell) = spell {
+        perform_action(combatant, spell.as_ref(), economy);
+    }
+}
+
+pub(crate) fn perform_bonus_action(combatant: &CombatantWithRelations, economy: &mut TurnEconomy) {
+    if !combatant.combatant.borrow().is_fighting() {
+        return;
+    }
+    let maybe_bonus_action = combatant
+        .combatant
+        .borrow()
+        .first_available_action(ActionSlot::BonusAction, economy);
+    if let Some(bonus_action) = maybe_bonus_action {
+        perform_action(combatant, bonus_action.as_ref(), economy);
     }
 }
 
-fn all_defeated(combatants: &[IntMutCombatant]) -> bool {
-    combatants.iter().all(|p| !p.borrow().is_conscious())
+pub(crate) fn all_defeated(combatants: &[IntMutCombatant]) -> bool {
+    combatants.iter().all(|p| !p.borrow().is_fighting())
 }
 
 fn count_survivors(combatants: &[IntMutCombatant]) -> usize {
@@ -108,3 +443,289 @@ fn count_survivors(combatants: &[IntMutCombatant]) -> usize {
         .filter(|p| p.borrow().is_conscious())
         .count()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        action::{
+            attack::Attack,
+            multi::MultiAction,
+            single::{Execution, SingleAction},
+        },
+        attack::damage::DamageRoll,
+        combat::{
+            action_selection::SelectionMode, lines::Line, reinforcements::ArrivalTrigger,
+        },
+        combatant::{
+            config::{ActionType, CombatantConfig},
+            defences::cover::Obscurement,
+            legendary::LegendaryCfg,
+            skill::Skill,
+            state::{Recharge, ResourceCfg, ResourceCfgs, ResourceCosts, ResourceKey},
+            stats::CombatantStats,
+        },
+        targeting::Targeting,
+    };
+
+    use super::*;
+
+    fn combatant(damage: i16) -> CombatantConfig {
+        let attack = SingleAction {
+            resource_cost: ResourceCosts::new(),
+            execution: Execution::Attack(Attack::new(0, DamageRoll::new(vec![], damage))),
+        };
+        CombatantConfig {
+            name: String::from("test"),
+            resources: ResourceCfgs::new(),
+            actions: vec![ActionType::MultiAction(MultiAction::new(vec![attack]))],
+            stats: CombatantStats {
+                max_hp: 10,
+                ac: 0,
+                ..Default::default()
+            },
+            selection_mode: SelectionMode::FirstAvailable,
+            reactions: vec![],
+            legendary: LegendaryCfg::default(),
+            targeting: Targeting::default(),
+            position: None,
+            line: Line::default(),
+            slip_past: false,
+            shelter: Shelter::default(),
+            surprised: false,
+            initiative_group: None,
+            morale: None,
+            leader: false,
+        }
+    }
+
+    /// Records only how the fights end
+    #[derive(Default)]
+    struct RecordedOutcomes(Vec<Outcome>);
+
+    impl Statistics for RecordedOutcomes {
+        fn record_round(&mut self) {}
+        fn record_win(&mut self, _nr_survivors: usize) {}
+        fn record_outcome(&mut self, outcome: Outcome) {
+            self.0.push(outcome);
+        }
+        fn print(&self, _nr_repetitions: usize) {}
+    }
+
+    fn outcome(damage: i16, settings: &EncounterSettings) -> (bool, Outcome) {
+        let players = instantiate_for_run(&[combatant(damage)]);
+        let mut stats = RecordedOutcomes::default();
+        let outcome = fight(&players, &[combatant(damage)], None, settings, &mut stats);
+        assert_eq!(stats.0, [outcome]);
+        (outcome.is_win(), outcome)
+    }
+
+    #[test]
+    fn round_limit_ends_fight_in_a_draw() {
+        let settings = EncounterSettings {
+            round_limit: Some(3),
+            ..Default::default()
+        };
+
+        assert_eq!(outcome(1, &settings), (false, Outcome::Draw));
+    }
+
+    #[test]
+    fn sides_that_cannot_damage_each_other_end_in_a_stalemate() {
+        assert_eq!(
+            outcome(0, &EncounterSettings::default()),
+            (false, Outcome::Stalemate)
+        );
+        let survive = EncounterSettings {
+            objective: Some(Objective::Survive(2)),
+            ..Default::default()
+        };
+        assert_eq!(outcome(0, &survive), (true, Outcome::Survived));
+        assert_eq!(outcome(1, &survive), (true, Outcome::Survived));
+    }
+
+    #[test]
+    fn spent_resources_that_do_not_recharge_end_in_a_stalemate() {
+        let limited = |recharge| {
+            let key = ResourceKey::Named(String::from("charge"));
+            let mut player = combatant(1);
+            player.resources = ResourceCfgs::from([(key.clone(), ResourceCfg::new(1, recharge))]);
+            player.actions = vec![ActionType::MultiAction(MultiAction::new(vec![SingleAction {
+                resource_cost: ResourceCosts::from([(key, 1)]),
+                execution: Execution::Attack(Attack::new(0, DamageRoll::new(vec![], 1))),
+            }]))];
+            let settings = EncounterSettings {
+                round_limit: Some(5),
+                ..Default::default()
+            };
+            let players = instantiate_for_run(&[player]);
+            fight(&players, &[combatant(0)], None, &settings, &mut RecordedOutcomes::default())
+        };
+
+        assert_eq!(limited(None), Outcome::Stalemate);
+        assert_eq!(limited(Some(Recharge::Recharge5)), Outcome::Draw);
+        assert_eq!(limited(Some(Recharge::TurnStart)), Outcome::Draw);
+    }
+
+    #[test]
+    fn leveled_bonus_action_spell_leaves_only_cantrips_for_the_action() {
+        let spell = |name: &str, slot_type, level| {
+            let cast = SingleAction {
+                resource_cost: ResourceCosts::from([(ResourceKey::Named(name.to_string()), 1)]),
+                execution: Execution::Attack(Attack::new(0, DamageRoll::new(vec![], 1))),
+            };
+            MultiAction::new_with_slot(vec![cast], slot_type, Some(level))
+        };
+        let charge = |name: &str| (ResourceKey::Named(name.to_string()), ResourceCfg::new(1, None));
+        let caster = CombatantConfig {
+            resources: ResourceCfgs::from([charge("fireball"), charge("healing word")]),
+            actions: vec![
+                ActionType::MultiAction(spell("fireball", ActionSlot::Action, 3)),
+                ActionType::MultiAction(spell("healing word", ActionSlot::BonusAction, 1)),
+            ],
+            ..combatant(0)
+        };
+        let players = instantiate_for_run(&[caster]);
+        let enemies = instantiate_for_run(&[combatant(0)]);
+        let battle = setup_battle(&players, &enemies, None, InitiativeRules::default());
+        let idx = battle
+            .combatants
+            .iter()
+            .position(|c| Rc::ptr_eq(&c.combatant, &players[0]))
+            .unwrap();
+
+        take_turn(&battle, idx);
+
+        let remaining: Vec<_> = players[0]
+            .borrow()
+            .remaining_resources()
+            .into_values()
+            .collect();
+        assert_eq!(remaining, vec![1, 0]);
+    }
+
+    #[test]
+    fn combatants_on_a_grid_must_close_in_to_attack() {
+        let players = instantiate_for_run(&[combatant(100)]);
+        let enemies = instantiate_for_run(&[combatant(0)]);
+        let battle = setup_battle(&players, &enemies, None, InitiativeRules::default());
+        Grid { distance: 90 }.place(&players, &enemies);
+        let distance = || {
+            let position = |c: &IntMutCombatant| c.borrow().position().unwrap();
+            position(&players[0]).distance(&position(&enemies[0]))
+        };
+
+        run_round(&battle);
+        assert_eq!(enemies[0].borrow().hp(), 10);
+        assert_eq!(distance(), 30);
+
+        run_round(&battle);
+        assert_eq!(distance(), 5);
+    }
+
+    #[test]
+    fn reinforcements_join_once_their_trigger_fires() {
+        let mut players = instantiate_for_run(&[combatant(0)]);
+        let mut enemies = instantiate_for_run(&[combatant(0)]);
+        let mut battle = setup_battle(&players, &enemies, None, InitiativeRules::default());
+        let wave = Reinforcement {
+            side: Side::Enemies,
+            trigger: ArrivalTrigger::Round(2),
+            combatants: vec![combatant(0), combatant(0)],
+        };
+        let rescue = Reinforcement {
+            side: Side::Players,
+            trigger: ArrivalTrigger::FirstDown,
+            combatants: vec![combatant(0)],
+        };
+        let settings = EncounterSettings {
+            reinforcements: vec![wave, rescue],
+            ..Default::default()
+        };
+        let mut pending: Vec<_> = settings.reinforcements.iter().collect();
+        reinforce(
+            &mut battle,
+            &mut players,
+            &mut enemies,
+            &mut pending,
+            1,
+            &settings,
+        );
+        assert_eq!(battle.combatants.len(), 2);
+
+        reinforce(
+            &mut battle,
+            &mut players,
+            &mut enemies,
+            &mut pending,
+            2,
+            &settings,
+        );
+        assert_eq!((battle.combatants.len(), enemies.len()), (4, 3));
+        let player = battle
+            .combatants
+            .iter()
+            .find(|c| Rc::ptr_eq(&c.combatant, &players[0]))
+            .unwrap();
+        assert_eq!(player.enemies.len(), 3);
+        let newcomer = battle
+            .combatants
+            .iter()
+            .find(|c| Rc::ptr_eq(&c.combatant, &enemies[2]))
+            .unwrap();
+        assert_eq!((newcomer.allies.len(), newcomer.enemies.len()), (3, 1));
+
+        let lethal = DamageRoll::new(vec![], 10).calculate_regular();
+        players[0].borrow_mut().take_damage(lethal);
+        reinforce(
+            &mut battle,
+            &mut players,
+            &mut enemies,
+            &mut pending,
+            3,
+            &settings,
+        );
+        assert_eq!((battle.combatants.len(), players.len()), (5, 2));
+    }
+
+    #[test]
+    fn surprised_side_loses_first_turn() {
+        let players = instantiate_for_run(&[combatant(100)]);
+        let enemies = instantiate_for_run(&[combatant(0)]);
+        let battle = setup_battle(&players, &enemies, None, InitiativeRules::default());
+        let settings = EncounterSettings {
+            surprised: Some(Side::Players),
+            ..Default::default()
+        };
+        surprise(&players, &enemies, &settings);
+
+        run_round(&battle);
+
+        assert_eq!(enemies[0].borrow().hp(), 10);
+        assert!(!players[0].borrow_mut().end_surprise());
+    }
+
+    #[test]
+    fn ambushers_surprise_those_that_notice_none_of_them() {
+        let players = instantiate_for_run(&[combatant(1), combatant(1)]);
+        let enemies = instantiate_for_run(&[combatant(1)]);
+        enemies[0].borrow_mut().stats.skills.insert(Skill::Stealth, 20);
+        players[1].borrow_mut().stats.skills.insert(Skill::Perception, 40);
+        let settings = EncounterSettings {
+            ambush: Some(Side::Enemies),
+            ..Default::default()
+        };
+
+        surprise(&players, &enemies, &settings);
+        assert!(players[0].borrow_mut().end_surprise());
+        assert!(!players[1].borrow_mut().end_surprise());
+
+        enemies[0].borrow_mut().take_shelter(Shelter {
+            cover: None,
+            obscured: Some(Obscurement::Heavily),
+            melee_steps_around: false,
+        });
+        surprise(&players, &enemies, &settings);
+        assert!(players[1].borrow_mut().end_surprise());
+        assert!(!enemies[0].borrow_mut().end_surprise());
+    }
+}
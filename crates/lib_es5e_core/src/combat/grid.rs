@@ -0,0 +1,297 @@
+use std::{borrow::Cow, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::action::reaction::provoke_opportunity_attacks;
+
+use super::encounter::{CombatantWithRelations, IntMutCombatant, Side};
+
+/// Edge of a square of the grid in feet, which is also the reach of most melee attacks
+pub const SQUARE: u32 = 5;
+
+/// Speed in feet of combatants that do not give theirs
+pub const DEFAULT_SPEED: u32 = 30;
+
+/// Square of the grid
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Position {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    /// Distance in feet, in which every diagonal step counts as a single square
+    pub fn distance(&self, other: &Position) -> u32 {
+        let squares = self.x.abs_diff(other.x).max(self.y.abs_diff(other.y));
+        squares * SQUARE
+    }
+}
+
+/// Spatial layer of an encounter. Combatants move on a grid and only reach the targets within
+/// the range of their actions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Grid {
+    /// Distance in feet between the lines of players and enemies at the start of the fight
+    pub distance: u32,
+}
+
+impl Grid {
+    /// Puts the combatants without a position of their own next to each other, the players in one
+    /// line and the enemies in another line the grid's distance away
+    pub(crate) fn place(&self, players: &[IntMutCombatant], enemies: &[IntMutCombatant]) {
+        self.place_side(players, Side::Players);
+        self.place_side(enemies, Side::Enemies);
+    }
+
+    /// Puts the combatants without a position of their own next to each other on the starting
+    /// line of their side, e.g. reinforcements that join the fight in progress
+    pub(crate) fn place_side(&self, combatants: &[IntMutCombatant], side: Side) {
+        let y = match side {
+            Side::Players => 0,
+            Side::Enemies => (self.distance / SQUARE) as i32,
+        };
+        let offset = combatants.len() as i32 / 2;
+        for (idx, combatant) in combatants.iter().enumerate() {
+            let mut combatant = combatant.borrow_mut();
+            let position = combatant
+                .start_position
+                .unwrap_or(Position::new(idx as i32 - offset, y));
+            combatant.move_to(position);
+        }
+    }
+}
+
+/// The given enemies that are within the range of the actor, or all of them if the actor has no position
+pub(crate) fn within_range<'a>(
+    actor: &IntMutCombatant,
+    enemies: &'a [IntMutCombatant],
+    range: u32,
+) -> Cow<'a, [IntMutCombatant]> {
+    let Some(position) = actor.borrow().position() else {
+        return Cow::Borrowed(enemies);
+    };
+    let in_range = |enemy: &IntMutCombatant| {
+        enemy
+            .borrow()
+            .position()
+            .map_or(true, |p| p.distance(&position) <= range)
+    };
+    Cow::Owned(enemies.iter().filter(|e| in_range(e)).cloned().collect())
+}
+
+/// Moves the combatant up to its speed to a free square from which a conscious enemy is within the
+/// given range, or as close to the enemies as possible. Combatants with a range beyond melee reach
+/// keep as far from their enemies as their range allows. Enemies block the way, and leaving the
+/// reach of an adjacent enemy provokes its opportunity attack.
+pub(crate) fn move_within_range(combatant: &CombatantWithRelations, range: u32) {
+    let (start, speed) = {
+        let actor = combatant.combatant.borrow();
+        match actor.position() {
+            Some(position) => (position, actor.speed()),
+            None => return,
+        }
+    };
+    let enemies = conscious_positions(&combatant.enemies, &combatant.combatant);
+    if enemies.is_empty() {
+        return;
+    }
+    let mut occupied = conscious_positions(&combatant.allies, &combatant.combatant);
+    occupied.extend(&enemies);
+
+    let nearest_enemy = |position: &Position| {
+        enemies
+            .iter()
+            .map(|enemy| enemy.distance(position))
+            .min()
+            .unwrap_or(u32::MAX)
+    };
+    let mut candidates: Vec<_> = reachable(start, speed / SQUARE, &enemies)
+        .into_iter()
+        .filter(|position| !occupied.contains(position))
+        .collect();
+    // ties are broken the same way every time
+    candidates.sort_by_key(|position| (position.x, position.y));
+    let keeps_distance = range > SQUARE;
+    let best = candidates.into_iter().min_by_key(|position| {
+        let nearest = nearest_enemy(position);
+        let moved = start.distance(position);
+        // prefers straight paths among squares that are equally far away on the grid
+        let detour = start.x.abs_diff(position.x) + start.y.abs_diff(position.y);
+        match (nearest <= range, keeps_distance) {
+            (true, true) => (0, u32::MAX - nearest, moved, detour),
+            (true, false) => (0, moved, nearest, detour),
+            (false, _) => (1, nearest, moved, detour),
+        }
+    });
+    let Some(position) = best.filter(|position| *position != start) else {
+        return;
+    };
+    let left_behind: Vec<_> = combatant
+        .enemies
+        .iter()
+        .filter(|enemy| {
+            let enemy = enemy.borrow();
+            enemy.is_fighting()
+                && enemy
+                    .position()
+                    .is_some_and(|p| p.distance(&start) <= SQUARE && p.distance(&position) > SQUARE)
+        })
+        .cloned()
+        .collect();
+    provoke_opportunity_attacks(&combatant.combatant, &left_behind);
+    // a combatant downed by an opportunity attack stops where it is
+    if combatant.combatant.borrow().is_fighting() {
+        combatant.combatant.borrow_mut().move_to(position);
+    }
+}
+
+/// Squares that can be reached from the start within the given number of steps without passing
+/// through the squares of enemies
+fn reachable(start: Position, steps: u32, enemies: &[Position]) -> Vec<Position> {
+    let steps = steps as i32;
+    let width = 2 * steps + 1;
+    let index = |p: Position| ((p.x - start.x + steps) * width + p.y - start.y + steps) as usize;
+    let mut visited = vec![false; (width * width) as usize];
+    visited[index(start)] = true;
+    for enemy in enemies {
+        if start.distance(enemy) <= steps as u32 * SQUARE {
+            visited[index(*enemy)] = true;
+        }
+    }
+    let mut reached = vec![start];
+    let mut frontier = vec![start];
+    for _ in 0..steps {
+        let mut next = vec![];
+        for square in frontier {
+            for (dx, dy) in (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy))) {
+                let neighbour = Position::new(square.x + dx, square.y + dy);
+                if !std::mem::replace(&mut visited[index(neighbour)], true) {
+                    next.push(neighbour);
+                }
+            }
+        }
+        reached.extend(&next);
+        frontier = next;
+    }
+    reached
+}
+
+fn conscious_positions(side: &[IntMutCombatant], except: &IntMutCombatant) -> Vec<Position> {
+    side.iter()
+        .filter(|c| !Rc::ptr_eq(c, except))
+        .filter_map(|c| {
+            let c = c.borrow();
+            c.position().filter(|_| c.is_fighting())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        action::{
+            negative_effect::negative_effect::NegativeEffect,
+            reaction::{Reaction, ReactionEffect, ReactionTrigger},
+            single::Execution,
+        },
+        attack::{area::Targets, damage::DamageRoll, save_based::SaveBasedAttack},
+        combat::encounter::CombatantWithRelations,
+        combatant::state::ResourceCosts,
+        targeting::strategy::tests::init_identical_test_targets,
+        utils::save::{Save, SaveType},
+    };
+
+    use super::{move_within_range, within_range, Grid, Position};
+
+    fn combatant_with_relations(
+        players: &[super::IntMutCombatant],
+        enemies: &[super::IntMutCombatant],
+    ) -> CombatantWithRelations {
+        CombatantWithRelations {
+            combatant: players[0].clone(),
+            allies: players.to_vec(),
+            enemies: enemies.to_vec(),
+        }
+    }
+
+    #[test]
+    fn diagonals_count_as_one_square() {
+        assert_eq!(Position::new(0, 0).distance(&Position::new(3, 2)), 15);
+        assert_eq!(Position::new(-1, 4).distance(&Position::new(-1, 0)), 20);
+    }
+
+    #[test]
+    fn sides_are_placed_the_distance_apart() {
+        let players = init_identical_test_targets(3);
+        let enemies = init_identical_test_targets(2);
+        Grid { distance: 60 }.place(&players, &enemies);
+
+        let position = |c: &super::IntMutCombatant| c.borrow().position().unwrap();
+        assert_eq!(position(&players[0]), Position::new(-1, 0));
+        assert_eq!(position(&players[2]), Position::new(1, 0));
+        assert_eq!(position(&enemies[0]), Position::new(-1, 12));
+    }
+
+    #[test]
+    fn only_enemies_within_range_can_be_targeted() {
+        let players = init_identical_test_targets(1);
+        let enemies = init_identical_test_targets(2);
+        assert_eq!(within_range(&players[0], &enemies, 5).len(), 2);
+
+        players[0].borrow_mut().move_to(Position::new(0, 0));
+        enemies[0].borrow_mut().move_to(Position::new(1, 1));
+        enemies[1].borrow_mut().move_to(Position::new(0, 6));
+        assert_eq!(within_range(&players[0], &enemies, 5).len(), 1);
+        assert_eq!(within_range(&players[0], &enemies, 30).len(), 2);
+    }
+
+    #[test]
+    fn melee_combatants_close_in_and_ranged_ones_keep_away() {
+        let players = init_identical_test_targets(1);
+        let enemies = init_identical_test_targets(1);
+        players[0].borrow_mut().move_to(Position::new(0, 0));
+        enemies[0].borrow_mut().move_to(Position::new(0, 20));
+        let player = combatant_with_relations(&players, &enemies);
+
+        move_within_range(&player, 5);
+        assert_eq!(players[0].borrow().position(), Some(Position::new(0, 6)));
+
+        enemies[0].borrow_mut().move_to(Position::new(0, 7));
+        move_within_range(&player, 5);
+        assert_eq!(players[0].borrow().position(), Some(Position::new(0, 6)));
+
+        move_within_range(&player, 60);
+        assert_eq!(players[0].borrow().position(), Some(Position::new(0, 0)));
+    }
+
+    #[test]
+    fn retreating_archers_provoke_opportunity_attacks() {
+        let players = init_identical_test_targets(1);
+        let enemies = init_identical_test_targets(1);
+        players[0].borrow_mut().move_to(Position::new(0, 0));
+        enemies[0].borrow_mut().move_to(Position::new(0, 1));
+        let blow = SaveBasedAttack::new(
+            Save::new(SaveType::STR, 30),
+            Targets::Number(1),
+            false,
+            DamageRoll::new(vec![], 10),
+        );
+        enemies[0].borrow_mut().reactions = vec![Reaction {
+            trigger: ReactionTrigger::LeavesReach,
+            effect: ReactionEffect::Strike(Execution::ApplyNegativeEffect(
+                NegativeEffect::Saveable(blow),
+            )),
+            resource_cost: ResourceCosts::new(),
+        }];
+        let archer = combatant_with_relations(&players, &enemies);
+
+        move_within_range(&archer, 60);
+        let position = players[0].borrow().position().unwrap();
+        assert!(position.distance(&Position::new(0, 1)) > 5);
+        assert_eq!(players[0].borrow().hp(), 90);
+    }
+}
@@ -0,0 +1,146 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    combatant::combatant::Combatant,
+    utils::{dice::Die::D20, rollable::Rollable, save::SaveType},
+};
+
+use super::encounter::{CombatantWithRelations, IntMutCombatant};
+
+/// How the combatants of an encounter roll initiative
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum InitiativeRules {
+    /// Every combatant rolls on its own
+    #[default]
+    #[serde(rename = "individual")]
+    Individual,
+    /// Combatants of the same initiative group, e.g. identical monsters, share a single roll
+    #[serde(rename = "group")]
+    Group,
+    /// Each side rolls a single d20 and all of its combatants act before those of the other side
+    #[serde(rename = "side")]
+    Side,
+}
+
+/// Rolls initiative and sorts the combatants into initiative order. Ties are broken by the
+/// Dexterity modifier and then at random. Returns the combatants with their initiative counts.
+pub(crate) fn roll_initiative(
+    players: Vec<CombatantWithRelations>,
+    enemies: Vec<CombatantWithRelations>,
+    rules: InitiativeRules,
+) -> Vec<(i16, CombatantWithRelations)> {
+    let mut rng = thread_rng();
+    let side_rolls = loop {
+        let rolls = [D20.roll() as i16, D20.roll() as i16];
+        if rolls[0] != rolls[1] {
+            break rolls;
+        }
+    };
+    let mut group_rolls = HashMap::new();
+    let mut order: Vec<_> = [players, enemies]
+        .into_iter()
+        .enumerate()
+        .flat_map(|(side, combatants)| combatants.into_iter().map(move |x| (side, x)))
+        .map(|(side, x)| {
+            let combatant = x.combatant.borrow();
+            let mut roll = || {
+                let initiative = combatant.roll_initiative();
+                let dex = combatant.stats.abilities.modifier(&SaveType::DEX);
+                (initiative, dex, rng.gen::<u32>())
+            };
+            let key = match (rules, &combatant.initiative_group) {
+                (InitiativeRules::Side, _) => (side_rolls[side], 0, 0),
+                (InitiativeRules::Group, Some(group)) => {
+                    *group_rolls.entry(group.clone()).or_insert_with(roll)
+                }
+                _ => roll(),
+            };
+            drop(combatant);
+            (key, x)
+        })
+        .collect();
+    order.sort_by_key(|(key, _)| Reverse(*key));
+    order
+        .into_iter()
+        .map(|((initiative, _, _), x)| (initiative, x))
+        .collect()
+}
+
+/// Initiative count of a combatant that joins a fight in progress. It shares the count of its
+/// side in side initiative and that of its group in group initiative.
+pub(crate) fn arrival_initiative(
+    arrival: &Combatant,
+    allies: &[IntMutCombatant],
+    rules: InitiativeRules,
+) -> i16 {
+    let shared = allies.iter().find(|ally| {
+        let ally = ally.borrow();
+        match rules {
+            InitiativeRules::Individual => false,
+            InitiativeRules::Group => {
+                arrival.initiative_group.is_some()
+                    && ally.initiative_group == arrival.initiative_group
+            }
+            InitiativeRules::Side => true,
+        }
+    });
+    shared.map_or_else(
+        || arrival.roll_initiative(),
+        |ally| ally.borrow().initiative(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        combat::encounter::{CombatantWithRelations, IntMutCombatant},
+        targeting::strategy::tests::init_identical_test_targets,
+    };
+
+    use super::{roll_initiative, InitiativeRules};
+
+    fn with_relations(side: &[IntMutCombatant]) -> Vec<CombatantWithRelations> {
+        side.iter()
+            .map(|c| CombatantWithRelations {
+                combatant: c.clone(),
+                allies: side.to_vec(),
+                enemies: vec![],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn groups_and_sides_act_together() {
+        let players = init_identical_test_targets(3);
+        let enemies = init_identical_test_targets(4);
+        for enemy in &enemies {
+            enemy.borrow_mut().initiative_group = Some(String::from("goblin"));
+        }
+
+        let order = roll_initiative(
+            with_relations(&players),
+            with_relations(&enemies),
+            InitiativeRules::Group,
+        );
+        let goblins: Vec<_> = order
+            .iter()
+            .filter(|(_, x)| x.combatant.borrow().initiative_group.is_some())
+            .map(|(initiative, _)| *initiative)
+            .collect();
+        assert!(goblins.iter().all(|initiative| *initiative == goblins[0]));
+
+        let order = roll_initiative(
+            with_relations(&players),
+            with_relations(&enemies),
+            InitiativeRules::Side,
+        );
+        let sides: Vec<_> = order
+            .iter()
+            .map(|(_, x)| x.combatant.borrow().initiative_group.is_some())
+            .collect();
+        assert!(sides.windows(2).filter(|pair| pair[0] != pair[1]).count() == 1);
+    }
+}
@@ -0,0 +1,135 @@
+use std::{cell::Cell, cell::RefCell, rc::Rc};
+
+use rand::{seq::IteratorRandom, thread_rng};
+
+use crate::{
+    action::action::Action,
+    combat::{
+        action_selection::ActionSelection,
+        encounter::{CombatantWithRelations, IntMutCombatant},
+    },
+    combatant::{combatant::Combatant, config::ActionType, defences::save::SaveModifiers},
+};
+
+/// Lair actions take place on this initiative count, losing initiative ties
+pub(crate) const LAIR_INITIATIVE: i16 = 20;
+
+/// Actions of the lair of the enemies, which belong to the encounter rather than to any combatant
+#[derive(Clone, Debug, Default)]
+pub struct LairCfg {
+    pub actions: Vec<ActionType>,
+}
+
+pub(crate) struct Lair {
+    /// Stand-in for the lair itself, which is not part of either side and can therefore not be targeted
+    pub(crate) relations: CombatantWithRelations,
+    actions: Vec<Rc<dyn Action>>,
+    last_used: Cell<Option<usize>>,
+    /// Position in the initiative order before which the lair acts
+    pub(crate) position: usize,
+}
+
+impl Lair {
+    pub(crate) fn new(
+        cfg: &LairCfg,
+        allies: Vec<IntMutCombatant>,
+        enemies: Vec<IntMutCombatant>,
+        position: usize,
+    ) -> Self {
+        Self {
+            relations: CombatantWithRelations {
+                combatant: stand_in(),
+                allies,
+                enemies,
+            },
+            actions: cfg.actions.iter().map(|x| x.get_action()).collect(),
+            last_used: Cell::new(None),
+            position,
+        }
+    }
+
+    /// Copy of the lair with the same state, acting on behalf of the given side
+    pub(crate) fn clone_with_relations(
+        &self,
+        allies: Vec<IntMutCombatant>,
+        enemies: Vec<IntMutCombatant>,
+    ) -> Self {
+        Self {
+            relations: CombatantWithRelations {
+                combatant: stand_in(),
+                allies,
+                enemies,
+            },
+            actions: self.actions.clone(),
+            last_used: self.last_used.clone(),
+            position: self.position,
+        }
+    }
+
+    /// Whether one of the lair actions can damage the players
+    pub(crate) fn deals_damage(&self) -> bool {
+        self.actions.iter().any(|action| action.deals_damage())
+    }
+
+    /// Takes a random lair action, which may not be the same as the one of the previous round
+    pub(crate) fn take_turn(&self) {
+        let last_used = self.last_used.get();
+        let choice = (0..self.actions.len())
+            .filter(|&idx| Some(idx) != last_used)
+            .choose(&mut thread_rng());
+        if let Some(idx) = choice {
+            let relations = &self.relations;
+            self.actions[idx].execute(&relations.combatant, &relations.allies, &relations.enemies);
+        }
+        self.last_used.set(choice);
+    }
+}
+
+fn stand_in() -> IntMutCombatant {
+    Rc::new(RefCell::new(Combatant::new(
+        0,
+        0,
+        0,
+        SaveModifiers::default(),
+        ActionSelection::default(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::action::action::{Action, MockAction};
+
+    use super::{Lair, LairCfg};
+
+    fn lair_with_actions(actions: Vec<Rc<dyn Action>>) -> Lair {
+        let mut lair = Lair::new(&LairCfg::default(), vec![], vec![], 0);
+        lair.actions = actions;
+        lair
+    }
+
+    fn action_executed(times: usize) -> Rc<dyn Action> {
+        let mut action = MockAction::new();
+        action.expect_execute().times(times).return_const(());
+        Rc::new(action)
+    }
+
+    #[test]
+    fn lair_actions_do_not_repeat_consecutively() {
+        let lair = lair_with_actions(vec![action_executed(2), action_executed(2)]);
+
+        for _ in 0..4 {
+            lair.take_turn();
+        }
+    }
+
+    #[test]
+    fn single_lair_action_is_used_every_other_round() {
+        let lair = lair_with_actions(vec![action_executed(2)]);
+
+        for _ in 0..4 {
+            lair.take_turn();
+        }
+    }
+}
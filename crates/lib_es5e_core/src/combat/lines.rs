@@ -0,0 +1,144 @@
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    action::reaction::provoke_opportunity_attacks,
+    attack::area::Area,
+    targeting::strategy::{Reach, TargetSelectionStrategy},
+};
+
+use super::encounter::{CombatantWithRelations, IntMutCombatant};
+
+/// Zone of a combatant in the front and back line model, a lightweight alternative to a grid.
+/// Combatants in the back line can only be reached by melee attacks once the front line of their
+/// side is down.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Line {
+    #[default]
+    #[serde(rename = "front")]
+    Front,
+    #[serde(rename = "back")]
+    Back,
+}
+
+/// Whether a conscious combatant of the side stands in its front line
+pub(crate) fn front_line_up(side: &[IntMutCombatant]) -> bool {
+    side.iter().any(|c| {
+        let c = c.borrow();
+        c.is_fighting() && c.line() == Line::Front
+    })
+}
+
+/// Whether both lines of the side are held by conscious combatants
+fn both_lines_up(side: &[IntMutCombatant]) -> bool {
+    front_line_up(side)
+        && side.iter().any(|c| {
+            let c = c.borrow();
+            c.is_fighting() && c.line() == Line::Back
+        })
+}
+
+/// Lets a combatant that slips past the enemy front line spend its action on doing so while the
+/// enemy back line is out of its reach. Every conscious enemy in the front line may make an
+/// opportunity attack against it. Returns whether the action was spent.
+pub(crate) fn slip_past_front_line(combatant: &CombatantWithRelations) -> bool {
+    let blocked = {
+        let actor = combatant.combatant.borrow();
+        actor.slips_past_front_line() && !actor.is_past_front_line()
+    };
+    if !blocked || !both_lines_up(&combatant.enemies) {
+        return false;
+    }
+    let front_line: Vec<_> = combatant
+        .enemies
+        .iter()
+        .filter(|e| e.borrow().line() == Line::Front)
+        .cloned()
+        .collect();
+    provoke_opportunity_attacks(&combatant.combatant, &front_line);
+    combatant.combatant.borrow_mut().pass_front_line();
+    true
+}
+
+/// Targets of an area of effect when the enemies hold both of their lines. The area is aimed at
+/// the line of the target the attacker would choose and spills over to each enemy of the other
+/// line by the area's chance.
+pub(crate) fn area_targets(
+    strategy: &dyn TargetSelectionStrategy,
+    area: &Area,
+    enemies: &[IntMutCombatant],
+    reach: Reach,
+) -> Option<Vec<IntMutCombatant>> {
+    if !both_lines_up(enemies) {
+        return None;
+    }
+    let line = strategy
+        .select_single_target(enemies, reach)?
+        .borrow()
+        .line();
+    let (aimed_at, other): (Vec<_>, Vec<_>) = enemies
+        .iter()
+        .filter(|e| e.borrow().is_fighting())
+        .cloned()
+        .partition(|e| e.borrow().line() == line);
+    let nr_targets = area.number_of_targets(aimed_at.len());
+    let mut targets =
+        strategy.select_multiple_targets(&aimed_at, nr_targets, reach.past_front_line());
+    let mut rng = thread_rng();
+    targets.extend(
+        other
+            .into_iter()
+            .filter(|_| area.spill() > 0.0 && rng.gen_bool(area.spill() as f64)),
+    );
+    Some(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        attack::area::{Area, Shape},
+        combat::encounter::CombatantWithRelations,
+        targeting::{
+            strategy::{target_selection_strategy, tests::init_identical_test_targets, Reach},
+            Targeting,
+        },
+    };
+
+    use super::{area_targets, slip_past_front_line, Line};
+
+    #[test]
+    fn areas_stay_in_one_line_without_spill() {
+        let enemies = init_identical_test_targets(6);
+        for enemy in &enemies[..2] {
+            enemy.borrow_mut().line = Line::Back;
+        }
+        let strategy = target_selection_strategy(Targeting::LowestHp);
+        let fireball = Area::new(Shape::Sphere, 20);
+
+        let targets = area_targets(strategy.as_ref(), &fireball, &enemies, Reach::MELEE).unwrap();
+        assert_eq!(targets.len(), 4);
+        assert!(targets.iter().all(|t| t.borrow().line() == Line::Front));
+
+        let spilling = fireball.with_spill(1.0);
+        let targets = area_targets(strategy.as_ref(), &spilling, &enemies, Reach::MELEE).unwrap();
+        assert_eq!(targets.len(), 6);
+    }
+
+    #[test]
+    fn slipping_past_the_front_line_opens_up_the_back_line() {
+        let attackers = init_identical_test_targets(1);
+        let enemies = init_identical_test_targets(2);
+        enemies[1].borrow_mut().line = Line::Back;
+        let attacker = CombatantWithRelations {
+            combatant: attackers[0].clone(),
+            allies: attackers.clone(),
+            enemies: enemies.clone(),
+        };
+        assert!(!slip_past_front_line(&attacker));
+
+        attackers[0].borrow_mut().slip_past = true;
+        assert!(slip_past_front_line(&attacker));
+        assert!(attackers[0].borrow().is_past_front_line());
+        assert!(!slip_past_front_line(&attacker));
+    }
+}
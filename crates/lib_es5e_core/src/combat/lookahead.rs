@@ -0,0 +1,203 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::action::action::Action;
+
+use super::{
+    action_selection::{LookaheadConfig, TurnEconomy},
+    encounter::{
+        all_defeated, perform_action, perform_bonus_action, run_round, run_turns_from, Battle,
+        CombatantWithRelations, IntMutCombatant,
+    },
+};
+
+/// Picks the candidate with the best average outcome over several simulated continuations of the battle.
+/// Ties are resolved in favour of the earlier candidate.
+pub(super) fn select_action(
+    battle: &Battle,
+    idx: usize,
+    candidates: Vec<Rc<dyn Action>>,
+    config: &LookaheadConfig,
+    economy: &TurnEconomy,
+) -> Option<Rc<dyn Action>> {
+    if candidates.len() <= 1 {
+        return candidates.into_iter().next();
+    }
+    let mut best: Option<(Rc<dyn Action>, f32)> = None;
+    for candidate in candidates {
+        let score: f32 = (0..config.rollouts)
+            .map(|_| rollout(battle, idx, candidate.as_ref(), config.rounds, economy))
+            .sum();
+        if best
+            .as_ref()
+            .map_or(true, |(_, best_score)| score > *best_score)
+        {
+            best = Some((candidate, score));
+        }
+    }
+    best.map(|(action, _)| action)
+}
+
+/// Simulates the rest of the turn, in which the economy records what is already used up, and the
+/// rounds after it
+fn rollout(
+    battle: &Battle,
+    idx: usize,
+    action: &dyn Action,
+    rounds: usize,
+    economy: &TurnEconomy,
+) -> f32 {
+    let battle = clone_battle(battle);
+    let actor = &battle.combatants[idx];
+    let mut economy = economy.clone();
+    perform_action(actor, action, &mut economy);
+    perform_bonus_action(actor, &mut economy);
+    run_turns_from(&battle, idx + 1);
+    for _ in 0..rounds {
+        if is_decided(actor) {
+            break;
+        }
+        run_round(&battle);
+    }
+    strength(&actor.allies) - strength(&actor.enemies)
+}
+
+/// Deep copy of the battle state, in which every combatant falls back to the default action selection
+fn clone_battle(battle: &Battle) -> Battle {
+    let clones: Vec<(&IntMutCombatant, IntMutCombatant)> = battle
+        .combatants
+        .iter()
+        .map(|c| {
+            let clone = c.combatant.borrow().with_default_selection();
+            (&c.combatant, Rc::new(RefCell::new(clone)))
+        })
+        .collect();
+    let find_clones = |originals: &[IntMutCombatant]| -> Vec<IntMutCombatant> {
+        originals
+            .iter()
+            .map(|original| {
+                clones
+                    .iter()
+                    .find(|(o, _)| Rc::ptr_eq(o, original))
+                    .map(|(_, clone)| clone.clone())
+                    .expect("related combatants take part in the battle")
+            })
+            .collect()
+    };
+    let combatants = battle
+        .combatants
+        .iter()
+        .zip(clones.iter())
+        .map(|(c, (_, clone))| CombatantWithRelations {
+            combatant: clone.clone(),
+            allies: find_clones(&c.allies),
+            enemies: find_clones(&c.enemies),
+        })
+        .collect();
+    let lair = battle.lair.as_ref().map(|lair| {
+        lair.clone_with_relations(
+            find_clones(&lair.relations.allies),
+            find_clones(&lair.relations.enemies),
+        )
+    });
+    Battle { combatants, lair }
+}
+
+fn is_decided(combatant: &CombatantWithRelations) -> bool {
+    all_defeated(&combatant.allies) || all_defeated(&combatant.enemies)
+}
+
+/// Average strength of a side, where each combatant still fighting counts 1 plus its remaining fraction of HP
+fn strength(side: &[IntMutCombatant]) -> f32 {
+    let total: f32 = side
+        .iter()
+        .map(|c| {
+            let c = c.borrow();
+            if c.is_fighting() {
+                1.0 + c.hp() as f32 / c.max_hp() as f32
+            } else {
+                0.0
+            }
+        })
+        .sum();
+    total / side.len().max(1) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        action::{
+            action::Action,
+            attack::Attack,
+            single::{Execution, SingleAction},
+        },
+        attack::damage::DamageRoll,
+        combat::{
+            action_selection::{ActionSelection, LookaheadConfig, TurnEconomy},
+            encounter::{Battle, CombatantWithRelations},
+        },
+        combatant::{combatant::Combatant, defences::save::SaveModifiers, state::ResourceCosts},
+    };
+
+    use super::select_action;
+
+    fn attack(damage: i16) -> Rc<dyn Action> {
+        Rc::new(SingleAction {
+            resource_cost: ResourceCosts::new(),
+            execution: Execution::Attack(Attack::new(30, DamageRoll::new(vec![], damage))),
+        })
+    }
+
+    #[test]
+    fn lookahead_prefers_more_damaging_action() {
+        let weak = attack(1);
+        let strong = attack(50);
+        let boss = Rc::new(RefCell::new(Combatant::new(
+            100,
+            10,
+            0,
+            SaveModifiers::default(),
+            ActionSelection::default(),
+        )));
+        let hero = Rc::new(RefCell::new(Combatant::new(
+            100,
+            10,
+            0,
+            SaveModifiers::default(),
+            ActionSelection::default(),
+        )));
+        let combatants = vec![
+            CombatantWithRelations {
+                combatant: boss.clone(),
+                allies: vec![boss.clone()],
+                enemies: vec![hero.clone()],
+            },
+            CombatantWithRelations {
+                combatant: hero.clone(),
+                allies: vec![hero.clone()],
+                enemies: vec![boss.clone()],
+            },
+        ];
+        let config = LookaheadConfig {
+            rollouts: 10,
+            rounds: 0,
+        };
+
+        let battle = Battle {
+            combatants,
+            lair: None,
+        };
+
+        let selected = select_action(
+            &battle,
+            0,
+            vec![weak, strong.clone()],
+            &config,
+            &TurnEconomy::default(),
+        );
+
+        assert!(Rc::ptr_eq(&selected.unwrap(), &strong));
+        assert_eq!(hero.borrow().hp(), 100);
+    }
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    action::reaction::provoke_opportunity_attacks,
+    utils::{
+        dice::{beats_dc, Die},
+        rollable::Rollable,
+        save::SaveType,
+    },
+};
+
+use super::{
+    encounter::{CombatantWithRelations, IntMutCombatant},
+    grid::SQUARE,
+};
+
+/// What a combatant does once its morale breaks
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Rout {
+    #[default]
+    #[serde(rename = "flee")]
+    Flee,
+    #[serde(rename = "surrender")]
+    Surrender,
+}
+
+/// When a combatant stops fighting. Its morale is tested once per fight, at the start of the
+/// first of its turns on which one of the conditions holds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Morale {
+    /// Breaks once its hit points are below this fraction of its maximum
+    #[serde(default)]
+    pub hp_below: Option<f32>,
+    /// Breaks once a leader of its side is down
+    #[serde(default)]
+    pub leader_down: bool,
+    /// DC of the Wisdom save that keeps it fighting, without which it breaks right away
+    #[serde(default)]
+    pub save_dc: Option<i16>,
+    #[serde(default)]
+    pub rout: Rout,
+}
+
+/// Tests the morale of the combatant at the start of its turn. A combatant that flees provokes
+/// opportunity attacks from the enemies next to it on a grid. Returns whether the morale broke.
+pub(crate) fn test_morale(combatant: &CombatantWithRelations) -> bool {
+    let morale = {
+        let actor = combatant.combatant.borrow();
+        match actor.morale {
+            Some(morale) if !actor.has_tested_morale() => morale,
+            _ => return false,
+        }
+    };
+    let shaken = {
+        let actor = combatant.combatant.borrow();
+        let bloodied = morale
+            .hp_below
+            .is_some_and(|fraction| (actor.hp() as f32) < fraction * actor.max_hp() as f32);
+        let leader_down = morale.leader_down
+            && combatant.allies.iter().any(|ally| {
+                let ally = ally.borrow();
+                ally.leader && !ally.is_fighting()
+            });
+        bloodied || leader_down
+    };
+    if !shaken {
+        return false;
+    }
+    let holds = morale.save_dc.is_some_and(|dc| {
+        let actor = combatant.combatant.borrow();
+        let save = Die::D20.roll() as i16 + actor.saves().modifier(&SaveType::WIS);
+        beats_dc(save, dc)
+    });
+    combatant.combatant.borrow_mut().record_morale_test();
+    if holds {
+        return false;
+    }
+    if morale.rout == Rout::Flee {
+        provoke_opportunity_attacks(&combatant.combatant, &adjacent_enemies(combatant));
+    }
+    combatant.combatant.borrow_mut().rout(morale.rout);
+    true
+}
+
+fn adjacent_enemies(combatant: &CombatantWithRelations) -> Vec<IntMutCombatant> {
+    let Some(position) = combatant.combatant.borrow().position() else {
+        return vec![];
+    };
+    combatant
+        .enemies
+        .iter()
+        .filter(|enemy| {
+            let enemy = enemy.borrow();
+            enemy.is_fighting()
+                && enemy
+                    .position()
+                    .is_some_and(|p| p.distance(&position) <= SQUARE)
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        combat::encounter::CombatantWithRelations,
+        targeting::strategy::tests::init_identical_test_targets,
+    };
+
+    use super::{test_morale, Morale, Rout};
+
+    #[test]
+    fn morale_breaks_once_the_leader_is_down() {
+        let side = init_identical_test_targets(2);
+        side[0].borrow_mut().leader = true;
+        side[1].borrow_mut().morale = Some(Morale {
+            leader_down: true,
+            rout: Rout::Surrender,
+            ..Default::default()
+        });
+        let follower = CombatantWithRelations {
+            combatant: side[1].clone(),
+            allies: side.clone(),
+            enemies: vec![],
+        };
+        assert!(!test_morale(&follower));
+
+        side[0].borrow_mut().rout(Rout::Flee);
+        assert!(test_morale(&follower));
+        assert_eq!(side[1].borrow().routed(), Some(Rout::Surrender));
+        assert!(!side[1].borrow().is_fighting());
+    }
+}
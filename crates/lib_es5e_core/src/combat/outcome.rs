@@ -0,0 +1,162 @@
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use super::encounter::{all_defeated, Battle, IntMutCombatant};
+
+/// How a fight ends
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    /// All enemies are defeated
+    Victory,
+    /// All players are defeated
+    Defeat,
+    /// The round limit is reached
+    Draw,
+    /// Neither side can damage the other
+    Stalemate,
+    /// The players held out for as many rounds as their objective asks for
+    Survived,
+    /// The enemy the players set out to kill is down
+    TargetKilled,
+    /// The player the party had to keep alive is down
+    VipLost,
+}
+
+impl Outcome {
+    /// Whether the players come out on top
+    pub fn is_win(&self) -> bool {
+        matches!(
+            self,
+            Outcome::Victory | Outcome::Survived | Outcome::TargetKilled
+        )
+    }
+
+    /// Whether the players are beaten. Draws and stalemates are neither won nor lost.
+    pub fn is_loss(&self) -> bool {
+        matches!(self, Outcome::Defeat | Outcome::VipLost)
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Outcome::Victory => "victory",
+            Outcome::Defeat => "defeat",
+            Outcome::Draw => "draw",
+            Outcome::Stalemate => "stalemate",
+            Outcome::Survived => "survived",
+            Outcome::TargetKilled => "target killed",
+            Outcome::VipLost => "VIP lost",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Goal of the players that ends the fight before either side is defeated. Combatants are
+/// referred to by name.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Objective {
+    /// The players win once they hold out for this many rounds
+    #[serde(rename = "survive")]
+    Survive(usize),
+    /// The players win once the enemy of this name is down
+    #[serde(rename = "kill")]
+    Kill(String),
+    /// The players lose once the player of this name is down
+    #[serde(rename = "protect")]
+    Protect(String),
+}
+
+impl Objective {
+    /// Outcome the objective decides after the given round, if any
+    pub(crate) fn outcome(
+        &self,
+        round: usize,
+        players: &[IntMutCombatant],
+        enemies: &[IntMutCombatant],
+    ) -> Option<Outcome> {
+        match self {
+            Objective::Survive(rounds) => (round >= *rounds).then_some(Outcome::Survived),
+            Objective::Kill(name) => is_down(enemies, name).then_some(Outcome::TargetKilled),
+            Objective::Protect(name) => is_down(players, name).then_some(Outcome::VipLost),
+        }
+    }
+}
+
+fn is_down(side: &[IntMutCombatant], name: &str) -> bool {
+    side.iter().any(|c| {
+        let c = c.borrow();
+        c.name() == name && !c.is_conscious()
+    })
+}
+
+/// Outcome of the fight once a round is over, if it ends there. A defeated party loses even if
+/// its objective is met in the same round.
+pub(crate) fn end_of_round(
+    round: usize,
+    players: &[IntMutCombatant],
+    enemies: &[IntMutCombatant],
+    objective: Option<&Objective>,
+) -> Option<Outcome> {
+    if all_defeated(players) {
+        return Some(Outcome::Defeat);
+    }
+    objective
+        .and_then(|objective| objective.outcome(round, players, enemies))
+        .or(all_defeated(enemies).then_some(Outcome::Victory))
+}
+
+/// Whether neither side can ever damage the other, as none of the combatants still fighting nor
+/// the lair has anything that deals damage. Resources that do not recharge during the fight only
+/// count while they last. Healing is not taken into account.
+pub(crate) fn is_stalemate(battle: &Battle) -> bool {
+    battle
+        .lair
+        .as_ref()
+        .map_or(true, |lair| !lair.deals_damage())
+        && battle
+            .combatants
+            .iter()
+            .all(|c| !c.combatant.borrow().deals_damage())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        attack::damage::DamageRoll, targeting::strategy::tests::init_identical_test_targets,
+    };
+
+    use super::{end_of_round, Objective, Outcome};
+
+    #[test]
+    fn objectives_end_the_fight_before_either_side_is_defeated() {
+        let players = init_identical_test_targets(2);
+        let enemies = init_identical_test_targets(2);
+        players[0].borrow_mut().name = String::from("vip");
+        enemies[1].borrow_mut().name = String::from("target");
+        let survive = Objective::Survive(3);
+        let kill = Objective::Kill(String::from("target"));
+        let protect = Objective::Protect(String::from("vip"));
+
+        assert_eq!(end_of_round(2, &players, &enemies, Some(&survive)), None);
+        assert_eq!(
+            end_of_round(3, &players, &enemies, Some(&survive)),
+            Some(Outcome::Survived)
+        );
+        assert_eq!(end_of_round(1, &players, &enemies, Some(&kill)), None);
+
+        let lethal = DamageRoll::new(vec![], 100).calculate_regular();
+        enemies[1].borrow_mut().take_damage(lethal.clone());
+        players[0].borrow_mut().take_damage(lethal);
+        assert_eq!(
+            end_of_round(1, &players, &enemies, Some(&kill)),
+            Some(Outcome::TargetKilled)
+        );
+        assert_eq!(
+            end_of_round(1, &players, &enemies, Some(&protect)),
+            Some(Outcome::VipLost)
+        );
+        assert_eq!(end_of_round(1, &players, &enemies, None), None);
+    }
+}
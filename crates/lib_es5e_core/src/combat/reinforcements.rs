@@ -0,0 +1,134 @@
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::combatant::config::CombatantConfig;
+
+use super::{
+    encounter::{
+        boss, instantiate_for_run, map_to_combatants_with_relations, Battle, EncounterSettings,
+        IntMutCombatant, Side,
+    },
+    initiative::arrival_initiative,
+    lair::LAIR_INITIATIVE,
+};
+
+/// Combatants that join a side of an encounter in progress
+#[derive(Clone, Debug)]
+pub struct Reinforcement {
+    pub side: Side,
+    pub trigger: ArrivalTrigger,
+    pub combatants: Vec<CombatantConfig>,
+}
+
+/// When reinforcements arrive. Triggers are checked at the start of every round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ArrivalTrigger {
+    /// At the start of the given round
+    #[serde(rename = "round")]
+    Round(usize),
+    /// Once the combatant with the most hit points of the side they join is below half of them
+    #[serde(rename = "boss_bloodied")]
+    BossBloodied,
+    /// Once the first combatant of the side they join is down
+    #[serde(rename = "first_down")]
+    FirstDown,
+}
+
+impl ArrivalTrigger {
+    fn fires(&self, round: usize, side: &[IntMutCombatant]) -> bool {
+        match self {
+            ArrivalTrigger::Round(arrival) => round >= *arrival,
+            ArrivalTrigger::BossBloodied => boss(side).is_some_and(|boss| {
+                let boss = boss.borrow();
+                boss.hp() * 2 < boss.max_hp()
+            }),
+            ArrivalTrigger::FirstDown => side.iter().any(|c| !c.borrow().is_conscious()),
+        }
+    }
+}
+
+/// Lets the pending reinforcements whose trigger fires join the battle, removing them from the pending ones
+pub(crate) fn reinforce(
+    battle: &mut Battle,
+    players: &mut Vec<IntMutCombatant>,
+    enemies: &mut Vec<IntMutCombatant>,
+    pending: &mut Vec<&Reinforcement>,
+    round: usize,
+    settings: &EncounterSettings,
+) {
+    let (due, later): (Vec<_>, Vec<_>) = pending.drain(..).partition(|r| {
+        let side = match r.side {
+            Side::Players => &players,
+            Side::Enemies => &enemies,
+        };
+        r.trigger.fires(round, side)
+    });
+    *pending = later;
+    for reinforcement in due {
+        join(battle, players, enemies, reinforcement, settings);
+    }
+}
+
+/// Adds the combatants of the reinforcement to their side and, by their initiative, to the
+/// initiative order. They enter the grid at the starting line of their side.
+fn join(
+    battle: &mut Battle,
+    players: &mut Vec<IntMutCombatant>,
+    enemies: &mut Vec<IntMutCombatant>,
+    reinforcement: &Reinforcement,
+    settings: &EncounterSettings,
+) {
+    let arrivals = instantiate_for_run(&reinforcement.combatants);
+    let (side, shelter) = match reinforcement.side {
+        Side::Players => (&mut *players, settings.shelter.players),
+        Side::Enemies => (&mut *enemies, settings.shelter.enemies),
+    };
+    if let Some(grid) = &settings.grid {
+        grid.place_side(&arrivals, reinforcement.side);
+    }
+    for arrival in &arrivals {
+        let initiative = arrival_initiative(&arrival.borrow(), side, settings.initiative);
+        let mut combatant = arrival.borrow_mut();
+        combatant.take_shelter(shelter);
+        combatant.enter_initiative(initiative);
+        drop(combatant);
+        // later arrivals of the same group share the initiative of this one
+        side.push(arrival.clone());
+    }
+
+    for combatant in &mut battle.combatants {
+        let is_player = players.iter().any(|p| Rc::ptr_eq(p, &combatant.combatant));
+        (combatant.allies, combatant.enemies) = if is_player {
+            (players.clone(), enemies.clone())
+        } else {
+            (enemies.clone(), players.clone())
+        };
+    }
+    let joined = match reinforcement.side {
+        Side::Players => map_to_combatants_with_relations(players, enemies),
+        Side::Enemies => map_to_combatants_with_relations(enemies, players),
+    };
+    let newcomers = joined
+        .into_iter()
+        .filter(|c| arrivals.iter().any(|a| Rc::ptr_eq(a, &c.combatant)));
+    for newcomer in newcomers {
+        let initiative = newcomer.combatant.borrow().initiative();
+        let position = battle
+            .combatants
+            .iter()
+            .position(|c| c.combatant.borrow().initiative() < initiative)
+            .unwrap_or(battle.combatants.len());
+        battle.combatants.insert(position, newcomer);
+    }
+
+    if let Some(lair) = &mut battle.lair {
+        lair.relations.allies = enemies.clone();
+        lair.relations.enemies = players.clone();
+        lair.position = battle
+            .combatants
+            .iter()
+            .filter(|c| c.combatant.borrow().initiative() >= LAIR_INITIATIVE)
+            .count();
+    }
+}
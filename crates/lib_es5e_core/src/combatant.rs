@@ -1,5 +1,10 @@
+#[allow(clippy::module_inception)]
 pub mod combatant;
 pub mod config;
 pub mod defences;
+pub mod hit_dice;
+pub mod legendary;
+pub mod skill;
+pub mod spell_slots;
 pub mod state;
 pub mod stats;
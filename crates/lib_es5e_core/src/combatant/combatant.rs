@@ -1,20 +1,60 @@
-use std::{cmp::min, rc::Rc};
+use std::{cmp::min, collections::BTreeMap, rc::Rc};
 
 use crate::{
-    action::action::Action, attack::damage::Damage, combat::action_selection::ActionSelection,
-    combatant::defences::save::SaveModifiers,
+    action::{
+        action::{Action, ActionSlot},
+        reaction::{Reaction, ReactionEffect, ReactionTrigger},
+    },
+    attack::damage::Damage,
+    combat::{
+        action_selection::{ActionSelection, SelectionMode, TurnEconomy},
+        grid::{Position, DEFAULT_SPEED},
+        lines::Line,
+        morale::{Morale, Rout},
+    },
+    combatant::{
+        defences::{
+            cover::{Obscurement, Shelter},
+            save::SaveModifiers,
+        },
+        skill::Skill,
+    },
+    targeting::{strategy::Reach, Targeting},
+    utils::{
+        dice::{beats_dc, Die, RollMode},
+        rollable::Rollable,
+    },
 };
 
 use super::{
-    state::{CombatantState, ResourceCfgs},
+    hit_dice::HitDicePolicy,
+    legendary::LEGENDARY_RESISTANCE_THRESHOLD,
+    state::{CombatantState, ResourceCfgs, ResourceKey},
     stats::CombatantStats,
 };
 
 #[derive(Clone, Debug)]
 pub struct Combatant {
+    pub(crate) name: String,
     pub stats: CombatantStats,
     state: CombatantState,
     action_selection: ActionSelection,
+    pub(crate) reactions: Vec<Reaction>,
+    pub(crate) targeting: Targeting,
+    /// Square the combatant starts on when fighting on a grid, instead of being placed in line with its side
+    pub(crate) start_position: Option<Position>,
+    pub(crate) line: Line,
+    /// Spends its action on slipping past the enemy front line to reach the enemy back line
+    pub(crate) slip_past: bool,
+    /// Cover and obscurement of the combatant, on top of those its side enjoys in the encounter
+    pub(crate) shelter: Shelter,
+    /// Caught by surprise at the start of every fight, whatever its side
+    pub(crate) starts_surprised: bool,
+    /// Combatants of the same group share their initiative roll in group initiative
+    pub(crate) initiative_group: Option<String>,
+    pub(crate) morale: Option<Morale>,
+    /// Leads its side, which may lose heart once it is down
+    pub(crate) leader: bool,
 }
 
 impl Combatant {
@@ -26,15 +66,38 @@ impl Combatant {
         saves: SaveModifiers,
         resources: ResourceCfgs,
     ) -> Self {
+        let stats = CombatantStats {
+            max_hp,
+            ac,
+            saves,
+            initiative,
+            ..Default::default()
+        };
+        Self::new_with_stats(stats, action_selection, resources)
+    }
+
+    pub fn new_with_stats(
+        stats: CombatantStats,
+        action_selection: ActionSelection,
+        resources: ResourceCfgs,
+    ) -> Self {
+        let mut state = CombatantState::new(stats.max_hp, resources);
+        state.hit_dice = stats.hit_dice.map_or(0, |hit_dice| hit_dice.count);
         Self {
+            name: String::new(),
             action_selection,
-            stats: CombatantStats {
-                max_hp,
-                ac,
-                saves,
-                initiative,
-            },
-            state: CombatantState::new(max_hp, resources),
+            stats,
+            state,
+            reactions: Vec::new(),
+            targeting: Targeting::default(),
+            start_position: None,
+            line: Line::default(),
+            slip_past: false,
+            shelter: Shelter::default(),
+            starts_surprised: false,
+            initiative_group: None,
+            morale: None,
+            leader: false,
         }
     }
 
@@ -48,14 +111,42 @@ impl Combatant {
         Self::new_with_saves_and_resources(max_hp, ac, init, action_selection, saves, ResourceCfgs::new())
     }
 
-    pub fn first_available_action(&self) -> Option<Rc<dyn Action>> {
+    pub fn first_available_action(
+        &self,
+        slot: ActionSlot,
+        economy: &TurnEconomy,
+    ) -> Option<Rc<dyn Action>> {
         self.action_selection
             .actions
             .iter()
-            .find(|x| self.state.can_execute(x.as_ref()))
+            .find(|x| self.is_available(x.as_ref(), slot, economy))
             .cloned()
     }
 
+    pub fn available_actions(&self, slot: ActionSlot, economy: &TurnEconomy) -> Vec<Rc<dyn Action>> {
+        self.action_selection
+            .actions
+            .iter()
+            .filter(|x| self.is_available(x.as_ref(), slot, economy))
+            .cloned()
+            .collect()
+    }
+
+    fn is_available(&self, action: &dyn Action, slot: ActionSlot, economy: &TurnEconomy) -> bool {
+        action.slot() == slot && economy.permits(action) && self.state.can_execute(action)
+    }
+
+    pub fn selection_mode(&self) -> &SelectionMode {
+        &self.action_selection.mode
+    }
+
+    /// Copy of this combatant that always uses the first available action, e.g. for simulated continuations
+    pub(crate) fn with_default_selection(&self) -> Self {
+        let mut combatant = self.clone();
+        combatant.action_selection.mode = SelectionMode::FirstAvailable;
+        combatant
+    }
+
     pub fn update_resources_on_start(&mut self) {
         self.state.recharge_on_turn_start();
     }
@@ -64,16 +155,280 @@ impl Combatant {
         self.state.use_resource(action.resource_costs())
     }
 
+    /// Regains the resources that recharge on a short rest and spends hit dice according to the policy.
+    /// Unconscious combatants cannot spend hit dice.
+    pub fn short_rest(&mut self, policy: HitDicePolicy) {
+        self.state.recharge_on_short_rest();
+        let Some(hit_dice) = self.stats.hit_dice else {
+            return;
+        };
+        while self.is_conscious()
+            && self.state.hit_dice > 0
+            && policy.wants_healing(self.hp(), self.max_hp())
+        {
+            self.state.hit_dice -= 1;
+            let healing = hit_dice.die.roll() as i16 + self.stats.abilities.con();
+            self.state.hp = min(self.max_hp(), self.state.hp + healing.max(0) as u32);
+        }
+    }
+
+    /// Regains all hit points and resources, as well as half of the hit dice
+    pub fn long_rest(&mut self) {
+        self.state.hp = self.stats.max_hp;
+        self.state.recharge_on_long_rest();
+        if let Some(hit_dice) = self.stats.hit_dice {
+            self.state.hit_dice = min(
+                hit_dice.count,
+                self.state.hit_dice + hit_dice.regained_on_long_rest(),
+            );
+        }
+    }
+
+    pub fn remaining_hit_dice(&self) -> u32 {
+        self.state.hit_dice
+    }
+
+    pub fn remaining_resources(&self) -> BTreeMap<ResourceKey, u32> {
+        self.state.remaining_charges()
+    }
+
+    /// Uses up the reaction for this round on the first reaction matching the trigger that is deemed useful
+    pub(crate) fn react(
+        &mut self,
+        trigger: ReactionTrigger,
+        is_useful: impl Fn(&ReactionEffect) -> bool,
+    ) -> Option<ReactionEffect> {
+        if !self.state.reaction_available || !self.is_fighting() {
+            return None;
+        }
+        let reaction = self.reactions.iter().find(|r| {
+            r.trigger == trigger && is_useful(&r.effect) && self.state.can_afford(&r.resource_cost)
+        })?;
+        let (effect, resource_cost) = (reaction.effect.clone(), reaction.resource_cost.clone());
+        self.state.use_resource(&resource_cost);
+        self.state.reaction_available = false;
+        Some(effect)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Initiative count of a fresh initiative roll
+    pub(crate) fn roll_initiative(&self) -> i16 {
+        let mode = if self.stats.initiative_advantage {
+            RollMode::Advantage
+        } else {
+            RollMode::Normal
+        };
+        mode.roll() as i16 + self.stats.initiative
+    }
+
+    /// Initiative count in the current fight
+    pub fn initiative(&self) -> i16 {
+        self.state.initiative
+    }
+
+    pub(crate) fn enter_initiative(&mut self, initiative: i16) {
+        self.state.initiative = initiative;
+    }
+
+    pub(crate) fn surprise(&mut self) {
+        self.state.surprised = true;
+        self.state.reaction_available = false;
+    }
+
+    /// Result of a Dexterity (Stealth) check to hide from the enemies
+    pub(crate) fn stealth_check(&self) -> i16 {
+        Die::D20.roll() as i16 + self.stats.skill_modifier(Skill::Stealth)
+    }
+
+    pub fn passive_perception(&self) -> i16 {
+        10 + self.stats.skill_modifier(Skill::Perception)
+    }
+
+    /// Whether the combatant notices a hiding enemy with the given Stealth check result. Lightly
+    /// obscured enemies impose disadvantage on Perception, which costs 5 points of passive
+    /// Perception, and heavily obscured ones cannot be seen at all.
+    pub(crate) fn notices(&self, hiding: &Combatant, stealth: i16) -> bool {
+        let penalty = match hiding.state.shelter.obscured {
+            None => 0,
+            Some(Obscurement::Lightly) => 5,
+            Some(Obscurement::Heavily) => return false,
+        };
+        !beats_dc(stealth, self.passive_perception() - penalty)
+    }
+
+    /// Ends the surprise at the start of the combatant's first turn, returning whether it was surprised
+    pub(crate) fn end_surprise(&mut self) -> bool {
+        std::mem::take(&mut self.state.surprised)
+    }
+
+    /// Square the combatant stands on, or `None` if the fight does not take place on a grid
+    pub fn position(&self) -> Option<Position> {
+        self.state.position
+    }
+
+    pub(crate) fn move_to(&mut self, position: Position) {
+        self.state.position = Some(position);
+    }
+
+    pub(crate) fn has_tested_morale(&self) -> bool {
+        self.state.morale_tested
+    }
+
+    pub(crate) fn record_morale_test(&mut self) {
+        self.state.morale_tested = true;
+    }
+
+    pub(crate) fn rout(&mut self, rout: Rout) {
+        self.state.routed = Some(rout);
+    }
+
+    /// Whether the combatant fled or surrendered
+    pub fn routed(&self) -> Option<Rout> {
+        self.state.routed
+    }
+
+    /// Takes part in a new fight with its morale restored, e.g. after fleeing an earlier one
+    pub(crate) fn rally(&mut self) {
+        self.state.routed = None;
+        self.state.morale_tested = false;
+    }
+
+    /// Forgets where the combatant stood in a previous fight
+    pub(crate) fn reset_position(&mut self) {
+        self.state.position = None;
+        self.state.past_front_line = false;
+    }
+
+    pub fn line(&self) -> Line {
+        self.line
+    }
+
+    pub(crate) fn slips_past_front_line(&self) -> bool {
+        self.slip_past
+    }
+
+    /// Whether the combatant has slipped past the enemy front line and reaches its back line in melee
+    pub(crate) fn is_past_front_line(&self) -> bool {
+        self.state.past_front_line
+    }
+
+    pub(crate) fn pass_front_line(&mut self) {
+        self.state.past_front_line = true;
+    }
+
+    /// Shelters the combatant for the fight, taking what its own shelter does not declare from its side
+    pub(crate) fn take_shelter(&mut self, side: Shelter) {
+        self.state.shelter = self.shelter.or(side);
+    }
+
+    /// Steps out of total cover, which the combatant cannot act from
+    pub(crate) fn leave_total_cover(&mut self) {
+        if self.state.shelter.in_total_cover() {
+            self.state.shelter.cover = None;
+        }
+    }
+
+    /// Whether total cover keeps actions of the given reach from targeting the combatant
+    pub fn is_shielded_from(&self, reach: Reach) -> bool {
+        self.state.shelter.shields_from(reach)
+    }
+
+    /// Bonus to AC from cover against an attack of the given reach
+    pub fn cover_bonus(&self, reach: Reach) -> i16 {
+        self.state.shelter.cover_bonus(reach)
+    }
+
+    /// Bonus to Dexterity saves from cover
+    pub fn cover_save_bonus(&self) -> i16 {
+        self.state.shelter.save_bonus()
+    }
+
+    /// How attack rolls against the combatant are made
+    pub fn attacked_with(&self) -> RollMode {
+        self.state.shelter.attacked_with()
+    }
+
+    /// Walking speed in feet
+    pub fn speed(&self) -> u32 {
+        self.stats.speed.unwrap_or(DEFAULT_SPEED)
+    }
+
+    pub(crate) fn grant_legendary(&mut self, points: u32, resistances: u32) {
+        self.state.legendary_points = points;
+        self.state.max_legendary_points = points;
+        self.state.legendary_resistances = resistances;
+    }
+
+    /// Spends legendary action points on the most expensive legendary action that is currently affordable
+    pub(crate) fn take_legendary_action(&mut self) -> Option<Rc<dyn Action>> {
+        if !self.is_fighting() || self.state.surprised {
+            return None;
+        }
+        let legendary_action = self
+            .action_selection
+            .legendary
+            .iter()
+            .filter(|x| {
+                x.cost <= self.state.legendary_points && self.state.can_execute(x.action.as_ref())
+            })
+            .max_by_key(|x| x.cost)?;
+        self.state.legendary_points -= legendary_action.cost;
+        Some(legendary_action.action.clone())
+    }
+
+    /// Spends a legendary resistance on a failed save if the prevented damage would knock the combatant out
+    /// or is a significant part of its maximum HP
+    pub(crate) fn resist_failed_save(&mut self, prevented_damage: f32) -> bool {
+        let worth_resisting = prevented_damage >= self.hp() as f32
+            || prevented_damage >= LEGENDARY_RESISTANCE_THRESHOLD * self.max_hp() as f32;
+        if self.state.legendary_resistances == 0 || !worth_resisting {
+            return false;
+        }
+        self.state.legendary_resistances -= 1;
+        true
+    }
+
+    pub(crate) fn raise_ac_until_turn_start(&mut self, bonus: i16) {
+        self.state.ac_bonus += bonus;
+    }
+
     pub fn is_conscious(&self) -> bool {
         self.state.hp > 0
     }
 
+    /// Conscious and neither fled nor surrendered
+    pub fn is_fighting(&self) -> bool {
+        self.is_conscious() && self.state.routed.is_none()
+    }
+
+    /// Whether the combatant is still fighting and has an action, legendary action or reaction that
+    /// can damage its enemies, with the resources it has left or regains during the fight
+    pub(crate) fn deals_damage(&self) -> bool {
+        let selection = &self.action_selection;
+        let usable = |action: &Rc<dyn Action>| {
+            action.deals_damage() && self.state.can_ever_afford(action.resource_costs())
+        };
+        let reaction_deals_damage = |reaction: &Reaction| match &reaction.effect {
+            ReactionEffect::Strike(execution) => {
+                execution.deals_damage() && self.state.can_ever_afford(&reaction.resource_cost)
+            }
+            _ => false,
+        };
+        self.is_fighting()
+            && (selection.actions.iter().any(usable)
+                || selection.legendary.iter().any(|l| usable(&l.action))
+                || self.reactions.iter().any(reaction_deals_damage))
+    }
+
     pub fn take_damage(&mut self, damage: Damage) {
         self.state.hp -= min(damage.amount(), self.state.hp)
     }
 
     pub fn ac(&self) -> i16 {
-        self.stats.ac
+        self.stats.ac + self.state.ac_bonus
     }
 
     pub fn saves(&self) -> &SaveModifiers {
@@ -83,4 +438,8 @@ impl Combatant {
     pub fn hp(&self) -> u32 {
         self.state.hp
     }
+
+    pub fn max_hp(&self) -> u32 {
+        self.stats.max_hp
+    }
 }
@@ -1,14 +1,49 @@
 use std::rc::Rc;
 
-use crate::action::{action::Action, multi::MultiAction, single::SingleAction};
+use crate::{
+    action::{action::Action, multi::MultiAction, reaction::Reaction, single::SingleAction},
+    combat::{
+        action_selection::{ActionSelection, SelectionMode},
+        grid::Position,
+        lines::Line,
+        morale::Morale,
+    },
+    combatant::defences::cover::Shelter,
+    targeting::Targeting,
+};
 
-use super::{combatant::Combatant, state::ResourceCfgs, stats::CombatantStats};
+use super::{
+    combatant::Combatant,
+    legendary::{LegendaryAction, LegendaryCfg},
+    state::ResourceCfgs,
+    stats::CombatantStats,
+};
 
 #[derive(Clone, Debug)]
 pub struct CombatantConfig {
+    pub name: String,
     pub resources: ResourceCfgs,
     pub actions: Vec<ActionType>,
     pub stats: CombatantStats,
+    pub selection_mode: SelectionMode,
+    pub reactions: Vec<Reaction>,
+    pub legendary: LegendaryCfg,
+    pub targeting: Targeting,
+    /// Square to start on when fighting on a grid
+    pub position: Option<Position>,
+    /// Line the combatant fights in, if the fight is not on a grid
+    pub line: Line,
+    /// Spends its action on slipping past the enemy front line to reach the enemy back line in melee
+    pub slip_past: bool,
+    /// Cover and obscurement of the combatant, on top of those of its side
+    pub shelter: Shelter,
+    /// Caught by surprise at the start of the fight, whatever its side
+    pub surprised: bool,
+    /// Combatants of the same group share their initiative roll in group initiative
+    pub initiative_group: Option<String>,
+    pub morale: Option<Morale>,
+    /// Leads its side, which may lose heart once it is down
+    pub leader: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -18,7 +53,7 @@ pub enum ActionType {
 }
 
 impl ActionType {
-    fn get_action(&self) -> Rc<dyn Action> {
+    pub(crate) fn get_action(&self) -> Rc<dyn Action> {
         match self {
             ActionType::SingleAction(action) => Rc::new(action.clone()),
             ActionType::MultiAction(action) => Rc::new(action.clone()),
@@ -28,16 +63,33 @@ impl ActionType {
 
 impl CombatantConfig {
     pub fn to_combatant(&self) -> Combatant {
-        let action_selection = crate::combat::action_selection::ActionSelection {
+        let action_selection = ActionSelection {
             actions: self.actions.iter().map(|x| x.get_action()).collect(),
+            mode: self.selection_mode.clone(),
+            legendary: self
+                .legendary
+                .actions
+                .iter()
+                .map(|x| LegendaryAction {
+                    cost: x.cost,
+                    action: x.action.get_action(),
+                })
+                .collect(),
         };
-        Combatant::new_with_saves_and_resources(
-            self.stats.max_hp,
-            self.stats.ac,
-            self.stats.initiative,
-            action_selection,
-            self.stats.saves.clone(),
-            self.resources.clone(),
-        )
+        let mut combatant =
+            Combatant::new_with_stats(self.stats.clone(), action_selection, self.resources.clone());
+        combatant.reactions = self.reactions.clone();
+        combatant.targeting = self.targeting;
+        combatant.start_position = self.position;
+        combatant.line = self.line;
+        combatant.slip_past = self.slip_past;
+        combatant.shelter = self.shelter;
+        combatant.name = self.name.clone();
+        combatant.starts_surprised = self.surprised;
+        combatant.initiative_group = self.initiative_group.clone();
+        combatant.morale = self.morale;
+        combatant.leader = self.leader;
+        combatant.grant_legendary(self.legendary.points, self.legendary.resistances);
+        combatant
     }
 }
@@ -1,2 +1,3 @@
+pub mod ability;
+pub mod cover;
 pub mod save;
-mod ability;
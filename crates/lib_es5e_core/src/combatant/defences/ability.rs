@@ -1,4 +1,6 @@
-#[derive(Clone, Debug)]
+use crate::utils::save::SaveType;
+
+#[derive(Clone, Debug, Default)]
 pub struct AbilityModifiers {
     str: i16,
     dex: i16,
@@ -20,14 +22,14 @@ impl AbilityModifiers {
         }
     }
 
-    pub fn default() -> Self {
-        AbilityModifiers {
-            str: 0,
-            dex: 0,
-            con: 0,
-            int: 0,
-            wis: 0,
-            cha: 0,
+    pub fn modifier(&self, ability: &SaveType) -> i16 {
+        match ability {
+            SaveType::STR => self.str,
+            SaveType::DEX => self.dex,
+            SaveType::CON => self.con,
+            SaveType::INT => self.int,
+            SaveType::WIS => self.wis,
+            SaveType::CHA => self.cha,
         }
     }
 
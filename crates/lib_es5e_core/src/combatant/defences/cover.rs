@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{targeting::strategy::Reach, utils::dice::RollMode};
+
+/// Obstacle between a combatant and its attackers
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Cover {
+    #[serde(rename = "half")]
+    Half,
+    #[serde(rename = "three_quarters")]
+    ThreeQuarters,
+    /// Cannot be targeted at all
+    #[serde(rename = "total")]
+    Total,
+}
+
+impl Cover {
+    /// Bonus to AC and Dexterity saves
+    pub fn bonus(&self) -> i16 {
+        match self {
+            Cover::Half => 2,
+            Cover::ThreeQuarters | Cover::Total => 5,
+        }
+    }
+}
+
+/// How hard a combatant is to see. Lightly obscured combatants only hinder Perception.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Obscurement {
+    #[serde(rename = "lightly")]
+    Lightly,
+    /// Attacks against the combatant have disadvantage
+    #[serde(rename = "heavily")]
+    Heavily,
+}
+
+/// Cover and obscurement a combatant enjoys against its attackers. Cover hinders melee and ranged
+/// attacks alike and protects against every Dexterity save.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Shelter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover: Option<Cover>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obscured: Option<Obscurement>,
+    /// Melee attackers step around the cover, which then only hinders ranged attacks
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub melee_steps_around: bool,
+}
+
+impl Shelter {
+    /// This shelter, with what it does not declare taken from the other one
+    pub fn or(self, other: Shelter) -> Shelter {
+        Shelter {
+            cover: self.cover.or(other.cover),
+            obscured: self.obscured.or(other.obscured),
+            melee_steps_around: self.melee_steps_around || other.melee_steps_around,
+        }
+    }
+
+    fn hinders(&self, reach: Reach) -> bool {
+        reach.ranged || !self.melee_steps_around
+    }
+
+    /// Bonus to AC against an attack of the given reach
+    pub fn cover_bonus(&self, reach: Reach) -> i16 {
+        match self.cover {
+            Some(cover) if self.hinders(reach) => cover.bonus(),
+            _ => 0,
+        }
+    }
+
+    /// Bonus to Dexterity saves, whatever the reach of the effect
+    pub fn save_bonus(&self) -> i16 {
+        self.cover.map_or(0, |cover| cover.bonus())
+    }
+
+    pub fn in_total_cover(&self) -> bool {
+        self.cover == Some(Cover::Total)
+    }
+
+    /// Whether total cover keeps actions of the given reach from targeting the combatant
+    pub fn shields_from(&self, reach: Reach) -> bool {
+        self.in_total_cover() && self.hinders(reach)
+    }
+
+    /// How attack rolls against the sheltered combatant are made
+    pub fn attacked_with(&self) -> RollMode {
+        match self.obscured {
+            Some(Obscurement::Heavily) => RollMode::Disadvantage,
+            _ => RollMode::Normal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{targeting::strategy::Reach, utils::dice::RollMode};
+
+    use super::{Cover, Obscurement, Shelter};
+
+    #[test]
+    fn cover_hinders_melee_unless_attackers_step_around_it() {
+        let shelter = Shelter {
+            cover: Some(Cover::ThreeQuarters),
+            obscured: Some(Obscurement::Lightly),
+            melee_steps_around: false,
+        };
+        assert_eq!(shelter.cover_bonus(Reach::RANGED), 5);
+        assert_eq!(shelter.cover_bonus(Reach::MELEE), 5);
+        assert_eq!(shelter.attacked_with(), RollMode::Normal);
+
+        let own = Shelter {
+            cover: None,
+            obscured: Some(Obscurement::Heavily),
+            melee_steps_around: true,
+        };
+        let combined = own.or(shelter);
+        assert_eq!(combined.cover, Some(Cover::ThreeQuarters));
+        assert_eq!(combined.attacked_with(), RollMode::Disadvantage);
+        assert_eq!(combined.cover_bonus(Reach::RANGED), 5);
+        assert_eq!(combined.cover_bonus(Reach::MELEE), 0);
+        assert_eq!(combined.save_bonus(), 5);
+    }
+}
@@ -2,7 +2,7 @@ use crate::utils::save::SaveType;
 
 use super::ability::AbilityModifiers;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct SaveModifiers {
     modifiers: AbilityModifiers,
 }
@@ -15,23 +15,10 @@ impl SaveModifiers {
     }
 
     pub fn from_modifiers(modifiers: AbilityModifiers) -> Self {
-        return Self { modifiers };
-    }
-
-    pub fn default() -> Self {
-        Self {
-            modifiers: AbilityModifiers::default(),
-        }
+        Self { modifiers }
     }
 
     pub fn modifier(&self, save_type: &SaveType) -> i16 {
-        match save_type {
-            SaveType::STR => self.modifiers.str(),
-            SaveType::DEX => self.modifiers.dex(),
-            SaveType::CON => self.modifiers.con(),
-            SaveType::INT => self.modifiers.int(),
-            SaveType::WIS => self.modifiers.wis(),
-            SaveType::CHA => self.modifiers.cha(),
-        }
+        self.modifiers.modifier(save_type)
     }
 }
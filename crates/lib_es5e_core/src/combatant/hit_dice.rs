@@ -0,0 +1,123 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{dice::Die, dice_roll::ParseDiceRoll};
+
+/// Dice a character can spend on short rests to regain hit points
+#[derive(Clone, Copy, Debug)]
+pub struct HitDice {
+    pub count: u32,
+    pub die: Die,
+}
+
+impl HitDice {
+    /// Number of hit dice regained on a long rest
+    pub fn regained_on_long_rest(&self) -> u32 {
+        (self.count / 2).max(1)
+    }
+}
+
+impl FromStr for HitDice {
+    type Err = ParseDiceRoll;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalised = s.replace(' ', "").to_lowercase();
+        let (count, sides) = normalised.split_once('d').ok_or(ParseDiceRoll)?;
+        Ok(Self {
+            count: count.parse().map_err(|_| ParseDiceRoll)?,
+            die: sides
+                .parse()
+                .ok()
+                .and_then(Die::from_sides)
+                .ok_or(ParseDiceRoll)?,
+        })
+    }
+}
+
+/// How many hit dice a character spends on a short rest
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum HitDicePolicy {
+    #[serde(rename = "none")]
+    None,
+    /// Spends hit dice until the character has at least the given fraction of its maximum HP
+    #[serde(rename = "heal_to")]
+    HealTo(f32),
+    /// Spends hit dice until the character is fully healed
+    #[default]
+    #[serde(rename = "heal_to_full")]
+    HealToFull,
+}
+
+impl HitDicePolicy {
+    pub(crate) fn wants_healing(&self, hp: u32, max_hp: u32) -> bool {
+        match self {
+            HitDicePolicy::None => false,
+            HitDicePolicy::HealTo(fraction) => (hp as f32) < fraction * max_hp as f32,
+            HitDicePolicy::HealToFull => hp < max_hp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{
+        attack::damage::DamageRoll,
+        combat::action_selection::ActionSelection,
+        combatant::{
+            combatant::Combatant, defences::ability::AbilityModifiers, state::ResourceCfgs,
+            stats::CombatantStats,
+        },
+        utils::dice::Die,
+    };
+
+    use super::{HitDice, HitDicePolicy};
+
+    #[test]
+    fn parse_hit_dice() {
+        let hit_dice = HitDice::from_str("8d10").unwrap();
+
+        assert_eq!(hit_dice.count, 8);
+        assert_eq!(hit_dice.die.sides(), 10);
+        assert_eq!(hit_dice.regained_on_long_rest(), 4);
+        assert!(HitDice::from_str("8d7").is_err());
+    }
+
+    #[test]
+    fn heal_to_fraction_of_max_hp() {
+        let policy = HitDicePolicy::HealTo(0.5);
+
+        assert!(policy.wants_healing(49, 100));
+        assert!(!policy.wants_healing(50, 100));
+        assert!(!HitDicePolicy::None.wants_healing(0, 100));
+        assert!(HitDicePolicy::HealToFull.wants_healing(99, 100));
+    }
+
+    #[test]
+    fn short_rest_spends_hit_dice_until_policy_is_satisfied() {
+        let stats = CombatantStats {
+            max_hp: 100,
+            abilities: AbilityModifiers::new(0, 0, 10, 0, 0, 0),
+            hit_dice: Some(HitDice {
+                count: 3,
+                die: Die::D2,
+            }),
+            ..Default::default()
+        };
+        let mut combatant =
+            Combatant::new_with_stats(stats, ActionSelection::default(), ResourceCfgs::new());
+        combatant.take_damage(DamageRoll::new(vec![], 30).calculate_regular());
+
+        combatant.short_rest(HitDicePolicy::HealTo(0.8));
+
+        assert!(combatant.hp() > 80);
+        assert_eq!(combatant.remaining_hit_dice(), 2);
+
+        combatant.long_rest();
+
+        assert_eq!(combatant.hp(), 100);
+        assert_eq!(combatant.remaining_hit_dice(), 3);
+    }
+}
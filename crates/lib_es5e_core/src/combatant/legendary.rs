@@ -0,0 +1,79 @@
+use std::rc::Rc;
+
+use crate::action::action::Action;
+
+use super::config::ActionType;
+
+/// Legendary actions and resistances of a boss monster
+#[derive(Clone, Debug, Default)]
+pub struct LegendaryCfg {
+    /// Legendary action points, refreshed at the start of the owner's turn
+    pub points: u32,
+    /// Number of failed saves that can be turned into successes during the fight
+    pub resistances: u32,
+    pub actions: Vec<LegendaryActionCfg>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LegendaryActionCfg {
+    pub cost: u32,
+    pub action: ActionType,
+}
+
+#[derive(Clone)]
+pub struct LegendaryAction {
+    pub cost: u32,
+    pub action: Rc<dyn Action>,
+}
+
+/// Minimum fraction of its maximum HP that a legendary resistance has to prevent for it to be used
+pub(crate) const LEGENDARY_RESISTANCE_THRESHOLD: f32 = 0.1;
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::{
+        action::multi::MultiAction,
+        combat::action_selection::ActionSelection,
+        combatant::{combatant::Combatant, defences::save::SaveModifiers},
+    };
+
+    use super::LegendaryAction;
+
+    fn boss(points: u32, resistances: u32, costs: &[u32]) -> Combatant {
+        let action_selection = ActionSelection {
+            legendary: costs
+                .iter()
+                .map(|&cost| LegendaryAction {
+                    cost,
+                    action: Rc::new(MultiAction::new(vec![])),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let mut boss = Combatant::new(100, 10, 0, SaveModifiers::default(), action_selection);
+        boss.grant_legendary(points, resistances);
+        boss
+    }
+
+    #[test]
+    fn legendary_points_are_spent_until_start_of_turn() {
+        let mut boss = boss(3, 0, &[1, 2]);
+
+        assert!(boss.take_legendary_action().is_some());
+        assert!(boss.take_legendary_action().is_some());
+        assert!(boss.take_legendary_action().is_none());
+        boss.update_resources_on_start();
+        assert!(boss.take_legendary_action().is_some());
+    }
+
+    #[test]
+    fn legendary_resistance_is_saved_for_significant_damage() {
+        let mut boss = boss(0, 1, &[]);
+
+        assert!(!boss.resist_failed_save(5.0));
+        assert!(boss.resist_failed_save(20.0));
+        assert!(!boss.resist_failed_save(20.0));
+    }
+}
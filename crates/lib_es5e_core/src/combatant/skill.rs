@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::save::SaveType;
+
+/// Skill of ability checks, e.g. Stealth or Perception
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Skill {
+    Acrobatics,
+    AnimalHandling,
+    Arcana,
+    Athletics,
+    Deception,
+    History,
+    Insight,
+    Intimidation,
+    Investigation,
+    Medicine,
+    Nature,
+    Perception,
+    Performance,
+    Persuasion,
+    Religion,
+    SleightOfHand,
+    Stealth,
+    Survival,
+}
+
+impl Skill {
+    /// Ability the skill is based on
+    pub fn ability(&self) -> SaveType {
+        match self {
+            Skill::Athletics => SaveType::STR,
+            Skill::Acrobatics | Skill::SleightOfHand | Skill::Stealth => SaveType::DEX,
+            Skill::Arcana
+            | Skill::History
+            | Skill::Investigation
+            | Skill::Nature
+            | Skill::Religion => SaveType::INT,
+            Skill::AnimalHandling
+            | Skill::Insight
+            | Skill::Medicine
+            | Skill::Perception
+            | Skill::Survival => SaveType::WIS,
+            Skill::Deception | Skill::Intimidation | Skill::Performance | Skill::Persuasion => {
+                SaveType::CHA
+            }
+        }
+    }
+}
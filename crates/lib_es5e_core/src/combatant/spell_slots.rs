@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use super::state::{Recharge, ResourceCfg, ResourceCfgs, ResourceKey};
+
+pub const MAX_SPELL_LEVEL: u8 = 9;
+
+/// Spell slots a caster can spend on its spells
+#[derive(Clone, Debug, Default)]
+pub struct SpellSlots {
+    /// Number of slots per level, starting with 1st level
+    pub per_level: Vec<u32>,
+    pub pact: Option<PactSlots>,
+    pub preference: SlotPreference,
+}
+
+/// Warlock slots, which are all of the same level
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct PactSlots {
+    pub count: u32,
+    pub level: u8,
+}
+
+/// Which slot a caster spends when several of them could be used for a spell
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SlotPreference {
+    /// Saves the higher slots for later
+    #[default]
+    #[serde(rename = "lowest")]
+    Lowest,
+    /// Deals the most damage now
+    #[serde(rename = "highest")]
+    Highest,
+}
+
+impl SpellSlots {
+    /// Resources tracking the remaining slots
+    pub fn resources(&self) -> ResourceCfgs {
+        let mut resources: ResourceCfgs = self
+            .per_level
+            .iter()
+            .zip(1..=MAX_SPELL_LEVEL)
+            .filter(|(&count, _)| count > 0)
+            .map(|(&count, level)| {
+                let slots = ResourceCfg::new(count, Some(Recharge::LongRest));
+                (ResourceKey::SpellSlot(level), slots)
+            })
+            .collect();
+        if let Some(pact) = self.pact.filter(|pact| pact.count > 0) {
+            resources.insert(
+                ResourceKey::PactSlot,
+                ResourceCfg::new(pact.count, Some(Recharge::ShortRest)),
+            );
+        }
+        resources
+    }
+
+    /// Slots that can be spent on a spell of the given level, together with the level the spell is then cast at,
+    /// in order of preference. Pact slots come before regular slots of the same level.
+    pub fn usable_for(&self, spell_level: u8) -> Vec<(ResourceKey, u8)> {
+        let mut usable = vec![];
+        for level in spell_level.max(1)..=MAX_SPELL_LEVEL {
+            if self
+                .pact
+                .is_some_and(|pact| pact.count > 0 && pact.level == level)
+            {
+                usable.push((ResourceKey::PactSlot, level));
+            }
+            if self
+                .per_level
+                .get(level as usize - 1)
+                .is_some_and(|&count| count > 0)
+            {
+                usable.push((ResourceKey::SpellSlot(level), level));
+            }
+        }
+        if self.preference == SlotPreference::Highest {
+            usable.reverse();
+        }
+        usable
+    }
+}
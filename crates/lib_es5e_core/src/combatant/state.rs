@@ -7,3 +7,4 @@ pub use combatant_state::ResourceCosts;
 pub use resource_config::Recharge;
 pub use resource_config::ResourceCfg;
 pub use resource_config::ResourceCfgs;
+pub use resource_config::ResourceKey;
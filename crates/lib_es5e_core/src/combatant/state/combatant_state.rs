@@ -2,6 +2,8 @@ use std::collections::BTreeMap;
 
 use crate::{
     action::action::Action,
+    combat::{grid::Position, morale::Rout},
+    combatant::defences::cover::Shelter,
     utils::{dice::Die, rollable::Rollable},
 };
 
@@ -11,15 +13,37 @@ use super::{
     ResourceCfgs,
 };
 
-pub type ResourceCosts = BTreeMap<usize, u32>;
+pub type ResourceCosts = BTreeMap<ResourceKey, u32>;
 
 #[derive(Clone, Debug)]
 pub struct CombatantState {
     pub(crate) hp: u32,
+    pub(crate) reaction_available: bool,
+    /// Temporary AC bonus that lasts until the start of the combatant's next turn
+    pub(crate) ac_bonus: i16,
+    pub(crate) legendary_points: u32,
+    pub(crate) max_legendary_points: u32,
+    pub(crate) legendary_resistances: u32,
+    pub(crate) hit_dice: u32,
+    /// Surprised combatants lose their first turn and cannot react before it
+    pub(crate) surprised: bool,
+    /// Square of the grid the combatant stands on, if the fight takes place on one
+    pub(crate) position: Option<Position>,
+    /// Whether the combatant has slipped past the enemy front line
+    pub(crate) past_front_line: bool,
+    /// Cover and obscurement against attackers in the current fight
+    pub(crate) shelter: Shelter,
+    /// Initiative count in the current fight
+    pub(crate) initiative: i16,
+    /// Fled or surrendered combatants no longer take part in the fight
+    pub(crate) routed: Option<Rout>,
+    pub(crate) morale_tested: bool,
     constant_resources: Resources,
     recharge5_resources: Resources,
     recharge6_resources: Resources,
     turn_recharge_resources: Resources,
+    short_rest_resources: Resources,
+    long_rest_resources: Resources,
 }
 
 impl CombatantState {
@@ -28,40 +52,75 @@ impl CombatantState {
         let mut recharge5_resources = Resources::new();
         let mut recharge6_resources = Resources::new();
         let mut turn_recharge_resources = Resources::new();
+        let mut short_rest_resources = Resources::new();
+        let mut long_rest_resources = Resources::new();
         for (name, res_cfg) in resources {
             match res_cfg.recharge {
                 None => &mut constant_resources,
                 Some(Recharge::TurnStart) => &mut turn_recharge_resources, // recharge at start of turn
                 Some(Recharge::Recharge5) => &mut recharge5_resources, // recharge at start of turn on rolling a 5 or 6 on a 1d6
                 Some(Recharge::Recharge6) => &mut recharge6_resources, // recharge at start of turn on rolling a 6 on a d6
+                Some(Recharge::ShortRest) => &mut short_rest_resources,
+                Some(Recharge::LongRest) => &mut long_rest_resources,
             }
             .insert(name, res_cfg.into());
         }
 
         Self {
             hp,
+            reaction_available: true,
+            ac_bonus: 0,
+            legendary_points: 0,
+            max_legendary_points: 0,
+            legendary_resistances: 0,
+            hit_dice: 0,
+            surprised: false,
+            position: None,
+            past_front_line: false,
+            shelter: Shelter::default(),
+            initiative: 0,
+            routed: None,
+            morale_tested: false,
             constant_resources,
             recharge5_resources,
             recharge6_resources,
             turn_recharge_resources,
+            short_rest_resources,
+            long_rest_resources,
         }
     }
 
-    pub fn use_resource(&mut self, resource_cost: &ResourceCosts) {
-        resource_cost.iter().for_each(|(&key, &val)| {
-            self.find_resource_and_reduce(key, val);
-        });
+    fn resource_pools(&self) -> [&Resources; 6] {
+        [
+            &self.constant_resources,
+            &self.recharge5_resources,
+            &self.recharge6_resources,
+            &self.turn_recharge_resources,
+            &self.short_rest_resources,
+            &self.long_rest_resources,
+        ]
     }
 
-    fn find_resource_and_reduce(&mut self, key: ResourceKey, cost: u32) {
-        let mut resource_types = [
+    fn resource_pools_mut(&mut self) -> [&mut Resources; 6] {
+        [
             &mut self.constant_resources,
             &mut self.recharge5_resources,
             &mut self.recharge6_resources,
             &mut self.turn_recharge_resources,
-        ];
-        resource_types.iter_mut().any(|resources| {
-            if let Some(resource) = resources.get_mut(&key) {
+            &mut self.short_rest_resources,
+            &mut self.long_rest_resources,
+        ]
+    }
+
+    pub fn use_resource(&mut self, resource_cost: &ResourceCosts) {
+        resource_cost.iter().for_each(|(key, &val)| {
+            self.find_resource_and_reduce(key, val);
+        });
+    }
+
+    fn find_resource_and_reduce(&mut self, key: &ResourceKey, cost: u32) {
+        self.resource_pools_mut().iter_mut().any(|resources| {
+            if let Some(resource) = resources.get_mut(key) {
                 if resource.charges >= cost {
                     resource.charges -= cost;
                 } else {
@@ -84,20 +143,64 @@ impl CombatantState {
             reset_charge_to_max(&mut self.recharge6_resources);
         }
         reset_charge_to_max(&mut self.turn_recharge_resources);
+        self.reaction_available = true;
+        self.ac_bonus = 0;
+        self.legendary_points = self.max_legendary_points;
+    }
+
+    pub fn recharge_on_short_rest(&mut self) {
+        reset_charge_to_max(&mut self.short_rest_resources);
+    }
+
+    /// Resets every resource, as a long rest ends the adventuring day
+    pub fn recharge_on_long_rest(&mut self) {
+        self.resource_pools_mut()
+            .into_iter()
+            .for_each(reset_charge_to_max);
+    }
+
+    /// Remaining charges of all resources
+    pub fn remaining_charges(&self) -> BTreeMap<ResourceKey, u32> {
+        self.resource_pools()
+            .into_iter()
+            .flatten()
+            .map(|(key, resource)| (key.clone(), resource.charges))
+            .collect()
     }
 
     pub fn can_execute(&self, x: &dyn Action) -> bool {
-        let resource_types = [
-            &self.constant_resources,
+        self.can_afford(x.resource_costs())
+    }
+
+    /// Whether the costs can be paid now or once the resources recharge during the fight
+    pub fn can_ever_afford(&self, resource_costs: &ResourceCosts) -> bool {
+        let recharging = [
             &self.recharge5_resources,
             &self.recharge6_resources,
             &self.turn_recharge_resources,
         ];
-        !x.resource_costs().iter().any(|(key, &cost)| {
-            resource_types.iter().any(|resources| {
+        resource_costs.iter().all(|(key, &cost)| {
+            let charged = |resources: &&Resources| {
+                resources
+                    .get(key)
+                    .is_some_and(|resource| cost <= resource.charges)
+            };
+            let recharges = |resources: &&Resources| {
                 resources
                     .get(key)
-                    .is_some_and(|resource| cost > resource.charges)
+                    .is_some_and(|resource| cost <= resource.max_charges)
+            };
+            self.resource_pools().iter().any(charged) || recharging.iter().any(recharges)
+        })
+    }
+
+    pub fn can_afford(&self, resource_costs: &ResourceCosts) -> bool {
+        let resource_pools = self.resource_pools();
+        resource_costs.iter().all(|(key, &cost)| {
+            resource_pools.iter().any(|resources| {
+                resources
+                    .get(key)
+                    .is_some_and(|resource| cost <= resource.charges)
             })
         })
     }
@@ -115,11 +218,11 @@ mod tests {
     fn test_use_resource() {
         let mut resource_cfgs = ResourceCfgs::new();
 
-        resource_cfgs.insert(0, ResourceCfg::new(5, None));
+        resource_cfgs.insert(ResourceKey::Special(0), ResourceCfg::new(5, None));
         let mut combatant = CombatantState::new(10, resource_cfgs);
 
         let mut resource_cost = ResourceCosts::new();
-        resource_cost.insert(0, 3);
+        resource_cost.insert(ResourceKey::Special(0), 3);
 
         let mut mock_action = MockAction::new();
         mock_action
@@ -135,11 +238,14 @@ mod tests {
     fn test_recharge_on_turn_start() {
         let mut resource_cfgs = ResourceCfgs::new();
 
-        resource_cfgs.insert(1, ResourceCfg::new(1, Some(Recharge::TurnStart)));
+        resource_cfgs.insert(
+            ResourceKey::Special(1),
+            ResourceCfg::new(1, Some(Recharge::TurnStart)),
+        );
         let mut combatant = CombatantState::new(10, resource_cfgs);
 
         let mut resource_cost = ResourceCosts::new();
-        resource_cost.insert(1, 1);
+        resource_cost.insert(ResourceKey::Special(1), 1);
 
         let mut mock_action = MockAction::new();
         mock_action
@@ -156,11 +262,14 @@ mod tests {
     fn test_recharge_on_turn_without_overcharging() {
         let mut resource_cfgs = ResourceCfgs::new();
 
-        resource_cfgs.insert(1, ResourceCfg::new(1, Some(Recharge::TurnStart)));
+        resource_cfgs.insert(
+            ResourceKey::Special(1),
+            ResourceCfg::new(1, Some(Recharge::TurnStart)),
+        );
         let mut combatant = CombatantState::new(10, resource_cfgs);
 
         let mut resource_cost = ResourceCosts::new();
-        resource_cost.insert(1, 1);
+        resource_cost.insert(ResourceKey::Special(1), 1);
 
         let mut mock_action = MockAction::new();
         mock_action
@@ -177,11 +286,11 @@ mod tests {
     fn test_using_unavailable_resource() {
         let mut resource_cfgs = ResourceCfgs::new();
 
-        resource_cfgs.insert(1, ResourceCfg::new(1, None));
+        resource_cfgs.insert(ResourceKey::Special(1), ResourceCfg::new(1, None));
         let mut combatant = CombatantState::new(10, resource_cfgs);
 
         let mut resource_cost = ResourceCosts::new();
-        resource_cost.insert(1, 2);
+        resource_cost.insert(ResourceKey::Special(1), 2);
 
         let mut mock_action = MockAction::new();
         mock_action
@@ -193,11 +302,29 @@ mod tests {
         combatant.use_resource(&resource_cost);
     }
 
+    #[test]
+    fn test_shared_named_resource() {
+        let ki = ResourceKey::Named("ki".to_string());
+        let mut resource_cfgs = ResourceCfgs::new();
+        resource_cfgs.insert(ki.clone(), ResourceCfg::new(3, Some(Recharge::ShortRest)));
+        let mut combatant = CombatantState::new(10, resource_cfgs);
+
+        let flurry = ResourceCosts::from([(ki.clone(), 1)]);
+        let stunning_strike = ResourceCosts::from([(ki, 2)]);
+
+        combatant.use_resource(&flurry);
+        assert!(combatant.can_afford(&stunning_strike));
+        combatant.use_resource(&stunning_strike);
+        combatant.recharge_on_turn_start();
+        assert!(!combatant.can_afford(&flurry));
+    }
+
     mock! {
         Action {}
         impl Action for Action {
-            fn execute(&self, _allies: &[IntMutCombatant], enemies: &[IntMutCombatant]);
+            fn execute(&self, actor: &IntMutCombatant, allies: &[IntMutCombatant], enemies: &[IntMutCombatant]);
             fn resource_costs(&self) -> &ResourceCosts;
+            fn deals_damage(&self) -> bool;
         }
         impl Clone for Action {
             fn clone(&self) -> Self;
@@ -1,8 +1,29 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, fmt};
 
 pub type ResourceCfgs = BTreeMap<ResourceKey, ResourceCfg>;
 
-pub type ResourceKey = usize;
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ResourceKey {
+    /// Resource declared by the combatant, which can be shared by several actions, e.g. ki points
+    Named(String),
+    /// Charges of the special action at the given position in the combatant's list
+    Special(usize),
+    /// Spell slots of the given level
+    SpellSlot(u8),
+    /// Warlock pact magic slots
+    PactSlot,
+}
+
+impl fmt::Display for ResourceKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceKey::Named(name) => write!(f, "{name}"),
+            ResourceKey::Special(idx) => write!(f, "special action {idx}"),
+            ResourceKey::SpellSlot(level) => write!(f, "level {level} spell slots"),
+            ResourceKey::PactSlot => write!(f, "pact slots"),
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct ResourceCfg {
@@ -30,4 +51,6 @@ pub enum Recharge {
     TurnStart, // recharge at start of turn
     Recharge5, // recharge at start of turn on rolling a 5 or 6 on a 1d6
     Recharge6, // recharge at start of turn on rolling a 6 on a d6
+    ShortRest, // recharge on a short or long rest
+    LongRest,  // recharge on a long rest
 }
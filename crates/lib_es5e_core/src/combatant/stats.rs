@@ -1,9 +1,33 @@
-use super::defences::save::SaveModifiers;
+use std::collections::BTreeMap;
 
-#[derive(Clone, Debug)]
+use super::{
+    defences::{ability::AbilityModifiers, save::SaveModifiers},
+    hit_dice::HitDice,
+    skill::Skill,
+};
+
+#[derive(Clone, Debug, Default)]
 pub struct CombatantStats {
     pub max_hp: u32,
     pub ac: i16,
     pub initiative: i16,
+    /// Rolls initiative with advantage, e.g. due to Feral Instinct
+    pub initiative_advantage: bool,
     pub saves: SaveModifiers,
+    pub abilities: AbilityModifiers,
+    pub hit_dice: Option<HitDice>,
+    /// Modifiers of the skills that differ from the ability modifier, e.g. due to proficiency
+    pub skills: BTreeMap<Skill, i16>,
+    /// Walking speed in feet, 30 if not given
+    pub speed: Option<u32>,
+}
+
+impl CombatantStats {
+    /// Modifier of ability checks of the skill
+    pub fn skill_modifier(&self, skill: Skill) -> i16 {
+        self.skills
+            .get(&skill)
+            .copied()
+            .unwrap_or_else(|| self.abilities.modifier(&skill.ability()))
+    }
 }
@@ -1,16 +1,43 @@
-use std::time::Instant;
+use std::{collections::BTreeMap, time::Instant};
+
+use crate::{combat::outcome::Outcome, combatant::state::ResourceKey};
 
 pub trait Statistics {
     fn record_round(&mut self);
     fn record_win(&mut self, nr_survivors: usize);
+    /// Called at the start of each fight with the names of the combatants in initiative order
+    fn record_initiative(&mut self, _order: &[String], _boss: &str) {}
+    /// Called at the end of each fight
+    fn record_enemy_fates(&mut self, _fates: &EnemyFates) {}
+    /// Called at the end of each fight
+    fn record_outcome(&mut self, _outcome: Outcome) {}
     fn print(&self, nr_repetitions: usize);
 }
 
+/// How many enemies were downed, fled or surrendered in a fight
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnemyFates {
+    pub killed: usize,
+    pub fled: usize,
+    pub surrendered: usize,
+}
+
 pub struct BaseStatistics {
     start: Instant,
     players_win_count: usize,
     nr_rounds_sum: usize,
     nr_survivors_sum: usize,
+    boss: String,
+    /// Number of fights in which each combatant acted before the boss
+    acted_before_boss: BTreeMap<String, usize>,
+    enemy_fates_sum: EnemyFates,
+    outcome_counts: BTreeMap<Outcome, usize>,
+}
+
+impl Default for BaseStatistics {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BaseStatistics {
@@ -20,6 +47,10 @@ impl BaseStatistics {
             players_win_count: 0,
             nr_rounds_sum: 0,
             nr_survivors_sum: 0,
+            boss: String::new(),
+            acted_before_boss: BTreeMap::new(),
+            enemy_fates_sum: EnemyFates::default(),
+            outcome_counts: BTreeMap::new(),
         }
     }
 }
@@ -34,6 +65,28 @@ impl Statistics for BaseStatistics {
         self.nr_survivors_sum += nr_survivors;
     }
 
+    fn record_initiative(&mut self, order: &[String], boss: &str) {
+        self.boss = boss.to_string();
+        let mut before_boss = true;
+        for name in order {
+            before_boss &= name != boss;
+            let count = self.acted_before_boss.entry(name.clone()).or_default();
+            if before_boss {
+                *count += 1;
+            }
+        }
+    }
+
+    fn record_enemy_fates(&mut self, fates: &EnemyFates) {
+        self.enemy_fates_sum.killed += fates.killed;
+        self.enemy_fates_sum.fled += fates.fled;
+        self.enemy_fates_sum.surrendered += fates.surrendered;
+    }
+
+    fn record_outcome(&mut self, outcome: Outcome) {
+        *self.outcome_counts.entry(outcome).or_default() += 1;
+    }
+
     fn print(&self, repetitions: usize) {
         println!(
             "Players win {} % of the time",
@@ -49,6 +102,145 @@ impl Statistics for BaseStatistics {
                 self.nr_survivors_sum as f32 / self.players_win_count as f32
             );
         }
+        let average = |sum: usize| sum as f32 / repetitions as f32;
+        println!(
+            "Average number of enemies killed: {}, fled: {}, surrendered: {}",
+            average(self.enemy_fates_sum.killed),
+            average(self.enemy_fates_sum.fled),
+            average(self.enemy_fates_sum.surrendered)
+        );
+        // victories and defeats alone are already told by the win rate
+        if self
+            .outcome_counts
+            .keys()
+            .any(|outcome| !matches!(outcome, Outcome::Victory | Outcome::Defeat))
+        {
+            println!("Outcomes:");
+            for (outcome, count) in &self.outcome_counts {
+                let share = *count as f32 / repetitions as f32 * 100.0;
+                println!("  {outcome}: {share} % of the time");
+            }
+        }
+        if !self.acted_before_boss.is_empty() {
+            println!("Acting before {}:", self.boss);
+            for (name, count) in &self.acted_before_boss {
+                if *name != self.boss {
+                    let share = *count as f32 / repetitions as f32 * 100.0;
+                    println!("  {name}: {share} % of the time");
+                }
+            }
+        }
+        println!("Program duration: {:.2?}", self.start.elapsed());
+    }
+}
+
+/// Remaining strength of the party after a fight
+#[derive(Clone, Debug, Default)]
+pub struct PartySnapshot {
+    pub nr_conscious: usize,
+    pub hp: u32,
+    pub max_hp: u32,
+    pub hit_dice: u32,
+    /// Remaining charges per resource, summed over the party
+    pub resources: BTreeMap<ResourceKey, u32>,
+}
+
+impl PartySnapshot {
+    fn add(&mut self, other: &PartySnapshot) {
+        self.nr_conscious += other.nr_conscious;
+        self.hp += other.hp;
+        self.max_hp += other.max_hp;
+        self.hit_dice += other.hit_dice;
+        for (key, charges) in &other.resources {
+            *self.resources.entry(key.clone()).or_default() += charges;
+        }
+    }
+}
+
+/// Statistics of whole adventuring days, which also record every single fight of the day
+pub trait DayStatistics: Statistics {
+    /// Called after each won fight, before the party rests
+    fn record_fight_won(&mut self, encounter: usize, party: &PartySnapshot);
+    fn record_day_survived(&mut self);
+}
+
+pub struct BaseDayStatistics {
+    start: Instant,
+    days_survived: usize,
+    nr_rounds_sum: usize,
+    /// Number of wins and summed up party state after each encounter of the day
+    encounters: Vec<(usize, PartySnapshot)>,
+}
+
+impl Default for BaseDayStatistics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BaseDayStatistics {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            days_survived: 0,
+            nr_rounds_sum: 0,
+            encounters: Vec::new(),
+        }
+    }
+}
+
+impl Statistics for BaseDayStatistics {
+    fn record_round(&mut self) {
+        self.nr_rounds_sum += 1;
+    }
+
+    fn record_win(&mut self, _nr_survivors: usize) {}
+
+    fn print(&self, repetitions: usize) {
+        println!(
+            "Party survives the whole day {} % of the time",
+            self.days_survived as f32 / repetitions as f32 * 100.0
+        );
+        println!(
+            "Average number of rounds per day: {}",
+            self.nr_rounds_sum as f32 / repetitions as f32
+        );
+        for (idx, (wins, party)) in self.encounters.iter().enumerate() {
+            println!(
+                "Encounter {}: won {} % of the time",
+                idx + 1,
+                *wins as f32 / repetitions as f32 * 100.0
+            );
+            if *wins == 0 {
+                continue;
+            }
+            let average = |sum: u32| sum as f32 / *wins as f32;
+            println!(
+                "  afterwards on average {} conscious, {} / {} HP",
+                party.nr_conscious as f32 / *wins as f32,
+                average(party.hp),
+                average(party.max_hp)
+            );
+            println!("  hit dice: {}", average(party.hit_dice));
+            for (key, charges) in &party.resources {
+                println!("  {key}: {}", average(*charges));
+            }
+        }
         println!("Program duration: {:.2?}", self.start.elapsed());
     }
 }
+
+impl DayStatistics for BaseDayStatistics {
+    fn record_fight_won(&mut self, encounter: usize, party: &PartySnapshot) {
+        if self.encounters.len() <= encounter {
+            self.encounters.resize_with(encounter + 1, Default::default);
+        }
+        let (wins, sum) = &mut self.encounters[encounter];
+        *wins += 1;
+        sum.add(party);
+    }
+
+    fn record_day_survived(&mut self) {
+        self.days_survived += 1;
+    }
+}
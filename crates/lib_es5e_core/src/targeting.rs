@@ -1,3 +1,5 @@
 mod random;
 pub(crate) mod strategy;
 mod weakest;
+
+pub use strategy::Targeting;
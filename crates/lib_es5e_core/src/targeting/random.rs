@@ -5,7 +5,7 @@ use rand::{
 
 use crate::combat::encounter::IntMutCombatant;
 
-use super::strategy::{get_viable_indices, TargetSelectionStrategy};
+use super::strategy::{get_viable_indices, Reach, TargetSelectionStrategy};
 
 pub(super) struct TargetRandomStrategy;
 
@@ -13,8 +13,9 @@ impl TargetSelectionStrategy for TargetRandomStrategy {
     fn select_single_target(
         &self,
         targets: &[IntMutCombatant],
+        reach: Reach,
     ) -> Option<IntMutCombatant> {
-        let viable_indices = get_viable_indices(targets);
+        let viable_indices = get_viable_indices(targets, reach);
         viable_indices
             .iter()
             .choose(&mut thread_rng())
@@ -25,8 +26,9 @@ impl TargetSelectionStrategy for TargetRandomStrategy {
         &self,
         targets: &[IntMutCombatant],
         max_targets: usize,
+        reach: Reach,
     ) -> Vec<IntMutCombatant> {
-        let viable_indices: Vec<_> = get_viable_indices(targets);
+        let viable_indices: Vec<_> = get_viable_indices(targets, reach);
         let selected: Vec<_> = viable_indices
             .choose_multiple(&mut thread_rng(), max_targets)
             .copied()
@@ -34,7 +36,8 @@ impl TargetSelectionStrategy for TargetRandomStrategy {
         targets
             .iter()
             .enumerate()
-            .filter_map(move |(i, p)| selected.contains(&i).then(|| p))
+            .filter(|(i, _)| selected.contains(i))
+            .map(|(_, p)| p)
             .cloned()
             .collect()
     }
@@ -1,33 +1,108 @@
-use crate::combat::encounter::IntMutCombatant;
+use serde::{Deserialize, Serialize};
+
+use crate::combat::{
+    encounter::IntMutCombatant,
+    lines::{front_line_up, Line},
+};
+
+use super::{
+    random::TargetRandomStrategy,
+    weakest::{AcAspect, HpAspect, TargetWeakestStrategy},
+};
+
+/// Which of its enemies a combatant prefers to target
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Targeting {
+    #[default]
+    #[serde(rename = "random")]
+    Random,
+    #[serde(rename = "lowest_hp")]
+    LowestHp,
+    #[serde(rename = "lowest_ac")]
+    LowestAc,
+}
+
+/// Farthest reach of melee attacks, e.g. of a dragon's tail. Actions with a longer range are ranged.
+pub const MELEE_REACH: u32 = 15;
+
+/// Which of the enemies an action can reach
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reach {
+    /// Ranged actions are hindered by cover even where melee attackers step around it
+    pub ranged: bool,
+    /// Whether the back line of the enemies can be reached while their front line is up
+    pub back_line: bool,
+}
+
+impl Reach {
+    pub const MELEE: Self = Self {
+        ranged: false,
+        back_line: false,
+    };
+    pub const RANGED: Self = Self {
+        ranged: true,
+        back_line: true,
+    };
+
+    /// Reach of an action with the given range in feet
+    pub fn of(range: u32) -> Self {
+        if range <= MELEE_REACH {
+            Self::MELEE
+        } else {
+            Self::RANGED
+        }
+    }
 
-use super::random::TargetRandomStrategy;
+    /// The same reach, extended to the back line of the enemies
+    pub fn past_front_line(self) -> Self {
+        Self {
+            back_line: true,
+            ..self
+        }
+    }
+}
 
 pub trait TargetSelectionStrategy {
     fn select_single_target(
         &self,
         targets: &[IntMutCombatant],
+        reach: Reach,
     ) -> Option<IntMutCombatant>;
     fn select_multiple_targets(
         &self,
         targets: &[IntMutCombatant],
         max_targets: usize,
+        reach: Reach,
     ) -> Vec<IntMutCombatant>;
 }
 
-pub fn target_selection_strategy() -> Box<dyn TargetSelectionStrategy> {
-    Box::new(TargetRandomStrategy)
+pub fn target_selection_strategy(targeting: Targeting) -> Box<dyn TargetSelectionStrategy> {
+    match targeting {
+        Targeting::Random => Box::new(TargetRandomStrategy),
+        Targeting::LowestHp => Box::new(TargetWeakestStrategy { aspect: HpAspect }),
+        Targeting::LowestAc => Box::new(TargetWeakestStrategy { aspect: AcAspect }),
+    }
 }
 
-pub(super) fn get_viable_indices(targets: &[IntMutCombatant]) -> Vec<usize> {
+/// Indices of the conscious targets within reach: the back line is out of reach of melee actions
+/// while the front line is up, and targets in total cover cannot be reached
+pub(super) fn get_viable_indices(targets: &[IntMutCombatant], reach: Reach) -> Vec<usize> {
+    let back_line_covered = !reach.back_line && front_line_up(targets);
     targets
         .iter()
         .enumerate()
-        .filter_map(|(i, p)| p.borrow().is_conscious().then(|| i))
+        .filter(|(_, p)| {
+            let p = p.borrow();
+            p.is_fighting()
+                && !(back_line_covered && p.line() == Line::Back)
+                && !p.is_shielded_from(reach)
+        })
+        .map(|(i, _)| i)
         .collect()
 }
 
 #[cfg(test)]
-pub(super) mod tests {
+pub(crate) mod tests {
     use std::cell::RefCell;
     use std::rc::Rc;
 
@@ -35,11 +110,13 @@ pub(super) mod tests {
     use crate::combat::encounter::IntMutCombatant;
     use crate::combatant::combatant::Combatant;
     use crate::combatant::defences::save::SaveModifiers;
-    use crate::targeting::strategy::{get_viable_indices, TargetSelectionStrategy};
+    use crate::attack::damage::DamageRoll;
+    use crate::combat::lines::Line;
+    use crate::combatant::defences::cover::{Cover, Shelter};
+    use crate::targeting::strategy::{get_viable_indices, Reach, TargetSelectionStrategy};
 
     pub fn init_identical_test_targets(nr_targets: usize) -> Vec<IntMutCombatant> {
         (0..nr_targets)
-            .into_iter()
             .map(|_| {
                 Rc::new(RefCell::new(Combatant::new(
                     100,
@@ -57,7 +134,7 @@ pub(super) mod tests {
         let targets = init_identical_test_targets(nr_targets);
 
         for to_select in 0..nr_targets + 1 {
-            let selected = target_strategy.select_multiple_targets(&targets, to_select);
+            let selected = target_strategy.select_multiple_targets(&targets, to_select, Reach::MELEE);
 
             assert_eq!(to_select, selected.len())
         }
@@ -68,7 +145,8 @@ pub(super) mod tests {
         let max_targets = 11;
         let targets = init_identical_test_targets(nr_targets);
 
-        let selected = target_strategy.select_multiple_targets(&targets, max_targets as usize);
+        let selected =
+            target_strategy.select_multiple_targets(&targets, max_targets as usize, Reach::RANGED);
 
         assert_eq!(nr_targets, selected.len())
     }
@@ -80,7 +158,6 @@ pub(super) mod tests {
         assert!(nr_conscious < nr_targets);
 
         let targets: Vec<_> = (0..nr_targets)
-            .into_iter()
             .map(|i| {
                 Rc::new(RefCell::new(Combatant::new(
                     if i < nr_conscious { 1 } else { 0 },
@@ -92,8 +169,43 @@ pub(super) mod tests {
             })
             .collect();
 
-        let viable_indices = get_viable_indices(&targets);
+        let viable_indices = get_viable_indices(&targets, Reach::RANGED);
         assert_eq!(nr_conscious, viable_indices.len());
         assert!(viable_indices.iter().all(|&x| x < nr_conscious));
     }
+
+    #[test]
+    fn melee_reaches_the_back_line_once_the_front_line_is_down() {
+        let targets = init_identical_test_targets(3);
+        targets[2].borrow_mut().line = Line::Back;
+
+        assert_eq!(get_viable_indices(&targets, Reach::MELEE), vec![0, 1]);
+        assert_eq!(get_viable_indices(&targets, Reach::RANGED), vec![0, 1, 2]);
+
+        for target in &targets[..2] {
+            target.borrow_mut().take_damage(DamageRoll::new(vec![], 100).calculate_regular());
+        }
+        assert_eq!(get_viable_indices(&targets, Reach::MELEE), vec![2]);
+    }
+
+    #[test]
+    fn actions_cannot_reach_total_cover() {
+        let targets = init_identical_test_targets(2);
+        let total_cover = Shelter {
+            cover: Some(Cover::Total),
+            obscured: None,
+            melee_steps_around: false,
+        };
+        targets[0].borrow_mut().take_shelter(total_cover);
+
+        assert_eq!(get_viable_indices(&targets, Reach::RANGED), vec![1]);
+        assert_eq!(get_viable_indices(&targets, Reach::MELEE), vec![1]);
+
+        targets[1].borrow_mut().take_shelter(Shelter {
+            melee_steps_around: true,
+            ..total_cover
+        });
+        assert_eq!(get_viable_indices(&targets, Reach::RANGED), Vec::<usize>::new());
+        assert_eq!(get_viable_indices(&targets, Reach::MELEE), vec![1]);
+    }
 }
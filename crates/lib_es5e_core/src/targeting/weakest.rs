@@ -7,7 +7,7 @@ use crate::combat::encounter::IntMutCombatant;
 
 use crate::combatant::combatant::Combatant;
 
-use super::strategy::TargetSelectionStrategy;
+use super::strategy::{get_viable_indices, Reach, TargetSelectionStrategy};
 
 pub(crate) struct TargetWeakestStrategy<A> {
     pub(crate) aspect: A,
@@ -17,7 +17,7 @@ pub(crate) trait SortAspect {
     type KeyType: Ord;
     fn key(&self, target: &Combatant) -> Self::KeyType;
     fn cmp(&self, a: &Combatant, b: &Combatant) -> Ordering {
-        return self.key(a).cmp(&self.key(b));
+        self.key(a).cmp(&self.key(b))
     }
 }
 
@@ -26,7 +26,7 @@ pub(crate) struct HpAspect;
 impl SortAspect for HpAspect {
     type KeyType = u32;
     fn key(&self, target: &Combatant) -> Self::KeyType {
-        return target.hp();
+        target.hp()
     }
 }
 
@@ -35,7 +35,7 @@ pub(crate) struct AcAspect;
 impl SortAspect for AcAspect {
     type KeyType = i16;
     fn key(&self, target: &Combatant) -> Self::KeyType {
-        return target.ac();
+        target.ac()
     }
 }
 
@@ -46,11 +46,12 @@ where
     fn select_single_target(
         &self,
         targets: &[IntMutCombatant],
+        reach: Reach,
     ) -> Option<IntMutCombatant> {
-        targets
+        get_viable_indices(targets, reach)
             .into_iter()
-            .filter(|target| target.borrow().is_conscious())
-            .min_by_key(|&target| target.borrow().hp())
+            .map(|idx| &targets[idx])
+            .min_by(|a, b| self.aspect.cmp(&a.borrow(), &b.borrow()))
             .cloned()
     }
 
@@ -58,11 +59,11 @@ where
         &self,
         targets: &[IntMutCombatant],
         max_targets: usize,
+        reach: Reach,
     ) -> Vec<IntMutCombatant> {
-        let mut targets_to_sort: Vec<_> = targets
-            .iter()
-            .filter(|target| target.borrow().is_conscious())
-            .cloned()
+        let mut targets_to_sort: Vec<_> = get_viable_indices(targets, reach)
+            .into_iter()
+            .map(|idx| targets[idx].clone())
             .collect();
         targets_to_sort.sort_by(|a, b| self.aspect.cmp(&a.borrow(), &b.borrow()));
         targets_to_sort.into_iter().take(max_targets).collect()
@@ -79,7 +80,7 @@ mod test {
         combat::action_selection::ActionSelection,
         combatant::{combatant::Combatant, defences::save::SaveModifiers},
         targeting::{
-            strategy::TargetSelectionStrategy,
+            strategy::{Reach, TargetSelectionStrategy},
             weakest::{AcAspect, HpAspect, TargetWeakestStrategy},
         },
     };
@@ -89,7 +90,6 @@ mod test {
     #[test]
     fn select_weakest_hp_discounting_unconscious() {
         let mut targets: Vec<_> = (0..5)
-            .into_iter()
             .map(|hp| {
                 Rc::new(RefCell::new(Combatant::new(
                     hp,
@@ -103,7 +103,7 @@ mod test {
         targets.shuffle(&mut thread_rng());
 
         let sut = TargetWeakestStrategy { aspect: HpAspect };
-        let selected = sut.select_single_target(&targets);
+        let selected = sut.select_single_target(&targets, Reach::MELEE);
 
         assert_eq!(selected.unwrap().borrow().hp(), 1);
     }
@@ -111,7 +111,6 @@ mod test {
     #[test]
     fn select_multiple_weakest_ac() {
         let mut targets: Vec<_> = (1..5)
-            .into_iter()
             .map(|ac| {
                 Rc::new(RefCell::new(Combatant::new(
                     42,
@@ -126,10 +125,10 @@ mod test {
         targets.shuffle(&mut thread_rng());
 
         let sut = TargetWeakestStrategy { aspect: AcAspect };
-        let selected = sut.select_multiple_targets(&targets, 2);
+        let selected = sut.select_multiple_targets(&targets, 2, Reach::MELEE);
 
         assert_eq!(2, selected.len());
-        assert_eq!(selected.get(0).unwrap().borrow().ac(), 1);
+        assert_eq!(selected.first().unwrap().borrow().ac(), 1);
         assert_eq!(selected.get(1).unwrap().borrow().ac(), 2);
     }
 
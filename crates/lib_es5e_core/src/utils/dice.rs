@@ -32,6 +32,39 @@ impl Die {
             Die::D20 => 20,
         }
     }
+
+    pub fn from_sides(sides: u32) -> Option<Die> {
+        match sides {
+            2 => Some(Die::D2),
+            4 => Some(Die::D4),
+            6 => Some(Die::D6),
+            8 => Some(Die::D8),
+            10 => Some(Die::D10),
+            12 => Some(Die::D12),
+            20 => Some(Die::D20),
+            _ => None,
+        }
+    }
+}
+
+/// How a d20 is rolled: once, or twice keeping the higher or the lower result
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RollMode {
+    #[default]
+    Normal,
+    Advantage,
+    Disadvantage,
+}
+
+impl Rollable<u32> for RollMode {
+    fn roll(&self) -> u32 {
+        let roll = Die::D20.roll();
+        match self {
+            RollMode::Normal => roll,
+            RollMode::Advantage => roll.max(Die::D20.roll()),
+            RollMode::Disadvantage => roll.min(Die::D20.roll()),
+        }
+    }
 }
 
 pub fn beats_dc<T: std::cmp::PartialOrd>(roll: T, dc: T) -> bool {
@@ -39,7 +72,7 @@ pub fn beats_dc<T: std::cmp::PartialOrd>(roll: T, dc: T) -> bool {
 }
 
 pub fn is_natural_20(val: u32) -> bool {
-    return val == Die::D20.sides();
+    val == Die::D20.sides()
 }
 
 #[cfg(test)]
@@ -1,6 +1,14 @@
-use std::{cmp, str::FromStr};
+use std::{
+    cmp,
+    fmt::{self, Display},
+    str::FromStr,
+};
 
-use super::{dice::Die, rollable::roll_sum};
+use super::{
+    dice::Die,
+    probability::{mean_sum, Meanable},
+    rollable::roll_sum,
+};
 
 #[derive(Clone, Debug, Default)]
 pub struct DiceRoll {
@@ -21,43 +29,109 @@ impl DiceRoll {
     pub fn without_mod(&self) -> u32 {
         roll_sum(&self.dice)
     }
+
+    /// Highest total the roll can come up with, including the modifier
+    pub fn max_with_mod(&self) -> u32 {
+        cmp::max(0, self.max_without_mod() as i32 + self.modifier as i32) as u32
+    }
+
+    pub fn max_without_mod(&self) -> u32 {
+        self.dice.iter().map(Die::sides).sum()
+    }
+
+    /// Roll of the dice of both rolls, adding up their modifiers
+    pub fn plus(&self, other: &DiceRoll) -> DiceRoll {
+        Self {
+            dice: self.dice.iter().chain(&other.dice).copied().collect(),
+            modifier: self.modifier + other.modifier,
+        }
+    }
+}
+
+impl Meanable for DiceRoll {
+    fn mean(&self) -> f32 {
+        mean_sum(&self.dice) + self.modifier as f32
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct ParseDiceRoll;
 
+impl Display for ParseDiceRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid dice roll, expected e.g. 2d6+3")
+    }
+}
+
 impl FromStr for DiceRoll {
     type Err = ParseDiceRoll;
+
+    /// Parses sums of dice and modifiers such as "2d10 + 1d8 + 6"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let normalised = s.replace(" ", "").replace("d", "D");
-        let mut components = normalised.split(&['+', '-']); // TODO: handle minus
-        let dice = components.next();
-
-        let mut dice_split_iterator = dice.unwrap().split("D");
-        let nr_dice = dice_split_iterator
-            .next()
-            .unwrap()
-            .parse::<usize>()
-            .unwrap();
-        let die_type = match dice_split_iterator.next().unwrap() {
-            "2" => Some(Die::D2),
-            "4" => Some(Die::D4),
-            "6" => Some(Die::D6),
-            "8" => Some(Die::D8),
-            "10" => Some(Die::D10),
-            "12" => Some(Die::D12),
-            "20" => Some(Die::D20),
-            _ => None,
-        };
-        let modifier: i16 = components.next().unwrap_or("0").parse().unwrap();
-        let signed_modifier = if normalised.contains('-') {
-            -modifier
-        } else {
-            modifier
-        };
-        return Ok(Self {
-            dice: vec![die_type.unwrap(); nr_dice],
-            modifier: signed_modifier,
-        });
+        let normalised = s.replace(' ', "").to_lowercase();
+        let mut dice = vec![];
+        let mut modifier: i16 = 0;
+        for term in signed_terms(&normalised) {
+            let (negative, term) = match term.strip_prefix('-') {
+                Some(term) => (true, term),
+                None => (false, term.strip_prefix('+').unwrap_or(term)),
+            };
+            match term.split_once('d') {
+                Some(_) if negative => return Err(ParseDiceRoll),
+                Some((nr_dice, sides)) => {
+                    let nr_dice: usize = nr_dice.parse().map_err(|_| ParseDiceRoll)?;
+                    let die = sides
+                        .parse()
+                        .ok()
+                        .and_then(Die::from_sides)
+                        .ok_or(ParseDiceRoll)?;
+                    dice.extend(vec![die; nr_dice]);
+                }
+                None => {
+                    let value: i16 = term.parse().map_err(|_| ParseDiceRoll)?;
+                    modifier += if negative { -value } else { value };
+                }
+            }
+        }
+        Ok(Self { dice, modifier })
+    }
+}
+
+/// Terms of a sum together with their sign, e.g. "2d6", "+1d4" and "-1"
+fn signed_terms(s: &str) -> Vec<&str> {
+    let mut terms = vec![];
+    let mut start = 0;
+    for (idx, c) in s.char_indices().skip(1) {
+        if c == '+' || c == '-' {
+            terms.push(&s[start..idx]);
+            start = idx;
+        }
+    }
+    terms.push(&s[start..]);
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::utils::probability::Meanable;
+
+    use super::{DiceRoll, ParseDiceRoll};
+
+    #[test]
+    fn parses_dice_with_modifier() {
+        assert_eq!(DiceRoll::from_str("2d6+3").unwrap().mean(), 10.0);
+        assert_eq!(DiceRoll::from_str("1D8 - 1").unwrap().mean(), 3.5);
+        assert_eq!(DiceRoll::from_str("3d4").unwrap().mean(), 7.5);
+        assert_eq!(DiceRoll::from_str("2d10 + 1d8 + 6").unwrap().mean(), 21.5);
+        assert_eq!(DiceRoll::from_str("1").unwrap().mean(), 1.0);
+    }
+
+    #[test]
+    fn rejects_invalid_dice() {
+        for invalid in ["", "2d", "d6", "2d7", "2x6", "1d6+", "1d6-1d4"] {
+            assert_eq!(DiceRoll::from_str(invalid).err(), Some(ParseDiceRoll));
+        }
     }
 }
@@ -6,7 +6,7 @@ pub trait Meanable {
 
 impl Meanable for Die {
     fn mean(&self) -> f32 {
-        return (self.sides() + 1) as f32 / 2.0;
+        (self.sides() + 1) as f32 / 2.0
     }
 }
 
@@ -1,12 +1,19 @@
 use serde::{Deserialize, Serialize};
 
+/// Ability of a saving throw, also used for the ability of checks and attacks
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum SaveType {
+    #[serde(alias = "str")]
     STR,
+    #[serde(alias = "dex")]
     DEX,
+    #[serde(alias = "con")]
     CON,
+    #[serde(alias = "int")]
     INT,
+    #[serde(alias = "wis")]
     WIS,
+    #[serde(alias = "cha")]
     CHA,
 }
 
@@ -18,7 +25,7 @@ pub struct Save {
 
 impl Save {
     pub fn new(save_type: SaveType, dc: i16) -> Save {
-        return Self { save_type, dc };
+        Self { save_type, dc }
     }
 
     pub fn save_type(&self) -> &SaveType {